@@ -0,0 +1,233 @@
+use anyhow::Result;
+use arrow::array::{Array, FixedSizeListArray, Float32Array, Int32Array, StringArray};
+use arrow::datatypes::Schema;
+use arrow::record_batch::{RecordBatch, RecordBatchIterator};
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::{Connection, Table};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::chunk::embedding_input;
+use crate::crypto::reveal;
+use crate::hash::content_hash;
+use crate::metric::Metric;
+use crate::schema::{
+    cache_schema, cache_table_name, days_to_date, embedding_array, schema_dimension, schema_encrypted, schema_metric,
+    schema_model, schema_normalized, sql_string, CACHE_TABLE_NAME,
+};
+
+/// Keys per lookup query, to keep the filter expression a reasonable size
+const LOOKUP_BATCH: usize = 500;
+
+/// Cache key for a chunk: the model id is part of the hash so switching models never reuses vectors
+pub fn cache_key(model_id: &str, text: &str) -> String {
+    content_hash(&format!("{}\n{}", model_id, text))
+}
+
+/// Embeddings of previously seen chunk texts, stored in their own LanceDB table.
+///
+/// Unlike the chunk table it is never dropped, so a rebuild or a chunking change
+/// only pays for text that was never embedded before. Each embedding dimension has its own
+/// table, so switching between models of different sizes keeps the vectors of both.
+pub struct EmbeddingCache {
+    table: Table,
+    embedding_dim: usize,
+}
+
+impl EmbeddingCache {
+    /// Open the cache table for `embedding_dim`, creating it on first use
+    pub async fn open(db: &Connection, embedding_dim: usize) -> Result<Self> {
+        let tables = db.table_names().execute().await?;
+        let name = cache_table_name(embedding_dim);
+
+        if tables.contains(&name) {
+            let table = db.open_table(&name).execute().await?;
+            return Ok(Self { table, embedding_dim });
+        }
+        // The single cache of older versions stays in use for the dimension it was built with
+        if tables.iter().any(|table| table == CACHE_TABLE_NAME) {
+            let table = db.open_table(CACHE_TABLE_NAME).execute().await?;
+            if schema_dimension(&table.schema().await?) == Some(embedding_dim) {
+                return Ok(Self { table, embedding_dim });
+            }
+        }
+
+        let table = db
+            .create_empty_table(&name, cache_schema(embedding_dim))
+            .execute()
+            .await?;
+        Ok(Self { table, embedding_dim })
+    }
+
+    /// Look up cached vectors for the given keys; missing keys are simply absent
+    pub async fn get(&self, keys: &[String]) -> Result<HashMap<String, Vec<f32>>> {
+        let mut found = HashMap::new();
+
+        for key_batch in keys.chunks(LOOKUP_BATCH) {
+            let quoted: Vec<String> = key_batch.iter().map(|k| sql_string(k)).collect();
+            let batches: Vec<RecordBatch> = self.table
+                .query()
+                .only_if(format!("key IN ({})", quoted.join(", ")))
+                .execute()
+                .await?
+                .try_collect()
+                .await?;
+
+            for batch in batches {
+                let keys = batch.column_by_name("key")
+                    .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                    .ok_or_else(|| anyhow::anyhow!("Missing key column"))?;
+                let embeddings = batch.column_by_name("embedding")
+                    .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                    .ok_or_else(|| anyhow::anyhow!("Missing embedding column"))?;
+
+                for i in 0..batch.num_rows() {
+                    let vector = embeddings.value(i);
+                    let vector = vector.as_any()
+                        .downcast_ref::<Float32Array>()
+                        .ok_or_else(|| anyhow::anyhow!("Failed to cast embedding column"))?;
+                    found.insert(keys.value(i).to_string(), vector.values().to_vec());
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Store new vectors; keys that are already cached are left untouched
+    pub async fn insert(&self, entries: Vec<(String, Vec<f32>)>) -> Result<()> {
+        // Identical chunks within one run would otherwise make the merge ambiguous
+        let entries: HashMap<String, Vec<f32>> = entries.into_iter().collect();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let (keys, embeddings): (Vec<String>, Vec<Vec<f32>>) = entries.into_iter().unzip();
+        let schema = cache_schema(self.embedding_dim);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(keys)) as Arc<dyn Array>,
+                Arc::new(embedding_array(embeddings, self.embedding_dim)) as Arc<dyn Array>,
+            ],
+        )?;
+
+        let mut merge = self.table.merge_insert(&["key"]);
+        merge.when_not_matched_insert_all();
+        merge
+            .execute(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Copy the vectors of an existing chunk table into the cache, keyed by the text each chunk
+    /// was embedded from, so a rebuild with the same model only embeds text that is new.
+    ///
+    /// Returns how many chunks were read, or 0 if the table's vectors can't be reused.
+    pub async fn seed_from(&self, table: &Table, model_id: &str) -> Result<usize> {
+        let schema = table.schema().await?;
+        if !is_reusable(&schema, model_id, self.embedding_dim) {
+            return Ok(0);
+        }
+
+        let encrypted = schema_encrypted(&schema);
+        let mut seeded = 0;
+        let mut batches = table
+            .query()
+            .select(Select::columns(&["path", "chunk_index", "date", "section", "content", "embedding"]))
+            .execute()
+            .await?;
+        while let Some(batch) = batches.try_next().await? {
+            let paths = batch.column_by_name("path")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| anyhow::anyhow!("Missing path column"))?;
+            let chunk_indices = batch.column_by_name("chunk_index")
+                .and_then(|c| c.as_any().downcast_ref::<Int32Array>())
+                .ok_or_else(|| anyhow::anyhow!("Missing chunk_index column"))?;
+            let dates = batch.column_by_name("date")
+                .and_then(|c| c.as_any().downcast_ref::<Int32Array>())
+                .ok_or_else(|| anyhow::anyhow!("Missing date column"))?;
+            let sections = batch.column_by_name("section")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| anyhow::anyhow!("Missing section column"))?;
+            let contents = batch.column_by_name("content")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| anyhow::anyhow!("Missing content column"))?;
+            let embeddings = batch.column_by_name("embedding")
+                .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                .ok_or_else(|| anyhow::anyhow!("Missing embedding column"))?;
+
+            let mut entries = Vec::with_capacity(batch.num_rows());
+            for i in 0..batch.num_rows() {
+                let text = reveal(contents.value(i), encrypted, paths.value(i), chunk_indices.value(i))?;
+                let input = embedding_input(days_to_date(dates.value(i)), sections.value(i), &text);
+                let vector = embeddings.value(i);
+                let vector = vector.as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| anyhow::anyhow!("Failed to cast embedding column"))?;
+                entries.push((cache_key(model_id, &input), vector.values().to_vec()));
+            }
+            seeded += entries.len();
+            self.insert(entries).await?;
+        }
+
+        Ok(seeded)
+    }
+}
+
+/// Whether a chunk table holds raw vectors from `model_id` for breadcrumbed chunk text, as the cache does
+fn is_reusable(schema: &Schema, model_id: &str, embedding_dim: usize) -> bool {
+    // Breadcrumbs were embedded from the version that added the section column
+    let embeds_breadcrumbs = schema.field_with_name("section").is_ok();
+    // Vectors normalized for cosine or dot only equal the model's output if the model normalizes itself
+    let raw = !schema_metric(schema).unwrap_or(Metric::L2).normalizes() || schema_normalized(schema) == Some(true);
+    embeds_breadcrumbs
+        && raw
+        && schema_model(schema) == Some(model_id)
+        && schema_dimension(schema) == Some(embedding_dim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_depends_on_model() {
+        assert_eq!(cache_key("model-a", "text"), cache_key("model-a", "text"));
+        assert_ne!(cache_key("model-a", "text"), cache_key("model-b", "text"));
+        assert_ne!(cache_key("model-a", "text"), cache_key("model-a", "text "));
+    }
+
+    #[test]
+    fn test_only_raw_vectors_of_the_same_model_are_reusable() {
+        use crate::schema::documents_schema;
+
+        let schema = |model, normalized, metric| documents_schema(model, 4, normalized, metric, &[], false, false);
+        assert!(is_reusable(&schema("model-a", true, Metric::Cosine), "model-a", 4));
+        assert!(is_reusable(&schema("model-a", false, Metric::L2), "model-a", 4));
+        assert!(!is_reusable(&schema("model-a", false, Metric::Cosine), "model-a", 4));
+        assert!(!is_reusable(&schema("model-b", true, Metric::Cosine), "model-a", 4));
+        assert!(!is_reusable(&schema("model-a", true, Metric::Cosine), "model-a", 8));
+    }
+
+    #[tokio::test]
+    async fn test_each_dimension_keeps_its_own_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = lancedb::connect(tmp.path().to_str().unwrap()).execute().await.unwrap();
+        let key = cache_key("model-a", "text");
+
+        let small = EmbeddingCache::open(&db, 2).await.unwrap();
+        small.insert(vec![(key.clone(), vec![1.0, 0.0])]).await.unwrap();
+        let large = EmbeddingCache::open(&db, 3).await.unwrap();
+        large.insert(vec![(key.clone(), vec![0.0, 1.0, 0.0])]).await.unwrap();
+
+        // Switching back finds the smaller vectors where they were
+        let small = EmbeddingCache::open(&db, 2).await.unwrap();
+        assert_eq!(small.get(std::slice::from_ref(&key)).await.unwrap()[&key], vec![1.0, 0.0]);
+        let mut tables = db.table_names().execute().await.unwrap();
+        tables.sort();
+        assert_eq!(tables, vec![cache_table_name(2), cache_table_name(3)]);
+    }
+}
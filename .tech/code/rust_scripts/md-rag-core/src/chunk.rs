@@ -0,0 +1,333 @@
+use chrono::NaiveDate;
+
+/// Chunk length in model tokens unless `--chunk-tokens` or `.mdrag.toml` says otherwise
+pub const DEFAULT_CHUNK_TOKENS: usize = 350;
+
+/// Tokens repeated from the end of one chunk at the start of the next
+pub const DEFAULT_CHUNK_OVERLAP: usize = 50;
+
+/// How large chunks are, measured in the embedding model's tokens
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkOptions {
+    pub max_tokens: usize,
+    pub overlap_tokens: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self { max_tokens: DEFAULT_CHUNK_TOKENS, overlap_tokens: DEFAULT_CHUNK_OVERLAP }
+    }
+}
+
+/// A chunk of an entry and the headings it sits under
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Heading path such as `V. End-of-Day Reflection > Key learnings`; empty before the first heading
+    pub section: String,
+    pub text: String,
+    /// First and last line (1-based) of the source the chunk came from, if they could be found
+    pub lines: Option<(usize, usize)>,
+}
+
+impl Chunk {
+    /// What gets embedded: the chunk prefixed with where it sits, so e.g. a bare
+    /// bullet list still carries the date and section it belongs to
+    pub fn embedding_text(&self, date: NaiveDate) -> String {
+        embedding_input(date, &self.section, &self.text)
+    }
+}
+
+/// The text embedded for a chunk, rebuilt from its stored date, section and content
+pub fn embedding_input(date: NaiveDate, section: &str, text: &str) -> String {
+    format!("{}\n{}", breadcrumb(date, section), text)
+}
+
+/// `2025-07-21 > V. End-of-Day Reflection`, or just the date outside any section
+pub fn breadcrumb(date: NaiveDate, section: &str) -> String {
+    if section.is_empty() {
+        date.to_string()
+    } else {
+        format!("{} > {}", date, section)
+    }
+}
+
+/// A piece of text that is never split further, with its token count
+#[derive(Clone)]
+struct Unit {
+    text: String,
+    tokens: usize,
+}
+
+/// The marker of a line opening or closing a fenced code block: ```` ``` ```` or `~~~`, possibly indented
+pub fn fence_marker(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+    ["```", "~~~"].into_iter().find(|marker| line.starts_with(marker))
+}
+
+/// Follows fenced code blocks through the lines of a document
+#[derive(Debug, Default)]
+pub struct FenceState {
+    open: Option<&'static str>,
+}
+
+impl FenceState {
+    /// Take the next line; true if it belongs to a code block, its fence lines included
+    pub fn in_code(&mut self, line: &str) -> bool {
+        match (self.open, fence_marker(line)) {
+            (None, Some(marker)) => {
+                self.open = Some(marker);
+                true
+            }
+            (Some(open), Some(marker)) if open == marker => {
+                self.open = None;
+                true
+            }
+            (open, _) => open.is_some(),
+        }
+    }
+}
+
+/// Pack `text` into chunks of at most `max_tokens`, starting each chunk with up to
+/// `overlap_tokens` from the end of the previous one.
+///
+/// Breaks fall between lines where possible, then between sentences, then between words,
+/// so a thought is only cut mid-sentence when a single sentence is longer than a chunk.
+/// Fenced code blocks are never broken up when they fit a chunk; longer ones are cut between
+/// their lines, each piece wrapped in its own fences.
+pub fn split_by_tokens(text: &str, options: ChunkOptions, count_tokens: &dyn Fn(&str) -> usize) -> Vec<String> {
+    let mut units = Vec::new();
+    let mut fence = FenceState::default();
+    let mut block: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if fence.in_code(line) {
+            block.push(line);
+            // The block is complete at its closing fence
+            if block.len() > 1 && fence_marker(line).is_some() {
+                push_code_block(&mut units, &std::mem::take(&mut block), options.max_tokens, count_tokens);
+            }
+            continue;
+        }
+        push_units(&mut units, line, options.max_tokens, count_tokens);
+        if let Some(last) = units.last_mut() {
+            last.text.push('\n');
+        }
+    }
+    // A block left open at the end of the text
+    if !block.is_empty() {
+        push_code_block(&mut units, &block, options.max_tokens, count_tokens);
+    }
+
+    let mut chunks = Vec::new();
+    let mut current: Vec<Unit> = Vec::new();
+    let mut current_tokens = 0;
+
+    for unit in units {
+        if current_tokens + unit.tokens > options.max_tokens && !current.is_empty() {
+            chunks.push(join(&current));
+
+            // Carry the tail of this chunk into the next one, as long as the new unit still fits
+            let mut kept = Vec::new();
+            let mut kept_tokens = 0;
+            for previous in current.iter().rev() {
+                if kept_tokens + previous.tokens > options.overlap_tokens
+                    || kept_tokens + previous.tokens + unit.tokens > options.max_tokens
+                {
+                    break;
+                }
+                kept_tokens += previous.tokens;
+                kept.push(previous.clone());
+            }
+            kept.reverse();
+            current = kept;
+            current_tokens = kept_tokens;
+        }
+
+        current_tokens += unit.tokens;
+        current.push(unit);
+    }
+
+    if !current.is_empty() {
+        chunks.push(join(&current));
+    }
+
+    chunks.retain(|chunk| !chunk.is_empty());
+    chunks
+}
+
+/// Split a line into units no larger than `max_tokens`: the whole line, its sentences, or runs of words
+fn push_units(units: &mut Vec<Unit>, line: &str, max_tokens: usize, count_tokens: &dyn Fn(&str) -> usize) {
+    let tokens = count_tokens(line);
+    if tokens <= max_tokens {
+        units.push(Unit { text: line.to_string(), tokens });
+        return;
+    }
+
+    for sentence in sentences(line) {
+        let tokens = count_tokens(sentence);
+        if tokens <= max_tokens {
+            units.push(Unit { text: format!("{} ", sentence), tokens });
+            continue;
+        }
+
+        // One enormous sentence: fall back to packing words
+        let mut run = String::new();
+        for word in sentence.split_whitespace() {
+            let candidate = if run.is_empty() { word.to_string() } else { format!("{} {}", run, word) };
+            if !run.is_empty() && count_tokens(&candidate) > max_tokens {
+                let tokens = count_tokens(&run);
+                units.push(Unit { text: format!("{} ", run), tokens });
+                run = word.to_string();
+            } else {
+                run = candidate;
+            }
+        }
+        if !run.is_empty() {
+            let tokens = count_tokens(&run);
+            units.push(Unit { text: format!("{} ", run), tokens });
+        }
+    }
+}
+
+/// A fenced code block (fence lines included) as one unit, or, when it's longer than
+/// `max_tokens`, as runs of its lines that each get the block's opening and closing fence
+fn push_code_block(units: &mut Vec<Unit>, block: &[&str], max_tokens: usize, count_tokens: &dyn Fn(&str) -> usize) {
+    let text = format!("{}\n", block.join("\n"));
+    let tokens = count_tokens(&text);
+    if tokens <= max_tokens || block.len() < 3 {
+        units.push(Unit { text, tokens });
+        return;
+    }
+
+    let opening = block[0];
+    let (body, closing) = match fence_marker(block[block.len() - 1]) {
+        Some(_) => (&block[1..block.len() - 1], block[block.len() - 1].to_string()),
+        None => (&block[1..], fence_marker(opening).unwrap_or("```").to_string()),
+    };
+    let fence_tokens = count_tokens(opening) + count_tokens(&closing);
+    let mut run: Vec<&str> = Vec::new();
+    let mut run_tokens = fence_tokens;
+    for line in body {
+        let tokens = count_tokens(line);
+        if !run.is_empty() && run_tokens + tokens > max_tokens {
+            units.push(Unit { text: format!("{}\n{}\n{}\n", opening, run.join("\n"), closing), tokens: run_tokens });
+            run.clear();
+            run_tokens = fence_tokens;
+        }
+        run.push(line);
+        run_tokens += tokens;
+    }
+    if !run.is_empty() {
+        units.push(Unit { text: format!("{}\n{}\n{}\n", opening, run.join("\n"), closing), tokens: run_tokens });
+    }
+}
+
+/// Sentences in `line`, each keeping its closing punctuation
+fn sentences(line: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let bytes = line.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if matches!(b, b'.' | b'!' | b'?') && bytes.get(i + 1) == Some(&b' ') {
+            sentences.push(line[start..=i].trim());
+            start = i + 1;
+        }
+    }
+    sentences.push(line[start..].trim());
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Find the lines of `source` that `text` was cut from, starting the search at line index `from`.
+///
+/// Returns 0-based line indexes. Chunks come from cleaned text, so each chunk line is
+/// matched, in order, to the next source line containing it: a chunk may start or end
+/// partway through a source line, and blank or stripped lines are skipped over.
+pub fn locate_lines(source: &[&str], text: &str, from: usize) -> Option<(usize, usize)> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next()?;
+
+    let start = (from..source.len()).find(|&i| source[i].contains(first))?;
+    let mut end = start;
+    for line in lines {
+        end = (end + 1..source.len()).find(|&i| source[i].contains(line))?;
+    }
+    Some((start, end))
+}
+
+fn join(units: &[Unit]) -> String {
+    units.iter().map(|u| u.text.as_str()).collect::<String>().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    #[test]
+    fn test_chunks_respect_limit_and_overlap() {
+        let text = "one two three four.\nfive six seven eight.\nnine ten eleven twelve.";
+        let options = ChunkOptions { max_tokens: 8, overlap_tokens: 4 };
+        let chunks = split_by_tokens(text, options, &words);
+
+        assert_eq!(chunks, vec![
+            "one two three four.\nfive six seven eight.",
+            "five six seven eight.\nnine ten eleven twelve.",
+        ]);
+        assert!(chunks.iter().all(|c| words(c) <= 8));
+    }
+
+    #[test]
+    fn test_breadcrumb() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap();
+        assert_eq!(breadcrumb(date, "V. End-of-Day Reflection"), "2025-07-21 > V. End-of-Day Reflection");
+        assert_eq!(breadcrumb(date, ""), "2025-07-21");
+
+        let chunk = Chunk { section: "Notes".to_string(), text: "- tea".to_string(), lines: None };
+        assert_eq!(chunk.embedding_text(date), "2025-07-21 > Notes\n- tea");
+    }
+
+    #[test]
+    fn test_locate_lines() {
+        let source = vec!["# Title", "", "## Notes", "First thought. Second thought.", "", "- tea", "- coffee"];
+        assert_eq!(locate_lines(&source, "## Notes\nFirst thought.", 0), Some((2, 3)));
+        assert_eq!(locate_lines(&source, "Second thought.\n\n- tea", 3), Some((3, 5)));
+        assert_eq!(locate_lines(&source, "- coffee", 0), Some((6, 6)));
+        assert_eq!(locate_lines(&source, "not here", 0), None);
+    }
+
+    #[test]
+    fn test_code_blocks_stay_whole() {
+        let text = "Before the code.\n```rust\nlet a = 1;\n## not a heading\nlet b = 2;\n```\nAfter it.";
+        let options = ChunkOptions { max_tokens: 16, overlap_tokens: 0 };
+        let chunks = split_by_tokens(text, options, &words);
+        assert_eq!(chunks, vec!["Before the code.", "```rust\nlet a = 1;\n## not a heading\nlet b = 2;\n```\nAfter it."]);
+
+        // Too long for one chunk: cut between lines, every piece fenced
+        let options = ChunkOptions { max_tokens: 8, overlap_tokens: 0 };
+        let chunks = split_by_tokens(text, options, &words);
+        assert_eq!(chunks, vec![
+            "Before the code.",
+            "```rust\nlet a = 1;\n```",
+            "```rust\n## not a heading\n```",
+            "```rust\nlet b = 2;\n```\nAfter it.",
+        ]);
+
+        let mut fence = FenceState::default();
+        let in_code: Vec<bool> = ["a", "~~~", "```", "~~~", "b"].iter().map(|line| fence.in_code(line)).collect();
+        assert_eq!(in_code, vec![false, true, true, true, false]);
+    }
+
+    #[test]
+    fn test_long_lines_split_at_sentences_then_words() {
+        let text = "Short one. This sentence is far too long to fit anywhere.";
+        let options = ChunkOptions { max_tokens: 4, overlap_tokens: 0 };
+        let chunks = split_by_tokens(text, options, &words);
+
+        assert_eq!(chunks[0], "Short one.");
+        assert!(chunks.iter().all(|c| words(c) <= 4));
+        assert_eq!(chunks.join(" "), text);
+    }
+}
@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::path_date::PathDatePattern;
+use crate::path_filter::PathFilter;
+use crate::scan::EntryDates;
+use crate::search::SortOrder;
+use crate::template_filter::template_lines;
+
+/// Config file looked up in the working directory and each of its parents
+pub const CONFIG_FILE_NAME: &str = ".mdrag.toml";
+
+/// Overrides where per-user state such as saved searches is kept
+pub const USER_DIR_ENV: &str = "MDRAG_CONFIG_DIR";
+
+/// Settings from `.mdrag.toml`; anything given on the command line or in the environment wins
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub journal_dir: Option<PathBuf>,
+    pub lance_dir: Option<PathBuf>,
+    pub table: Option<String>,
+    /// `local` or `openai`
+    pub backend: Option<String>,
+    /// Embedding model for `mdrag index`; searches always use the model the index was built with
+    pub model: Option<String>,
+    /// Maximum model tokens per chunk
+    pub chunk_tokens: Option<usize>,
+    /// Tokens shared between consecutive chunks
+    pub chunk_overlap: Option<usize>,
+    /// Frontmatter keys stored as filterable columns
+    pub metadata_fields: Option<Vec<String>>,
+    /// Where entries without a frontmatter date take it from in their path: a strftime format or a regex
+    pub path_date: Option<String>,
+    /// strftime formats the frontmatter `date` may also be in, e.g. `%d/%m/%Y`; `YYYY-MM-DD` always works
+    pub date_formats: Vec<String>,
+    /// `.gitignore`-style patterns of files to leave out, on top of `.ragignore`
+    pub exclude: Vec<String>,
+    /// Globs narrowing indexing to matching files
+    pub include: Vec<String>,
+    /// Encrypt chunk text at rest with the content key (`$MDRAG_CONTENT_KEY` or the OS keyring)
+    pub encrypt_content: Option<bool>,
+    pub template: TemplateConfig,
+    pub search: SearchDefaults,
+    pub ask: AskDefaults,
+    pub new: NewDefaults,
+}
+
+/// Journal template boilerplate to strip before indexing. Named templates are tables such as
+/// `[template.weekly]`, or come from `file` or `template_files`; the patterns of all of them are
+/// applied to every entry
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TemplateConfig {
+    /// Keep the patterns of the journal template this repo was built for
+    pub builtin: bool,
+    /// YAML (`.yaml`/`.yml`) or TOML file of more named templates, each a table of
+    /// `boilerplate_headers` and `empty_sections`
+    pub file: Option<PathBuf>,
+    /// Journal templates such as `template/daily.md`: a line of one left unchanged in an entry is
+    /// stripped, so only what was written into the template is indexed. Each becomes a named
+    /// template after its file stem
+    pub template_files: Vec<PathBuf>,
+    /// Headers whose whole section is template (e.g. `## Daily Checklist`)
+    pub boilerplate_headers: Vec<String>,
+    /// Lines that mark a section as an unfilled template when it has at most one other line, for
+    /// sections the structural check misses; compared ignoring indentation and blank lines
+    pub empty_sections: Vec<String>,
+    /// Lines stripped wherever they appear, compared without leading and trailing whitespace
+    pub boilerplate_lines: Vec<String>,
+    /// Comments and metadata removed before anything else, as `[template.scrub]`; `scrub` is not
+    /// a template name
+    pub scrub: ScrubConfig,
+    /// By name; those in `.mdrag.toml` win over those of the same name in `file`
+    #[serde(flatten)]
+    pub templates: BTreeMap<String, TemplatePatterns>,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            file: None,
+            template_files: Vec::new(),
+            boilerplate_headers: Vec::new(),
+            empty_sections: Vec::new(),
+            boilerplate_lines: Vec::new(),
+            scrub: ScrubConfig::default(),
+            templates: BTreeMap::new(),
+        }
+    }
+}
+
+/// What `scrub::scrub` removes from entries before they are embedded or shown; all but code by default
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScrubConfig {
+    /// `<!-- ... -->`
+    pub html_comments: bool,
+    /// Obsidian's `%% ... %%`
+    pub obsidian_comments: bool,
+    /// ```dataview and ```dataviewjs blocks, and `key:: value` inline field lines
+    pub dataview: bool,
+    /// `---` blocks of YAML keys in the body, like a second frontmatter
+    pub frontmatter: bool,
+    /// Every fenced code block, so code isn't embedded; off by default
+    pub code_blocks: bool,
+}
+
+impl Default for ScrubConfig {
+    fn default() -> Self {
+        Self { html_comments: true, obsidian_comments: true, dataview: true, frontmatter: true, code_blocks: false }
+    }
+}
+
+/// The boilerplate of one named template
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplatePatterns {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub boilerplate_headers: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub empty_sections: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub boilerplate_lines: Vec<String>,
+}
+
+/// Defaults for `mdrag search` options
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchDefaults {
+    pub num_results: Option<usize>,
+    pub hybrid: bool,
+    pub rerank: bool,
+    pub group_by_doc: bool,
+    pub expand: Option<usize>,
+    pub full_doc: bool,
+    pub recency_halflife: Option<f32>,
+    pub min_score: Option<f32>,
+    pub sort: Option<SortOrder>,
+    /// Log searches for `mdrag history`; `false` keeps no record
+    pub history: Option<bool>,
+}
+
+/// Defaults for `mdrag ask`. The API key only comes from the environment
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AskDefaults {
+    /// Base URL of an OpenAI-compatible chat API
+    pub url: Option<String>,
+    pub model: Option<String>,
+    pub num_results: Option<usize>,
+    /// Token budget for the journal excerpts in the prompt
+    pub max_tokens: Option<usize>,
+}
+
+/// Defaults for new entries from `mdrag new` and the MCP `create_entry` tool
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewDefaults {
+    /// Template file for new entries
+    pub template: Option<PathBuf>,
+    /// Frontmatter values every new entry starts with, e.g. `location = "Lisbon"`
+    pub fields: BTreeMap<String, toml::Value>,
+}
+
+impl NewDefaults {
+    /// The configured fields as frontmatter `key: value` pairs, strings unquoted
+    pub fn field_values(&self) -> Vec<(String, String)> {
+        self.fields
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    value => value.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
+impl Config {
+    /// Read a config file. Relative directories are taken relative to the file, not the working directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        let mut config: Config = toml::from_str(&text)
+            .with_context(|| format!("Invalid config {}", path.display()))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        config.journal_dir = config.journal_dir.map(|dir| base.join(dir));
+        config.lance_dir = config.lance_dir.map(|dir| base.join(dir));
+        config.new.template = config.new.template.map(|file| base.join(file));
+        config.template.file = config.template.file.map(|file| base.join(file));
+        if let Some(file) = &config.template.file {
+            for (name, patterns) in load_templates(file)? {
+                config.template.templates.entry(name).or_insert(patterns);
+            }
+        }
+        config.template.template_files = config.template.template_files.iter().map(|file| base.join(file)).collect();
+        for file in &config.template.template_files {
+            let text = fs::read_to_string(file)
+                .with_context(|| format!("Failed to read template {}", file.display()))?;
+            let name = file.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let patterns = config.template.templates.entry(name).or_default();
+            patterns.boilerplate_lines.extend(template_lines(&text));
+        }
+
+        Ok(config)
+    }
+
+    /// Files to index under `journal_dir`, with `exclude`/`include` from the command line added to the configured ones
+    pub fn path_filter(&self, journal_dir: &Path, exclude: &[String], include: &[String]) -> Result<PathFilter> {
+        let exclude = [self.exclude.as_slice(), exclude].concat();
+        let include = [self.include.as_slice(), include].concat();
+        PathFilter::new(journal_dir, &exclude, &include)
+    }
+
+    /// The configured path date pattern, or the built-in one
+    pub fn path_date(&self) -> Result<PathDatePattern> {
+        match &self.path_date {
+            Some(pattern) => pattern.parse().map_err(anyhow::Error::msg).context("Invalid path_date in config"),
+            None => Ok(PathDatePattern::default()),
+        }
+    }
+
+    /// How entries are dated: `date_formats` for the frontmatter, then `path_date`
+    pub fn entry_dates(&self) -> Result<EntryDates> {
+        Ok(EntryDates { formats: self.date_formats.clone(), path: self.path_date()? })
+    }
+}
+
+/// Named templates from a YAML or TOML file, by its extension
+fn load_templates(path: &Path) -> Result<BTreeMap<String, TemplatePatterns>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read templates {}", path.display()))?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(&text).with_context(|| format!("Invalid templates {}", path.display())),
+        _ => toml::from_str(&text).with_context(|| format!("Invalid templates {}", path.display())),
+    }
+}
+
+/// Per-user directory for state that belongs to the person rather than the journal:
+/// `$MDRAG_CONFIG_DIR`, else `$XDG_CONFIG_HOME/mdrag`, `~/.config/mdrag` or `%APPDATA%\mdrag`
+pub fn user_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    var(USER_DIR_ENV)
+        .or_else(|| var("XDG_CONFIG_HOME").map(|dir| dir.join("mdrag")))
+        .or_else(|| var("HOME").map(|home| home.join(".config").join("mdrag")))
+        .or_else(|| var("APPDATA").map(|dir| dir.join("mdrag")))
+}
+
+/// Find the nearest `.mdrag.toml` in `start` or one of its ancestors
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_discover() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let nested = root.join("journal/2025");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(root.join("template")).unwrap();
+        fs::write(root.join("template/daily.md"), "---\ndate: YYYY-MM-DD\n---\n# YYYY-MM-DD\n## Gratitude\n- \n\n**Mood:**\n").unwrap();
+        fs::write(root.join("templates.yaml"), "weekly:\n  boilerplate_headers: [\"## Ignored\"]\nmonthly:\n  empty_sections: [\"- Wins:\\n\"]\n").unwrap();
+        fs::write(root.join(CONFIG_FILE_NAME), r#"
+journal_dir = "journal"
+chunk_tokens = 256
+date_formats = ["%d/%m/%Y"]
+
+[template]
+boilerplate_headers = ["## Daily Checklist"]
+file = "templates.yaml"
+template_files = ["template/daily.md"]
+
+[template.weekly]
+boilerplate_headers = ["## Week in Review"]
+
+[template.scrub]
+dataview = false
+
+[search]
+num_results = 5
+hybrid = true
+
+[ask]
+model = "qwen2.5:7b"
+
+[new]
+template = "template/daily.md"
+fields = { location = "Lisbon", energy = 5 }
+"#).unwrap();
+
+        let path = discover(&nested).unwrap();
+        assert_eq!(path, root.join(CONFIG_FILE_NAME));
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.journal_dir, Some(root.join("journal")));
+        assert_eq!(config.lance_dir, None);
+        assert_eq!(config.chunk_tokens, Some(256));
+        assert_eq!(config.chunk_overlap, None);
+        assert_eq!(config.entry_dates().unwrap().formats, vec!["%d/%m/%Y"]);
+        assert_eq!(config.template.boilerplate_headers, vec!["## Daily Checklist"]);
+        assert!(config.template.builtin);
+        assert!(!config.template.scrub.dataview && config.template.scrub.html_comments);
+        assert_eq!(config.template.templates.keys().collect::<Vec<_>>(), vec!["daily", "monthly", "weekly"]);
+        assert_eq!(config.template.templates["daily"].boilerplate_lines, vec!["-", "**Mood:**"]);
+        assert_eq!(config.template.templates["weekly"].boilerplate_headers, vec!["## Week in Review"]);
+        assert_eq!(config.template.templates["monthly"].empty_sections, vec!["- Wins:\n"]);
+        assert_eq!(config.search.num_results, Some(5));
+        assert!(config.search.hybrid);
+        assert_eq!(config.ask.model.as_deref(), Some("qwen2.5:7b"));
+        assert_eq!(config.new.template, Some(root.join("template/daily.md")));
+        assert_eq!(
+            config.new.field_values(),
+            vec![("energy".to_string(), "5".to_string()), ("location".to_string(), "Lisbon".to_string())],
+        );
+
+        fs::write(root.join(CONFIG_FILE_NAME), "jornal_dir = \"typo\"\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+}
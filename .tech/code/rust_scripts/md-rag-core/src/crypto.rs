@@ -0,0 +1,191 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Holds the content key, base64-encoded; checked before the OS keyring
+pub const KEY_ENV: &str = "MDRAG_CONTENT_KEY";
+
+/// Service and user of the content key's entry in the OS keyring
+pub const KEYRING_SERVICE: &str = "mdrag";
+pub const KEYRING_USER: &str = "content-key";
+
+/// Starts an encrypted `content` value, followed by base64 of the nonce and ciphertext
+const ENCRYPTED_PREFIX: &str = "mdrag:aes-256-gcm:";
+
+/// Bytes of the random nonce stored in front of each ciphertext
+const NONCE_LEN: usize = 12;
+
+/// Encrypts and decrypts chunk text with AES-256-GCM
+pub struct ContentCipher {
+    cipher: Aes256Gcm,
+}
+
+impl ContentCipher {
+    /// From a base64-encoded 32-byte key
+    pub fn from_base64(key: &str) -> Result<Self> {
+        let bytes = BASE64.decode(key.trim()).context("Content key isn't valid base64")?;
+        let cipher = Aes256Gcm::new_from_slice(&bytes)
+            .map_err(|_| anyhow!("Content key must be 32 bytes, not {}", bytes.len()))?;
+        Ok(Self { cipher })
+    }
+
+    /// The key in `$MDRAG_CONTENT_KEY`, else the one in the OS keyring (builds with the `keyring` feature)
+    pub fn load() -> Result<Self> {
+        if let Ok(key) = std::env::var(KEY_ENV) && !key.trim().is_empty() {
+            return Self::from_base64(&key).with_context(|| format!("Invalid {}", KEY_ENV));
+        }
+        if let Some(key) = keyring_key()? {
+            return Self::from_base64(&key).context("Invalid content key in the OS keyring");
+        }
+        bail!("No content key: set {} to a base64-encoded 32-byte key, or make one with `mdrag content-key`", KEY_ENV)
+    }
+
+    /// `text` of chunk `chunk_index` of `path` sealed with a fresh random nonce, as a marked base64
+    /// string. The row is authenticated along with the text, so the value only decrypts there
+    pub fn encrypt(&self, text: &str, path: &str, chunk_index: i32) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = row_binding(path, chunk_index);
+        let ciphertext = self.cipher
+            .encrypt(&nonce, Payload { msg: text.as_bytes(), aad: &aad })
+            .map_err(|_| anyhow!("Failed to encrypt chunk text"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(sealed)))
+    }
+
+    /// Text from `encrypt` for the same row. Anything else is refused, plain text included: in an
+    /// encrypted index it can only have been written by something other than mdrag
+    pub fn decrypt(&self, stored: &str, path: &str, chunk_index: i32) -> Result<String> {
+        let Some(sealed) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            bail!(
+                "Chunk {} of {} isn't encrypted, though the index is; run `mdrag index --rebuild` to rewrite it",
+                chunk_index,
+                path,
+            );
+        };
+        let sealed = BASE64.decode(sealed).context("Corrupt encrypted chunk text")?;
+        if sealed.len() < NONCE_LEN {
+            bail!("Corrupt encrypted chunk text");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let aad = row_binding(path, chunk_index);
+        let plain = self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad })
+            .map_err(|_| anyhow!(
+                "Can't decrypt chunk {} of {}: the content key isn't the one the index was built with, or the text was moved from another chunk",
+                chunk_index,
+                path,
+            ))?;
+        String::from_utf8(plain).context("Decrypted chunk text isn't UTF-8")
+    }
+}
+
+/// Associated data tying a ciphertext to its row
+fn row_binding(path: &str, chunk_index: i32) -> Vec<u8> {
+    format!("{}\n{}", path, chunk_index).into_bytes()
+}
+
+/// Whether a stored `content` value was encrypted by `ContentCipher::encrypt`
+pub fn is_encrypted(stored: &str) -> bool {
+    stored.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// The stored `content` of chunk `chunk_index` of `path` as text: as it is unless the index is
+/// `encrypted` (`schema::schema_encrypted`), else decrypted with the key from
+/// `ContentCipher::load`, loaded on first use and kept for the process
+pub fn reveal<'a>(stored: &'a str, encrypted: bool, path: &str, chunk_index: i32) -> Result<Cow<'a, str>> {
+    static SHARED: OnceLock<ContentCipher> = OnceLock::new();
+    if !encrypted {
+        return Ok(Cow::Borrowed(stored));
+    }
+    let cipher = match SHARED.get() {
+        Some(cipher) => cipher,
+        None => {
+            let cipher = ContentCipher::load()?;
+            SHARED.get_or_init(|| cipher)
+        }
+    };
+    Ok(Cow::Owned(cipher.decrypt(stored, path, chunk_index)?))
+}
+
+/// A new random content key, base64-encoded
+pub fn generate_key() -> String {
+    BASE64.encode(Aes256Gcm::generate_key(OsRng))
+}
+
+/// 128 random bits from the OS as 32 hex digits, for ids and tokens that go in URLs and headers
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Save `key` as the content key in the OS keyring, unless it holds one already
+#[cfg(feature = "keyring")]
+pub fn store_key(key: &str) -> Result<()> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+    match entry.get_password() {
+        Ok(_) => bail!(
+            "The OS keyring already holds a content key ({} / {}); an index encrypted with it can't be read without it",
+            KEYRING_SERVICE,
+            KEYRING_USER,
+        ),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(e).context("Failed to read the OS keyring"),
+    }
+    entry.set_password(key).context("Failed to save the content key in the OS keyring")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store_key(_key: &str) -> Result<()> {
+    bail!("This build has no OS keyring support; rebuild with `--features keyring`, or keep the key in {}", KEY_ENV)
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_key() -> Result<Option<String>> {
+    match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).context("Failed to read the content key from the OS keyring"),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_key() -> Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_cipher() {
+        let cipher = ContentCipher::from_base64(&generate_key()).unwrap();
+        let sealed = cipher.encrypt("Long walk by the river.", "2025/07/21.md", 0).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("river"));
+        assert_ne!(sealed, cipher.encrypt("Long walk by the river.", "2025/07/21.md", 0).unwrap());
+        assert_eq!(cipher.decrypt(&sealed, "2025/07/21.md", 0).unwrap(), "Long walk by the river.");
+        assert_eq!(reveal("plain text", false, "2025/07/21.md", 0).unwrap(), "plain text");
+
+        // Plain text in an encrypted index, and text moved to another row, are refused
+        assert!(cipher.decrypt("plain text", "2025/07/21.md", 0).is_err());
+        assert!(cipher.decrypt(&sealed, "2025/07/21.md", 1).is_err());
+        assert!(cipher.decrypt(&sealed, "2025/07/22.md", 0).is_err());
+
+        let other = ContentCipher::from_base64(&generate_key()).unwrap();
+        assert!(other.decrypt(&sealed, "2025/07/21.md", 0).is_err());
+        assert!(ContentCipher::from_base64("c2hvcnQ=").is_err());
+
+        let token = random_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, random_token());
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{bail, Result};
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+/// The first and last day (inclusive) a date expression covers, relative to `today`.
+///
+/// Understands `2025-07-21`, `2025-07`, `2025`, `today`, `yesterday`, `3 days ago`,
+/// `2 weeks ago`, `last 30 days`, `this week`, `last month`, `last year`, `july` (the
+/// latest July up to now) and `july 2025`. Weeks start on Monday.
+pub fn parse_date_range(text: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate)> {
+    let text = text.trim().to_lowercase();
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let range = match words.as_slice() {
+        [word] if is_digits(word, 4) => {
+            let year = word.parse()?;
+            (ymd(year, 1, 1)?, ymd(year, 12, 31)?)
+        }
+        [word] if word.len() == 7 && word.is_ascii() && is_digits(&word[..4], 4) && word.as_bytes()[4] == b'-' => {
+            month_range(ymd(word[..4].parse()?, word[5..].parse()?, 1)?)
+        }
+        [word] if NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok() => {
+            let date = NaiveDate::parse_from_str(word, "%Y-%m-%d")?;
+            (date, date)
+        }
+        ["today"] => (today, today),
+        ["yesterday"] => {
+            let date = shift_back(today, 1, Unit::Day)?;
+            (date, date)
+        }
+        [count, unit, "ago"] => {
+            let date = shift_back(today, count.parse()?, unit.parse()?)?;
+            (date, date)
+        }
+        ["last" | "past", count, unit] if count.parse::<u32>().is_ok() => {
+            (shift_back(today, count.parse()?, unit.parse()?)?, today)
+        }
+        ["this", unit] => period(today, unit.parse()?),
+        ["last", unit] => {
+            let unit: Unit = unit.parse()?;
+            period(shift_back(today, 1, unit)?, unit)
+        }
+        [month] if month_number(month).is_some() => {
+            let month = month_number(month).unwrap_or_default();
+            // The latest such month that has started
+            let year = if month <= today.month() { today.year() } else { today.year() - 1 };
+            month_range(ymd(year, month, 1)?)
+        }
+        [month, year] if month_number(month).is_some() && is_digits(year, 4) => {
+            month_range(ymd(year.parse()?, month_number(month).unwrap_or_default(), 1)?)
+        }
+        _ => bail!(
+            "Unrecognized date '{}'. Use YYYY-MM-DD, YYYY-MM, 'yesterday', '2 weeks ago', 'last 30 days', 'last month' or 'july 2025'",
+            text,
+        ),
+    };
+    Ok(range)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl std::str::FromStr for Unit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim_end_matches('s') {
+            "day" => Ok(Unit::Day),
+            "week" => Ok(Unit::Week),
+            "month" => Ok(Unit::Month),
+            "year" => Ok(Unit::Year),
+            _ => bail!("Unknown time unit '{}'; use days, weeks, months or years", s),
+        }
+    }
+}
+
+fn shift_back(date: NaiveDate, count: u32, unit: Unit) -> Result<NaiveDate> {
+    let shifted = match unit {
+        Unit::Day => date.checked_sub_days(Days::new(count.into())),
+        Unit::Week => date.checked_sub_days(Days::new(u64::from(count) * 7)),
+        Unit::Month => date.checked_sub_months(Months::new(count)),
+        Unit::Year => date.checked_sub_months(Months::new(count.saturating_mul(12))),
+    };
+    shifted.ok_or_else(|| anyhow::anyhow!("Date out of range"))
+}
+
+/// The day, week, month or year containing `date`
+fn period(date: NaiveDate, unit: Unit) -> (NaiveDate, NaiveDate) {
+    match unit {
+        Unit::Day => (date, date),
+        Unit::Week => {
+            let monday = date - Days::new(date.weekday().num_days_from_monday().into());
+            (monday, monday + Days::new(6))
+        }
+        Unit::Month => month_range(date.with_day(1).unwrap_or(date)),
+        Unit::Year => (
+            date.with_ordinal(1).unwrap_or(date),
+            NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date),
+        ),
+    }
+}
+
+/// The month starting on `first`
+fn month_range(first: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let last = (first + Months::new(1)) - Days::new(1);
+    (first, last)
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> Result<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| anyhow::anyhow!("Invalid date {:04}-{:02}-{:02}", year, month, day))
+}
+
+fn is_digits(text: &str, len: usize) -> bool {
+    text.len() == len && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// 1-12 for a month name or its three-letter abbreviation
+fn month_number(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june",
+        "july", "august", "september", "october", "november", "december",
+    ];
+    MONTHS
+        .iter()
+        .position(|month| *month == name || (name.len() == 3 && month.starts_with(name)))
+        .map(|i| i as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_range() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap(); // a Monday
+        let range = |text| {
+            let (start, end) = parse_date_range(text, today).unwrap();
+            (start.to_string(), end.to_string())
+        };
+        let pair = |start: &str, end: &str| (start.to_string(), end.to_string());
+
+        assert_eq!(range("2025-03-04"), pair("2025-03-04", "2025-03-04"));
+        assert_eq!(range("2024-02"), pair("2024-02-01", "2024-02-29"));
+        assert_eq!(range("2 weeks ago"), pair("2025-07-07", "2025-07-07"));
+        assert_eq!(range("last 30 days"), pair("2025-06-21", "2025-07-21"));
+        assert_eq!(range("last week"), pair("2025-07-14", "2025-07-20"));
+        assert_eq!(range("Last Month"), pair("2025-06-01", "2025-06-30"));
+        assert_eq!(range("december"), pair("2024-12-01", "2024-12-31"));
+        assert_eq!(range("jan 2025"), pair("2025-01-01", "2025-01-31"));
+        assert!(parse_date_range("next tuesday", today).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+use anyhow::Result;
+use fastembed::{TextEmbedding, InitOptions, EmbeddingModel};
+use ort::execution_providers::{CoreMLExecutionProvider, CUDAExecutionProvider, ExecutionProvider, ExecutionProviderDispatch};
+use std::cell::RefCell;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::Arc;
+
+mod openai;
+pub use openai::{OpenAiBackend, EMBED_API_KEY_ENV, EMBED_URL_ENV};
+
+/// Model used when neither the command line nor an existing index names one
+pub const DEFAULT_MODEL: &str = "BAAI/bge-base-en-v1.5";
+
+/// Something that turns text into vectors
+pub trait EmbeddingBackend {
+    /// Embed a batch of texts, one vector per text in the same order
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
+
+    /// Identifier of the model, stored with the index
+    fn model_id(&self) -> &str;
+
+    /// Length of the vectors this model produces
+    fn dimension(&self) -> usize;
+
+    /// Counts how many tokens the model sees in a text; about four characters per token unless
+    /// the backend has the model's tokenizer
+    fn token_counter(&self) -> TokenCounter {
+        Arc::new(estimate_tokens)
+    }
+}
+
+/// Token counting that can be shared with the threads chunking entries
+pub type TokenCounter = Arc<dyn Fn(&str) -> usize + Send + Sync>;
+
+/// About four characters per token, for when the model's tokenizer isn't at hand
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Where embeddings are computed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// fastembed on this machine
+    #[default]
+    Local,
+    /// An OpenAI-compatible `/v1/embeddings` endpoint (OpenAI, Ollama, LM Studio)
+    OpenAi,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "local" | "fastembed" => Ok(Backend::Local),
+            "openai" | "remote" => Ok(Backend::OpenAi),
+            _ => Err(format!("unknown embedding backend '{}' (expected 'local' or 'openai')", s)),
+        }
+    }
+}
+
+/// Hardware the local model runs on, as an ONNX Runtime execution provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Device {
+    #[default]
+    Cpu,
+    /// NVIDIA GPUs; needs a build with the `cuda` feature and the CUDA libraries
+    Cuda,
+    /// Apple Neural Engine and GPU; needs a build with the `coreml` feature
+    CoreMl,
+}
+
+impl Device {
+    /// The execution provider to register, or `None` for ONNX Runtime's built-in CPU provider
+    fn execution_provider(self) -> Result<Option<ExecutionProviderDispatch>> {
+        let provider = match self {
+            Device::Cpu => return Ok(None),
+            Device::Cuda => {
+                let provider = CUDAExecutionProvider::default();
+                provider.is_available()?.then(|| provider.build())
+            }
+            Device::CoreMl => {
+                let provider = CoreMLExecutionProvider::default();
+                provider.is_available()?.then(|| provider.build())
+            }
+        };
+        match provider {
+            // Fail loudly instead of ONNX Runtime's silent fallback to the CPU
+            Some(provider) => Ok(Some(provider.error_on_failure())),
+            None => anyhow::bail!(
+                "The {} execution provider isn't available; build mdrag with `--features {}` and install its runtime libraries",
+                self,
+                self.to_string().to_ascii_lowercase(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Device::Cpu => "CPU",
+            Device::Cuda => "CUDA",
+            Device::CoreMl => "CoreML",
+        })
+    }
+}
+
+impl FromStr for Device {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(Device::Cpu),
+            "cuda" | "gpu" => Ok(Device::Cuda),
+            "coreml" => Ok(Device::CoreMl),
+            _ => Err(format!("unknown device '{}' (expected cpu, cuda or coreml)", s)),
+        }
+    }
+}
+
+/// Manages text embeddings for the RAG system
+pub struct EmbeddingGenerator {
+    backend: Box<dyn EmbeddingBackend>,
+    normalized: bool,
+}
+
+impl EmbeddingGenerator {
+    /// Create a new embedding generator with the default BGE-base-en-v1.5 model
+    pub fn new() -> Result<Self> {
+        Self::with_model(DEFAULT_MODEL)
+    }
+
+    /// Create a local embedding generator for a fastembed model code such as `BAAI/bge-small-en-v1.5`
+    pub fn with_model(model_id: &str) -> Result<Self> {
+        Self::load(Backend::Local, model_id)
+    }
+
+    /// Create an embedding generator for `model_id` on the given backend
+    pub fn load(backend: Backend, model_id: &str) -> Result<Self> {
+        Self::load_on(backend, model_id, Device::Cpu, None)
+    }
+
+    /// Like [`EmbeddingGenerator::load`], running a local model on `device` with at most `threads`
+    /// ONNX Runtime threads (default one per CPU)
+    pub fn load_on(backend: Backend, model_id: &str, device: Device, threads: Option<NonZeroUsize>) -> Result<Self> {
+        let backend: Box<dyn EmbeddingBackend> = match backend {
+            Backend::Local => Box::new(FastEmbedBackend::new(model_id, device, threads)?),
+            Backend::OpenAi if device != Device::Cpu || threads.is_some() => {
+                anyhow::bail!("--device and --threads only apply to the local backend; the openai backend embeds remotely")
+            }
+            Backend::OpenAi => Box::new(OpenAiBackend::from_env(model_id)?),
+        };
+
+        // Recorded with the index so a search can tell it is comparing like with like
+        let probe = backend.embed(vec!["normalization probe".to_string()])?;
+        let normalized = probe.first().is_some_and(|v| is_unit_length(v));
+
+        Ok(Self { backend, normalized })
+    }
+
+    /// Generate embeddings for a batch of texts
+    pub fn generate_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        self.backend.embed(texts)
+    }
+
+    /// Generate a single embedding
+    pub fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
+        let embeddings = self.backend.embed(vec![text.to_string()])?;
+
+        // Return the first (and only) embedding
+        embeddings.into_iter().next()
+            .ok_or_else(|| anyhow::anyhow!("No embedding generated"))
+    }
+
+    /// Get the dimension of embeddings produced by this model
+    pub fn embedding_dimension(&self) -> usize {
+        self.backend.dimension()
+    }
+
+    /// Model identifier, stored with the index so searches use the same model
+    pub fn model_id(&self) -> &str {
+        self.backend.model_id()
+    }
+
+    /// Whether the model produces unit-length vectors
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
+    /// Length of a text in the model's tokens, for sizing chunks
+    pub fn token_counter(&self) -> TokenCounter {
+        self.backend.token_counter()
+    }
+}
+
+/// True if `vector` has an L2 norm of 1, allowing for float rounding
+pub fn is_unit_length(vector: &[f32]) -> bool {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    (norm - 1.0).abs() < 1e-3
+}
+
+/// Local embeddings with fastembed
+pub struct FastEmbedBackend {
+    model: RefCell<TextEmbedding>,
+    model_id: String,
+    dimension: usize,
+}
+
+impl FastEmbedBackend {
+    pub fn new(model_id: &str, device: Device, threads: Option<NonZeroUsize>) -> Result<Self> {
+        // Rejected before anything is downloaded or loaded, see `on_cpus`
+        if threads.is_some() && cfg!(not(target_os = "linux")) {
+            anyhow::bail!(
+                "--threads is only supported on Linux; on this platform the embedding model always uses one thread per CPU"
+            );
+        }
+        let model = parse_model(model_id)?;
+        let info = TextEmbedding::get_model_info(&model)?;
+        let model_id = info.model_code.clone();
+        let dimension = info.dim;
+
+        // Status goes to stderr so JSON and MCP output on stdout stay clean
+        eprintln!("🤖 Loading embedding model ({})...", model_id);
+
+        let mut options = InitOptions::new(model);
+        if let Some(provider) = device.execution_provider()? {
+            options = options.with_execution_providers(vec![provider]);
+        }
+        let model = match threads {
+            Some(threads) => on_cpus(threads, || Ok(TextEmbedding::try_new(options)?))?,
+            None => TextEmbedding::try_new(options)?,
+        };
+
+        match threads {
+            Some(threads) => eprintln!("✅ Embedding model loaded successfully! (running on {}, {} threads)", device, threads),
+            None => eprintln!("✅ Embedding model loaded successfully! (running on {})", device),
+        }
+
+        Ok(Self { model: RefCell::new(model), model_id, dimension })
+    }
+}
+
+impl EmbeddingBackend for FastEmbedBackend {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        // fastembed expects &str, so we need to convert
+        let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+
+        Ok(self.model.borrow_mut().embed(text_refs, None)?)
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn token_counter(&self) -> TokenCounter {
+        // A copy of the tokenizer, so counting doesn't need the model.
+        // It truncates at the model's input limit, which is still enough to tell a chunk is too long
+        let tokenizer = self.model.borrow().tokenizer.clone();
+        Arc::new(move |text| match tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(_) => estimate_tokens(text),
+        })
+    }
+}
+
+/// Run `load` on a thread of its own that may only use the first `threads` of this process's CPUs.
+/// fastembed builds its ONNX Runtime session itself, with one intra-op thread per CPU
+/// `available_parallelism` reports and no option to change that. That count follows the
+/// thread's affinity, and the session's threads inherit it from the thread creating them, so
+/// narrowing the affinity is what sets both. Only Linux lets a thread narrow it.
+#[cfg(target_os = "linux")]
+fn on_cpus<T: Send>(threads: NonZeroUsize, load: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                limit_cpus(threads.get())?;
+                load()
+            })
+            .join()
+            .map_err(|_| anyhow::anyhow!("Loading the embedding model panicked"))?
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn on_cpus<T: Send>(_threads: NonZeroUsize, _load: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+    unreachable!("--threads is rejected by FastEmbedBackend::new off Linux")
+}
+
+/// Restrict the calling thread to the first `threads` CPUs it is allowed on: the new mask is
+/// always a subset of the current one, so a container's or `taskset`'s limits still hold
+#[cfg(target_os = "linux")]
+fn limit_cpus(threads: usize) -> Result<()> {
+    use anyhow::Context;
+
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    // cpu_set_t is a plain bit mask, for which all zeroes is the empty set
+    let mut allowed: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, size, &mut allowed) } != 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to read the CPUs mdrag may run on");
+    }
+    let mut limited: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let cpus = (0..libc::CPU_SETSIZE as usize).filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &allowed) });
+    for cpu in cpus.take(threads) {
+        unsafe { libc::CPU_SET(cpu, &mut limited) };
+    }
+    if unsafe { libc::sched_setaffinity(0, size, &limited) } != 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to limit the CPUs the embedding model runs on");
+    }
+    Ok(())
+}
+
+/// Length of the vectors `model_id` produces: looked up for a local model without loading it, asked
+/// of the API for a remote one
+pub fn model_dimension(backend: Backend, model_id: &str) -> Result<usize> {
+    match backend {
+        Backend::Local => Ok(TextEmbedding::get_model_info(&parse_model(model_id)?)?.dim),
+        Backend::OpenAi => Ok(OpenAiBackend::from_env(model_id)?.dimension()),
+    }
+}
+
+fn parse_model(model_id: &str) -> Result<EmbeddingModel> {
+    model_id.parse().map_err(|_| {
+        anyhow::anyhow!("Unknown embedding model '{}'. Supported models: {}", model_id, supported_models().join(", "))
+    })
+}
+
+/// fastembed model codes accepted by `--model` with the local backend
+pub fn supported_models() -> Vec<String> {
+    TextEmbedding::list_supported_models()
+        .into_iter()
+        .map(|info| info.model_code)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_names() {
+        assert_eq!("cuda".parse::<Device>(), Ok(Device::Cuda));
+        assert_eq!("CoreML".parse::<Device>(), Ok(Device::CoreMl));
+        assert!("tpu".parse::<Device>().is_err());
+        assert!(Device::Cpu.execution_provider().unwrap().is_none());
+
+        #[cfg(target_os = "linux")]
+        {
+            let available = on_cpus(NonZeroUsize::MIN, || Ok(std::thread::available_parallelism()?.get())).unwrap();
+            assert_eq!(available, 1);
+        }
+    }
+
+    #[test]
+    fn test_embedding_dimension() {
+        let generator = EmbeddingGenerator::new().unwrap();
+        assert_eq!(generator.embedding_dimension(), 768);
+        assert_eq!(model_dimension(Backend::Local, "BAAI/bge-small-en-v1.5").unwrap(), 384);
+    }
+
+    #[test]
+    fn test_single_embedding() {
+        let generator = EmbeddingGenerator::new().unwrap();
+        let embedding = generator.generate_embedding("Hello, world!").unwrap();
+        assert_eq!(embedding.len(), 768);
+    }
+
+    #[test]
+    fn test_unknown_model_is_rejected() {
+        assert!(EmbeddingGenerator::with_model("not-a-model").is_err());
+        assert!(supported_models().iter().any(|m| m == DEFAULT_MODEL));
+    }
+
+    #[test]
+    fn test_is_unit_length() {
+        assert!(is_unit_length(&[0.6, 0.8]));
+        assert!(!is_unit_length(&[3.0, 4.0]));
+        assert!(!is_unit_length(&[]));
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("local".parse::<Backend>(), Ok(Backend::Local));
+        assert_eq!("OpenAI".parse::<Backend>(), Ok(Backend::OpenAi));
+        assert!("gpu".parse::<Backend>().is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::EmbeddingBackend;
+
+/// Base URL of the embeddings API, e.g. `http://gpu-box:11434/v1`
+pub const EMBED_URL_ENV: &str = "MDRAG_EMBED_URL";
+
+/// Bearer token for the embeddings API, if it needs one
+pub const EMBED_API_KEY_ENV: &str = "MDRAG_EMBED_API_KEY";
+
+/// A local Ollama server
+const DEFAULT_EMBED_URL: &str = "http://localhost:11434/v1";
+
+/// Embeddings from an OpenAI-compatible `/v1/embeddings` endpoint
+pub struct OpenAiBackend {
+    endpoint: String,
+    api_key: Option<String>,
+    model_id: String,
+    dimension: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+impl OpenAiBackend {
+    /// Configure the endpoint from `MDRAG_EMBED_URL` and `MDRAG_EMBED_API_KEY`
+    pub fn from_env(model_id: &str) -> Result<Self> {
+        let base_url = std::env::var(EMBED_URL_ENV).unwrap_or_else(|_| DEFAULT_EMBED_URL.to_string());
+        let api_key = std::env::var(EMBED_API_KEY_ENV).ok().filter(|k| !k.is_empty());
+
+        eprintln!("🌐 Using embedding model {} at {}", model_id, base_url);
+
+        let mut backend = Self {
+            endpoint: format!("{}/embeddings", base_url.trim_end_matches('/')),
+            api_key,
+            model_id: model_id.to_string(),
+            dimension: 0,
+        };
+
+        // The API doesn't advertise the vector size, so ask for one embedding up front.
+        // This also fails early on a wrong URL, key or model name.
+        backend.dimension = backend
+            .embed(vec!["dimension probe".to_string()])?
+            .first()
+            .map(Vec::len)
+            .ok_or_else(|| anyhow::anyhow!("Embedding endpoint returned no vectors"))?;
+
+        Ok(backend)
+    }
+}
+
+impl EmbeddingBackend for OpenAiBackend {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let expected = texts.len();
+        let mut request = ureq::post(&self.endpoint);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+
+        let response: EmbeddingResponse = request
+            .send_json(json!({ "model": self.model_id, "input": texts }))
+            .with_context(|| format!("Embedding request to {} failed", self.endpoint))?
+            .into_json()
+            .context("Invalid embedding response")?;
+
+        let embeddings = into_ordered_embeddings(response);
+        if embeddings.len() != expected {
+            anyhow::bail!("Embedding endpoint returned {} vectors for {} texts", embeddings.len(), expected);
+        }
+        Ok(embeddings)
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+/// The API may return vectors in any order; `index` ties them back to the inputs
+fn into_ordered_embeddings(mut response: EmbeddingResponse) -> Vec<Vec<f32>> {
+    response.data.sort_by_key(|d| d.index);
+    response.data.into_iter().map(|d| d.embedding).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_is_reordered_by_index() {
+        let response: EmbeddingResponse = serde_json::from_value(json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 1, "embedding": [0.5, 0.5] },
+                { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] },
+            ],
+            "model": "nomic-embed-text",
+        })).unwrap();
+
+        assert_eq!(into_ordered_embeddings(response), vec![vec![1.0, 0.0], vec![0.5, 0.5]]);
+    }
+}
@@ -0,0 +1,285 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static FRONTMATTER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)^---\r?\n(.*?)\r?\n---[ \t]*(?:\r?\n|$)").unwrap()
+});
+
+/// Split a markdown document into its YAML frontmatter block (if any) and body
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    match FRONTMATTER_RE.captures(content) {
+        Some(captures) => {
+            let yaml = captures.get(1).unwrap().as_str();
+            let body = &content[captures.get(0).unwrap().end()..];
+            (Some(yaml), body)
+        }
+        None => (None, content),
+    }
+}
+
+/// Parse the YAML frontmatter of a markdown document into a key/value map
+pub fn extract_frontmatter(content: &str) -> Result<HashMap<String, serde_yaml::Value>> {
+    match split_frontmatter(content) {
+        (Some(yaml_content), _) => {
+            let frontmatter: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(yaml_content)
+                .context("Failed to parse YAML frontmatter")?;
+            Ok(frontmatter)
+        }
+        (None, _) => Err(anyhow::anyhow!("No frontmatter found")),
+    }
+}
+
+/// Read the `date` field from parsed frontmatter: `YYYY-MM-DD`, also with a time after it
+/// (`2025-07-21T08:30:00+01:00`, `2025-07-21 08:30`) or as a tagged YAML timestamp, else in one
+/// of the strftime `formats` such as `%d/%m/%Y` or `%Y%m%d` (which also reads a bare number)
+pub fn parse_date_from_frontmatter(frontmatter: &HashMap<String, serde_yaml::Value>, formats: &[String]) -> Result<NaiveDate> {
+    let date_value = frontmatter.get("date")
+        .ok_or_else(|| anyhow::anyhow!("No date field in frontmatter"))?;
+    let text = date_text(date_value).ok_or_else(|| anyhow::anyhow!("Date field is not a date or a string"))?;
+    parse_date(&text, formats).ok_or_else(|| anyhow::anyhow!("Failed to parse date '{}'", text))
+}
+
+/// `text` as a date in the built-in ISO form or one of `formats`; any time in it is dropped
+pub fn parse_date(text: &str, formats: &[String]) -> Option<NaiveDate> {
+    let iso = text.get(..10).filter(|_| text.len() == 10 || text[10..].starts_with(['T', 't', ' ']));
+    iso.and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()).or_else(|| {
+        formats.iter().find_map(|format| {
+            NaiveDate::parse_from_str(text, format)
+                .ok()
+                .or_else(|| NaiveDateTime::parse_from_str(text, format).ok().map(|datetime| datetime.date()))
+                .or_else(|| DateTime::parse_from_str(text, format).ok().map(|datetime| datetime.date_naive()))
+        })
+    })
+}
+
+fn date_text(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.trim().to_string()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        // `!!timestamp 2025-07-21`
+        serde_yaml::Value::Tagged(tagged) => date_text(&tagged.value),
+        _ => None,
+    }
+}
+
+/// The value at `path`: a top-level key, or a dot path into nested mappings and lists such as
+/// `sleep.hours`, `exercise[0].type` or `exercise.0.type`. A key that itself contains dots wins
+pub fn lookup<'a>(frontmatter: &'a HashMap<String, serde_yaml::Value>, path: &str) -> Option<&'a serde_yaml::Value> {
+    if let Some(value) = frontmatter.get(path) {
+        return Some(value);
+    }
+    let head = path.find(['.', '[']).unwrap_or(path.len());
+    let mut value = frontmatter.get(&path[..head])?;
+    let mut rest = &path[head..];
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let (index, tail) = after.split_once(']')?;
+            value = value.as_sequence()?.get(index.trim().parse::<usize>().ok()?)?;
+            rest = tail;
+        } else {
+            let after = rest.strip_prefix('.')?;
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            value = match key.parse::<usize>() {
+                Ok(index) if value.is_sequence() => value.get(index)?,
+                _ => value.get(key)?,
+            };
+            rest = &after[end..];
+        }
+    }
+    Some(value)
+}
+
+/// Convert a YAML value to JSON, stripping trailing `# comments` from strings
+pub fn yaml_to_json_value(yaml_val: &serde_yaml::Value) -> serde_json::Value {
+    match yaml_val {
+        serde_yaml::Value::Null => serde_json::Value::Null,
+        serde_yaml::Value::Bool(b) => serde_json::Value::Bool(*b),
+        serde_yaml::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                serde_json::Value::Number(i.into())
+            } else if let Some(f) = n.as_f64() {
+                serde_json::Value::Number(serde_json::Number::from_f64(f).unwrap_or(0.into()))
+            } else {
+                serde_json::Value::Null
+            }
+        }
+        serde_yaml::Value::String(s) => {
+            // Clean up values by removing comments
+            let cleaned = if let Some(pos) = s.find('#') {
+                s[..pos].trim().to_string()
+            } else {
+                s.clone()
+            };
+            serde_json::Value::String(cleaned)
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            serde_json::Value::Array(seq.iter().map(yaml_to_json_value).collect())
+        }
+        serde_yaml::Value::Mapping(map) => {
+            let obj: serde_json::Map<String, serde_json::Value> = map
+                .iter()
+                .filter_map(|(k, v)| {
+                    k.as_str().map(|key| (key.to_string(), yaml_to_json_value(v)))
+                })
+                .collect();
+            serde_json::Value::Object(obj)
+        }
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// A frontmatter value as a number: numbers, numeric strings, ranges like `"3-4"` as their
+/// midpoint, and booleans as 1 or 0
+pub fn numeric_value(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        serde_json::Value::String(s) => {
+            // Handle range values like "3-4"
+            if let Some(dash_pos) = s.find('-') {
+                let (start, end) = s.split_at(dash_pos);
+                let end = &end[1..]; // Skip the dash
+
+                if let (Ok(start_val), Ok(end_val)) = (start.trim().parse::<f64>(), end.trim().parse::<f64>()) {
+                    return Some((start_val + end_val) / 2.0);
+                }
+            }
+
+            // Try direct parse
+            s.trim().parse::<f64>().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Set `fields` in a document's frontmatter and keep the rest as written.
+///
+/// An existing `key:` line, or a commented-out `# key:`, gets the new value in place and keeps
+/// its trailing `# comment`; the indented or `- ` lines of a block value it had are dropped.
+/// Other keys are added at the end, and a document without frontmatter gets one. Values are
+/// written as given, so they are YAML (`7`, `[work, rust]`, `"a: b"`)
+pub fn set_fields(content: &str, fields: &[(String, String)]) -> String {
+    let Some((mut lines, body)) = frontmatter_lines(content) else {
+        let frontmatter: String = fields.iter().map(|(key, value)| format!("{}: {}\n", key, value)).collect();
+        return format!("---\n{}---\n\n{}", frontmatter, content);
+    };
+    for (key, value) in fields {
+        let Some(i) = lines.iter().position(|line| field_value(line, key).is_some()) else {
+            lines.push(format!("{}: {}", key, value));
+            continue;
+        };
+        let comment = field_value(&lines[i], key).and_then(trailing_comment);
+        if !lines[i].starts_with('#') {
+            let block = lines[i + 1..]
+                .iter()
+                .take_while(|line| line.starts_with([' ', '\t']) || line.starts_with("- "))
+                .count();
+            lines.drain(i + 1..i + 1 + block);
+        }
+        lines[i] = match comment {
+            Some(comment) => format!("{}: {} {}", key, value, comment),
+            None => format!("{}: {}", key, value),
+        };
+    }
+    format!("---\n{}\n{}", lines.join("\n"), body)
+}
+
+/// The lines between a document's `---` fences, and the rest of it from the closing fence on.
+/// Join them back with `---\n{lines}\n{rest}`
+pub(crate) fn frontmatter_lines(content: &str) -> Option<(Vec<String>, &str)> {
+    let rest = content.strip_prefix("---\n")?;
+    let (frontmatter, body) = match rest.find("\n---") {
+        Some(end) => (&rest[..end + 1], &rest[end + 1..]),
+        None => ("", rest),
+    };
+    Some((frontmatter.lines().map(String::from).collect(), body))
+}
+
+/// What follows `key:` on a top-level frontmatter line, also when the line is commented out
+fn field_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let line = line.strip_prefix('#').map(str::trim_start).unwrap_or(line);
+    line.strip_prefix(key)?.strip_prefix(':')
+}
+
+/// A `# ...` comment after the value, as in `mood: # 1-10 scale` or `tags: [] # for search`.
+/// A `#` inside a quoted value isn't one
+pub(crate) fn trailing_comment(value: &str) -> Option<&str> {
+    let value = value.trim_start();
+    if value.starts_with('#') {
+        return Some(value);
+    }
+    let scalar_end = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => value[1..].find(quote).map_or(value.len(), |i| i + 2),
+        _ => 0,
+    };
+    value[scalar_end..].find(" #").map(|i| &value[scalar_end + i + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_parse_frontmatter() {
+        let content = "---\ndate: 2025-07-21\nmood: 7\n---\n# Title\nBody\n";
+        let (yaml, body) = split_frontmatter(content);
+        assert_eq!(yaml, Some("date: 2025-07-21\nmood: 7"));
+        assert_eq!(body, "# Title\nBody\n");
+
+        let frontmatter = extract_frontmatter(content).unwrap();
+        let july_21 = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap();
+        assert_eq!(parse_date_from_frontmatter(&frontmatter, &[]).unwrap(), july_21);
+
+        let formats = ["%d/%m/%Y".to_string(), "%Y%m%d".to_string()];
+        for date in ["2025-07-21T08:30:00+01:00", "2025-07-21 08:30", "!!timestamp 2025-07-21", "'21/07/2025'", "20250721"] {
+            let frontmatter = extract_frontmatter(&format!("---\ndate: {}\n---\n", date)).unwrap();
+            assert_eq!(parse_date_from_frontmatter(&frontmatter, &formats).unwrap(), july_21, "{}", date);
+        }
+        assert!(parse_date("21/07/2025", &[]).is_none());
+        assert!(parse_date("2025-07-21x", &[]).is_none());
+
+        assert_eq!(split_frontmatter("# No frontmatter"), (None, "# No frontmatter"));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let content = "---
+sleep:
+  hours: 7.5
+exercise:
+  - type: run
+    km: 5
+  - type: yoga
+weight.kg: 82
+---
+";
+        let frontmatter = extract_frontmatter(content).unwrap();
+        let get = |path| lookup(&frontmatter, path).map(yaml_to_json_value);
+
+        assert_eq!(get("sleep.hours"), Some(serde_json::json!(7.5)));
+        assert_eq!(get("exercise[1].type"), Some(serde_json::json!("yoga")));
+        assert_eq!(get("exercise.0.km"), Some(serde_json::json!(5)));
+        assert_eq!(get("weight.kg"), Some(serde_json::json!(82)));
+        assert_eq!(get("exercise[2].type"), None);
+        assert_eq!(get("sleep.hours.max"), None);
+        assert_eq!(get("sleep[0]"), None);
+    }
+
+    #[test]
+    fn test_set_fields() {
+        let content = "---\ndate: 2025-07-21\nmood: 5 # 1-10 scale\ntags:\n  - work\n  - rust\nquote: \"C# #1\"\n# weight_kg:\n---\n# Title\n";
+        let fields = [("mood", "7"), ("tags", "[rest]"), ("quote", "none"), ("weight_kg", "82.4"), ("energy", "6")]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+        let updated = set_fields(content, &fields);
+        assert_eq!(
+            updated,
+            "---\ndate: 2025-07-21\nmood: 7 # 1-10 scale\ntags: [rest]\nquote: none\nweight_kg: 82.4\nenergy: 6\n---\n# Title\n",
+        );
+        assert!(extract_frontmatter(&updated).is_ok());
+        assert_eq!(set_fields("# Title\n", &fields[..1]), "---\nmood: 7\n---\n\n# Title\n");
+    }
+}
@@ -0,0 +1,321 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::frontmatter::numeric_value;
+
+/// A condition on an entry's frontmatter, e.g. `mood >= 7 AND anxiety < 3 AND 'rust' in tags`.
+///
+/// Comparisons (`=`, `!=`, `<`, `<=`, `>`, `>=`) are numeric when both sides are numbers
+/// (including strings like `"7"` or ranges like `3-4`, taken as their midpoint), else they
+/// compare text. `x in field` looks for `x` in a list, or in a comma-separated string, ignoring
+/// case. Fields may be dot paths such as `sleep.hours` or `exercise[0].type`. `AND`, `OR`, `NOT`
+/// and parentheses combine them. A missing or empty field fails every comparison, so
+/// `NOT mood > 5` holds for entries without a mood
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CmpOp, Operand),
+    In(Operand, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Field(String),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Text(String),
+    Op(CmpOp),
+    Open,
+    Close,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Filter(expr)),
+            Some(token) => Err(format!("Unexpected {} in filter", describe(token))),
+        }
+    }
+}
+
+impl Filter {
+    /// Whether an entry passes, given its frontmatter values by key
+    pub fn matches(&self, field: &impl Fn(&str) -> Option<Value>) -> bool {
+        self.0.eval(field)
+    }
+}
+
+impl Expr {
+    fn eval(&self, field: &impl Fn(&str) -> Option<Value>) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(field) && b.eval(field),
+            Expr::Or(a, b) => a.eval(field) || b.eval(field),
+            Expr::Not(a) => !a.eval(field),
+            Expr::Compare(a, op, b) => match (a.resolve(field), b.resolve(field)) {
+                (Some(a), Some(b)) => compare(&a, &b).is_some_and(|ordering| op.holds(ordering)),
+                _ => false,
+            },
+            Expr::In(item, list) => match (item.resolve(field), list.resolve(field)) {
+                (Some(item), Some(Value::Array(items))) => items.iter().any(|candidate| same_item(&item, candidate)),
+                (Some(item), Some(Value::String(list))) => {
+                    list.split(',').any(|candidate| same_item(&item, &Value::String(candidate.trim().to_string())))
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, field: &impl Fn(&str) -> Option<Value>) -> Option<Value> {
+        match self {
+            Operand::Field(name) => field(name).filter(|value| !value.is_null() && value != ""),
+            Operand::Literal(value) => Some(value.clone()),
+        }
+    }
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CmpOp::Eq => ordering == Ordering::Equal,
+            CmpOp::Ne => ordering != Ordering::Equal,
+            CmpOp::Lt => ordering == Ordering::Less,
+            CmpOp::Le => ordering != Ordering::Greater,
+            CmpOp::Gt => ordering == Ordering::Greater,
+            CmpOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Numbers (and booleans, as 1 and 0) by value, anything else as text; lists and maps don't compare
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (numeric_value(a), numeric_value(b)) {
+        return a.partial_cmp(&b);
+    }
+    match (a, b) {
+        (Value::Array(_) | Value::Object(_), _) | (_, Value::Array(_) | Value::Object(_)) => None,
+        _ => Some(text(a).cmp(&text(b))),
+    }
+}
+
+fn same_item(item: &Value, candidate: &Value) -> bool {
+    match (numeric_value(item), numeric_value(candidate)) {
+        (Some(a), Some(b)) => a == b,
+        _ => text(item).eq_ignore_ascii_case(&text(candidate)),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '\'' | '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, end)) if end == c => break,
+                        Some((_, ch)) => value.push(ch),
+                        None => return Err(format!("Unterminated string starting at {}", &text[start..])),
+                    }
+                }
+                tokens.push(Token::Text(value));
+            }
+            '=' | '!' | '<' | '>' => {
+                let next = chars.next_if(|(_, next)| *next == '=' || (c == '<' && *next == '>')).map(|(_, next)| next);
+                let op = match (c, next) {
+                    ('=', _) => CmpOp::Eq,
+                    ('!', Some('=')) | ('<', Some('>')) => CmpOp::Ne,
+                    ('<', Some('=')) => CmpOp::Le,
+                    ('<', None) => CmpOp::Lt,
+                    ('>', Some('=')) => CmpOp::Ge,
+                    ('>', None) => CmpOp::Gt,
+                    _ => return Err(format!("Unknown operator at {}", &text[start..])),
+                };
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, _)) = chars.next_if(|(_, next)| next.is_ascii_digit() || *next == '.') {
+                    end = i + 1;
+                }
+                let number = text[start..end].parse().map_err(|_| format!("Invalid number '{}'", &text[start..end]))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, next)) = chars.next_if(|(_, next)| next.is_alphanumeric() || "_-.[]".contains(*next)) {
+                    end = i + next.len_utf8();
+                }
+                tokens.push(Token::Word(text[start..end].to_string()));
+            }
+            _ => return Err(format!("Unexpected '{}' in filter", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := not (AND not)*`,
+/// `not := NOT not | '(' or ')' | operand (op operand | IN operand)`
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.or()?;
+            if self.tokens.get(self.pos) != Some(&Token::Close) {
+                return Err("Missing ')' in filter".to_string());
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+
+        let left = self.operand()?;
+        if self.keyword("in") {
+            return Ok(Expr::In(left, self.operand()?));
+        }
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => {
+                self.pos += 1;
+                Ok(Expr::Compare(left, *op, self.operand()?))
+            }
+            Some(token) => Err(format!("Expected a comparison or 'in', found {}", describe(token))),
+            None => Err("Expected a comparison or 'in' at the end of the filter".to_string()),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        let token = self.tokens.get(self.pos).ok_or("Filter ends where a field or value was expected")?;
+        let operand = match token {
+            Token::Word(word) if word.eq_ignore_ascii_case("true") => Operand::Literal(Value::Bool(true)),
+            Token::Word(word) if word.eq_ignore_ascii_case("false") => Operand::Literal(Value::Bool(false)),
+            Token::Word(word) if is_keyword(word) => return Err(format!("Expected a field or value, found {}", describe(token))),
+            Token::Word(word) => Operand::Field(word.clone()),
+            Token::Number(n) => Operand::Literal(serde_json::json!(n)),
+            Token::Text(text) => Operand::Literal(Value::String(text.clone())),
+            token => return Err(format!("Expected a field or value, found {}", describe(token))),
+        };
+        self.pos += 1;
+        Ok(operand)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.tokens.get(self.pos), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    ["and", "or", "not", "in"].iter().any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::Number(n) => format!("'{}'", n),
+        Token::Text(text) => format!("'{}'", text),
+        Token::Op(op) => format!("'{}'", op.symbol()),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter() {
+        let entry = json!({ "mood": 8, "anxiety": "2", "sleep": "6-7", "tags": ["Rust", "work"], "people": "Ana, Rui", "gym": true });
+        let matches = |filter: &str| filter.parse::<Filter>().unwrap().matches(&|key: &str| entry.get(key).cloned());
+
+        assert!(matches("mood >= 7 AND anxiety < 3 AND 'rust' in tags"));
+        assert!(matches("sleep = 6.5 and gym = true"));
+        assert!(matches("'rui' IN people OR mood < 0"));
+        assert!(matches("NOT (mood > 9 or weight > 80)"));
+        assert!(matches("mood <> 7"));
+        assert!(!matches("weight != 80"));
+        assert!(!matches("'python' in tags"));
+
+        assert!("mood >".parse::<Filter>().is_err());
+        assert!("mood > 7 and".parse::<Filter>().is_err());
+        assert!("(mood > 7".parse::<Filter>().is_err());
+        assert!("mood > 7 anxiety < 3".parse::<Filter>().is_err());
+        assert!("'rust in tags".parse::<Filter>().is_err());
+    }
+}
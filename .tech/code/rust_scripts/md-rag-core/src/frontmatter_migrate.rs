@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use crate::frontmatter::{frontmatter_lines, trailing_comment};
+
+/// Type a field's values are rewritten as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Int,
+    Float,
+    Bool,
+    String,
+}
+
+impl FromStr for FieldType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "int" | "integer" => Ok(FieldType::Int),
+            "float" | "number" => Ok(FieldType::Float),
+            "bool" | "boolean" => Ok(FieldType::Bool),
+            "string" | "str" => Ok(FieldType::String),
+            _ => Err(format!("Unknown type '{}'. Use int, float, bool or string", s)),
+        }
+    }
+}
+
+/// Key renames and type coercions for the frontmatter of every entry, to undo years of schema drift
+#[derive(Debug, Default, Clone)]
+pub struct Migration {
+    /// Old key, new key
+    pub renames: Vec<(String, String)>,
+    /// Applied after the renames, so they name the new keys
+    pub coercions: Vec<(String, FieldType)>,
+}
+
+/// What a migration did to one document
+#[derive(Debug, Default, PartialEq)]
+pub struct Migrated {
+    /// The rewritten document; None when nothing changed
+    pub content: Option<String>,
+    /// Keys and values left alone, e.g. `'weight: heavy' isn't a number`
+    pub problems: Vec<String>,
+}
+
+impl Migration {
+    /// Rewrite the frontmatter lines in place, keeping their order and comments. A rename whose
+    /// new key is already set, a block value and a value that doesn't convert are reported
+    /// instead of changed
+    pub fn apply(&self, content: &str) -> Migrated {
+        let Some((mut lines, body)) = frontmatter_lines(content) else {
+            return Migrated::default();
+        };
+        let mut problems = Vec::new();
+        let mut changed = false;
+
+        for (old, new) in &self.renames {
+            let Some(i) = key_line(&lines, old) else {
+                continue;
+            };
+            if key_line(&lines, new).is_some() {
+                problems.push(format!("both '{}' and '{}' are set, so '{}' was kept", old, new, old));
+                continue;
+            }
+            lines[i] = format!("{}{}", new, &lines[i][old.len()..]);
+            changed = true;
+        }
+
+        for (key, field_type) in &self.coercions {
+            let Some(i) = key_line(&lines, key) else {
+                continue;
+            };
+            let rest = &lines[i][key.len() + 1..];
+            let comment = trailing_comment(rest);
+            let value = rest[..rest.len() - comment.map_or(0, str::len)].trim();
+            if value.is_empty() {
+                // Null, or a list or mapping on the following lines
+                if lines.get(i + 1).is_some_and(|line| line.starts_with([' ', '\t']) || line.starts_with("- ")) {
+                    problems.push(format!("'{}' has a block value, left as is", key));
+                }
+                continue;
+            }
+            match coerce(value, *field_type) {
+                Ok(coerced) if coerced == value => {}
+                Ok(coerced) => {
+                    lines[i] = match comment {
+                        Some(comment) => format!("{}: {} {}", key, coerced, comment),
+                        None => format!("{}: {}", key, coerced),
+                    };
+                    changed = true;
+                }
+                Err(e) => problems.push(format!("'{}: {}' {}", key, value, e)),
+            }
+        }
+
+        Migrated {
+            content: changed.then(|| format!("---\n{}\n{}", lines.join("\n"), body)),
+            problems,
+        }
+    }
+}
+
+/// Index of the top-level, uncommented line setting `key`
+fn key_line(lines: &[String], key: &str) -> Option<usize> {
+    lines.iter().position(|line| line.strip_prefix(key).is_some_and(|rest| rest.starts_with(':')))
+}
+
+/// A YAML scalar rewritten as `field_type`
+fn coerce(value: &str, field_type: FieldType) -> Result<String, &'static str> {
+    let unquoted = value.trim_matches(['"', '\'']);
+    match field_type {
+        FieldType::Int => {
+            let number = parse_number(unquoted).ok_or("isn't a number")?;
+            if number.fract() != 0.0 {
+                return Err("isn't a whole number");
+            }
+            Ok(format!("{}", number as i64))
+        }
+        // Always with a decimal point, so YAML reads a float
+        FieldType::Float => {
+            let number = parse_number(unquoted).ok_or("isn't a number")?;
+            Ok(if number.fract() == 0.0 { format!("{:.1}", number) } else { number.to_string() })
+        }
+        FieldType::Bool => match unquoted.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" => Ok("true".to_string()),
+            "false" | "no" | "n" | "off" | "0" => Ok("false".to_string()),
+            _ => Err("isn't a yes/no value"),
+        },
+        FieldType::String if value.len() >= 2 && value.starts_with(['"', '\'']) && value.ends_with(&value[..1]) => {
+            Ok(value.to_string())
+        }
+        FieldType::String => Ok(format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))),
+    }
+}
+
+/// A number as people write it in frontmatter: `82.4`, `82,4` or `82.4 kg`. Ranges like `3-4` aren't
+fn parse_number(text: &str) -> Option<f64> {
+    let number = text.trim_end_matches(|c: char| c.is_alphabetic() || c == '%').trim().replace(',', ".");
+    number.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration() {
+        let migration = Migration {
+            renames: vec![("anxiety_level".to_string(), "anxiety".to_string()), ("mood_score".to_string(), "mood".to_string())],
+            coercions: vec![
+                ("anxiety".to_string(), FieldType::Int),
+                ("weight".to_string(), FieldType::Float),
+                ("gym".to_string(), FieldType::Bool),
+                ("mood".to_string(), FieldType::Int),
+            ],
+        };
+        let entry = "---\ndate: 2025-07-21\nanxiety_level: '3' # 1-10\nweight: 82,4 kg\ngym: yes\nmood: 3-4\nmood_score: 4\n---\n# Title\n";
+        let migrated = migration.apply(entry);
+        assert_eq!(
+            migrated.content.as_deref(),
+            Some("---\ndate: 2025-07-21\nanxiety: 3 # 1-10\nweight: 82.4\ngym: true\nmood: 3-4\nmood_score: 4\n---\n# Title\n"),
+        );
+        assert_eq!(
+            migrated.problems,
+            vec!["both 'mood_score' and 'mood' are set, so 'mood_score' was kept", "'mood: 3-4' isn't a number"],
+        );
+
+        assert_eq!(migration.apply("---\ndate: 2025-07-21\nweight: 80.5\n---\n"), Migrated::default());
+        assert_eq!(coerce("7", FieldType::Float), Ok("7.0".to_string()));
+        assert_eq!(coerce("say \"hi\"", FieldType::String), Ok("\"say \\\"hi\\\"\"".to_string()));
+        assert_eq!("Integer".parse::<FieldType>(), Ok(FieldType::Int));
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::frontmatter::yaml_to_json_value;
+
+/// Distinct example values kept per key
+const MAX_EXAMPLES: usize = 3;
+
+/// Every frontmatter key seen across a journal, to find out what can be queried
+#[derive(Debug, Default, Serialize)]
+pub struct Schema {
+    /// Entries with frontmatter that were added
+    pub entries: usize,
+    /// Most often filled first
+    pub keys: Vec<KeySummary>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct KeySummary {
+    /// Top-level key, or a dot path such as `sleep.hours` into a nested mapping
+    pub key: String,
+    /// How many values had each type: int, float, bool, date, string, list, map or null
+    pub types: BTreeMap<&'static str, usize>,
+    /// Entries where the key has a non-empty value
+    pub filled: usize,
+    pub fill_rate: f64,
+    pub examples: Vec<String>,
+}
+
+/// Collects key summaries one entry at a time
+#[derive(Debug, Default)]
+pub struct SchemaScan {
+    entries: usize,
+    keys: HashMap<String, KeySummary>,
+}
+
+impl SchemaScan {
+    pub fn add(&mut self, frontmatter: &HashMap<String, Value>) {
+        self.entries += 1;
+        for (key, value) in frontmatter {
+            self.add_value(key.clone(), value);
+        }
+    }
+
+    /// Keys of nested mappings are recorded as dot paths; lists are recorded whole
+    fn add_value(&mut self, path: String, value: &Value) {
+        if let Value::Mapping(map) = value {
+            for (key, nested) in map {
+                if let Some(key) = key.as_str() {
+                    self.add_value(format!("{}.{}", path, key), nested);
+                }
+            }
+        }
+
+        let summary = self.keys.entry(path.clone()).or_insert_with(|| KeySummary { key: path, ..Default::default() });
+        *summary.types.entry(type_name(value)).or_default() += 1;
+        let empty = matches!(value, Value::Null) || value.as_str().is_some_and(|s| s.trim().is_empty());
+        if empty {
+            return;
+        }
+        summary.filled += 1;
+        if summary.examples.len() < MAX_EXAMPLES && !matches!(value, Value::Mapping(_)) {
+            let example = match yaml_to_json_value(value) {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            };
+            if !summary.examples.contains(&example) {
+                summary.examples.push(example);
+            }
+        }
+    }
+
+    pub fn finish(self) -> Schema {
+        let entries = self.entries;
+        let mut keys: Vec<KeySummary> = self
+            .keys
+            .into_values()
+            .map(|mut summary| {
+                summary.fill_rate = summary.filled as f64 / entries.max(1) as f64;
+                summary
+            })
+            .collect();
+        keys.sort_by(|a, b| b.filled.cmp(&a.filled).then_with(|| a.key.cmp(&b.key)));
+        Schema { entries, keys }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(s) if NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() => "date",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "list",
+        Value::Mapping(_) => "map",
+        Value::Tagged(tagged) => type_name(&tagged.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::extract_frontmatter;
+
+    #[test]
+    fn test_schema_scan() {
+        let mut scan = SchemaScan::default();
+        for content in [
+            "---\ndate: 2025-07-20\nmood: 7\nsleep:\n  hours: 7.5\ntags: [work]\n---\n",
+            "---\ndate: 2025-07-21\nmood: 3-4\nsleep:\n  hours: 6\n---\n",
+            "---\ndate: 2025-07-22\nmood:\nweight_kg: 82.4\n---\n",
+            "---\ndate: 2025-07-23\nmood: 7\n---\n",
+        ] {
+            scan.add(&extract_frontmatter(content).unwrap());
+        }
+        let schema = scan.finish();
+        assert_eq!(schema.entries, 4);
+
+        let keys: Vec<_> = schema.keys.iter().map(|k| (k.key.as_str(), k.filled)).collect();
+        assert_eq!(keys, vec![("date", 4), ("mood", 3), ("sleep", 2), ("sleep.hours", 2), ("tags", 1), ("weight_kg", 1)]);
+
+        let mood = &schema.keys[1];
+        assert_eq!(mood.types, BTreeMap::from([("int", 2), ("null", 1), ("string", 1)]));
+        assert_eq!(mood.fill_rate, 0.75);
+        assert_eq!(mood.examples, vec!["7", "3-4"]);
+        assert_eq!(schema.keys[0].types, BTreeMap::from([("date", 4)]));
+        assert_eq!(schema.keys[3].types, BTreeMap::from([("float", 1), ("int", 1)]));
+        assert!(schema.keys[2].examples.is_empty());
+    }
+}
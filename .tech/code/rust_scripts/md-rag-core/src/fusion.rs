@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Damping constant from the original RRF paper; keeps one retriever's top hit from dominating
+pub const RRF_K: f32 = 60.0;
+
+/// Combine several rankings with reciprocal rank fusion.
+///
+/// Each item scores `1 / (k + rank)` per ranking it appears in (rank starting at 1).
+/// Returns every item once, best first; ties keep the order items were first seen in.
+pub fn reciprocal_rank_fusion<K: Clone + Eq + Hash>(rankings: &[Vec<K>], k: f32) -> Vec<(K, f32)> {
+    let mut scores: HashMap<K, (f32, usize)> = HashMap::new();
+    let mut seen = 0;
+
+    for ranking in rankings {
+        for (rank, item) in ranking.iter().enumerate() {
+            let entry = scores.entry(item.clone()).or_insert_with(|| {
+                seen += 1;
+                (0.0, seen)
+            });
+            entry.0 += 1.0 / (k + rank as f32 + 1.0);
+        }
+    }
+
+    let mut fused: Vec<(K, f32, usize)> = scores
+        .into_iter()
+        .map(|(item, (score, order))| (item, score, order))
+        .collect();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.2.cmp(&b.2)));
+
+    fused.into_iter().map(|(item, score, _)| (item, score)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_found_by_both_rankings_win() {
+        let vector = vec!["a", "b", "c"];
+        let text = vec!["c", "d"];
+        let fused = reciprocal_rank_fusion(&[vector, text], RRF_K);
+
+        let order: Vec<&str> = fused.iter().map(|(item, _)| *item).collect();
+        assert_eq!(order, vec!["c", "a", "b", "d"]);
+        assert!((fused[0].1 - (1.0 / 63.0 + 1.0 / 61.0)).abs() < 1e-6);
+    }
+}
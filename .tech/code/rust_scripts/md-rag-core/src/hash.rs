@@ -0,0 +1,20 @@
+use sha2::{Digest, Sha256};
+
+/// Hex-encoded SHA-256 of `text`, used to detect changed entries between index runs
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash("hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_ne!(content_hash("hello"), content_hash("hello "));
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::user_dir;
+
+/// File in the user dir that searches are logged to, one JSON object per line
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// One search as it was run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub at: DateTime<Utc>,
+    pub query: String,
+    pub collection: String,
+    /// Results the search returned
+    pub results: usize,
+}
+
+/// A query and how often it was searched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryCount {
+    pub query: String,
+    pub count: usize,
+    pub last: DateTime<Utc>,
+}
+
+/// Where the history lives, if there is a user dir
+pub fn default_path() -> Option<PathBuf> {
+    user_dir().map(|dir| dir.join(HISTORY_FILE))
+}
+
+/// Add `entry` to the end of the history. The file is only readable by its owner
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Remove the history; true if there was one
+pub fn clear(path: &Path) -> Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+/// Every logged search, oldest first. Lines that don't parse, such as one cut short by a crash, are skipped
+pub fn read(path: &Path) -> Result<Vec<HistoryEntry>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Queries by how often they were searched, ignoring case and surrounding whitespace, then by
+/// how recently. Each is shown as it was last typed
+pub fn most_frequent(entries: &[HistoryEntry]) -> Vec<QueryCount> {
+    let mut counts: HashMap<String, QueryCount> = HashMap::new();
+    for entry in entries {
+        let count = counts.entry(entry.query.trim().to_lowercase()).or_insert_with(|| QueryCount {
+            query: String::new(),
+            count: 0,
+            last: entry.at,
+        });
+        count.count += 1;
+        if entry.at >= count.last || count.query.is_empty() {
+            count.last = entry.at;
+            count.query = entry.query.trim().to_string();
+        }
+    }
+    let mut counts: Vec<QueryCount> = counts.into_values().collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then(b.last.cmp(&a.last)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_history_round_trip_and_counts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join(HISTORY_FILE);
+        assert!(read(&path).unwrap().is_empty());
+
+        let entry = |minute, query: &str| HistoryEntry {
+            at: Utc.with_ymd_and_hms(2025, 7, 21, 12, minute, 0).unwrap(),
+            query: query.to_string(),
+            collection: "documents".to_string(),
+            results: 3,
+        };
+        for e in [entry(1, "sleep"), entry(2, "burnout"), entry(3, "Sleep ")] {
+            append(&path, &e).unwrap();
+        }
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "{\"at\": \"trunc").unwrap();
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1], entry(2, "burnout"));
+
+        let counts: Vec<(String, usize)> = most_frequent(&entries).into_iter().map(|c| (c.query, c.count)).collect();
+        assert_eq!(counts, vec![("Sleep".to_string(), 2), ("burnout".to_string(), 1)]);
+    }
+}
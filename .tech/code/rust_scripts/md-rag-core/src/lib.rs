@@ -0,0 +1,37 @@
+//! Shared building blocks for the journal RAG tools: embeddings and their
+//! cache, chunking, the LanceDB schema, journal scanning, frontmatter parsing,
+//! frontmatter filter expressions and key discovery, tag extraction, task checkboxes, comment and metadata scrubbing, search snippets, encryption of stored chunk text, the index lock, date expressions, the `.mdrag.toml` config,
+//! saved searches and search history, prompting a chat model with search results, and new
+//! entries from the journal template.
+
+pub mod cache;
+pub mod chunk;
+pub mod config;
+pub mod crypto;
+pub mod date_expr;
+pub mod embeddings;
+pub mod frontmatter;
+pub mod frontmatter_filter;
+pub mod frontmatter_migrate;
+pub mod frontmatter_schema;
+pub mod fusion;
+pub mod hash;
+pub mod history;
+pub mod llm;
+pub mod lock;
+pub mod metadata;
+pub mod metric;
+pub mod new_entry;
+pub mod path_date;
+pub mod path_filter;
+pub mod prompt;
+pub mod rerank;
+pub mod saved;
+pub mod scan;
+pub mod schema;
+pub mod scrub;
+pub mod search;
+pub mod snippet;
+pub mod tags;
+pub mod tasks;
+pub mod template_filter;
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Base URL of the chat API, e.g. `https://api.openai.com/v1`
+pub const LLM_URL_ENV: &str = "MDRAG_LLM_URL";
+
+/// Bearer token for the chat API, if it needs one
+pub const LLM_API_KEY_ENV: &str = "MDRAG_LLM_API_KEY";
+
+/// Chat model to ask
+pub const LLM_MODEL_ENV: &str = "MDRAG_LLM_MODEL";
+
+/// A local Ollama server, which also speaks the OpenAI API
+pub const DEFAULT_LLM_URL: &str = "http://localhost:11434/v1";
+
+/// A small model Ollama users commonly have pulled
+pub const DEFAULT_LLM_MODEL: &str = "llama3.2";
+
+/// Answers from an OpenAI-compatible `/v1/chat/completions` endpoint (OpenAI, Ollama, LM Studio, llama.cpp)
+pub struct ChatClient {
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+impl ChatClient {
+    /// Talk to `model` at `base_url`, authenticating with `MDRAG_LLM_API_KEY` when it is set
+    pub fn new(base_url: &str, model: &str) -> Self {
+        Self {
+            endpoint: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            api_key: std::env::var(LLM_API_KEY_ENV).ok().filter(|k| !k.is_empty()),
+            model: model.to_string(),
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The model's reply to `prompt` under the `system` instructions
+    pub fn complete(&self, system: &str, prompt: &str) -> Result<String> {
+        let mut request = ureq::post(&self.endpoint);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+
+        let response: ChatResponse = request
+            .send_json(json!({
+                "model": self.model,
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": prompt },
+                ],
+                // Answers should stick to the sources, not improvise
+                "temperature": 0.2,
+                "stream": false,
+            }))
+            .with_context(|| format!("Chat request to {} failed", self.endpoint))?
+            .into_json()
+            .context("Invalid chat response")?;
+
+        first_reply(response)
+    }
+}
+
+fn first_reply(response: ChatResponse) -> Result<String> {
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content.trim().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Chat endpoint returned no answer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_reply() {
+        let response: ChatResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "model": "llama3.2",
+            "choices": [
+                { "index": 0, "message": { "role": "assistant", "content": " Reproducible builds [1].\n" }, "finish_reason": "stop" },
+            ],
+        })).unwrap();
+        assert_eq!(first_reply(response).unwrap(), "Reproducible builds [1].");
+
+        let empty: ChatResponse = serde_json::from_value(json!({ "choices": [] })).unwrap();
+        assert!(first_reply(empty).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::fmt;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// Lock file in the lance dir, locked by the OS while a process writes to the index
+pub const LOCK_FILE_NAME: &str = "mdrag.lock";
+
+/// How long to wait for a new holder to record itself before reporting it as unknown
+const HOLDER_GRACE: Duration = Duration::from_millis(200);
+
+/// The process holding the lock, as recorded in the lock file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockHolder {
+    /// `None` while the holder has taken the lock but not yet recorded itself
+    pub pid: Option<u32>,
+    /// RFC 3339 time the lock was taken
+    pub since: String,
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "pid {}, since {}", pid, self.since),
+            None => f.write_str("a run that is just starting"),
+        }
+    }
+}
+
+/// Lock on the index, so a watcher and a manual run don't write at the same time.
+///
+/// An OS file lock (`flock` on Unix, `LockFileEx` on Windows) on `mdrag.lock`, so taking it is
+/// atomic and a crashed holder releases it with its process: nothing is ever judged stale or
+/// deleted. The file stays in place; the holder writes its pid and start time into it once it
+/// has the lock, and empties it again on release. Released when dropped.
+pub struct IndexLock {
+    file: File,
+}
+
+impl IndexLock {
+    /// Take the lock on the index in `lance_dir`, or return who holds it
+    pub fn try_acquire(lance_dir: &Path) -> Result<Result<Self, LockHolder>> {
+        let path = lance_dir.join(LOCK_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(Err(read_holder(&path))),
+            Err(TryLockError::Error(e)) => return Err(e).with_context(|| format!("Failed to lock {}", path.display())),
+        }
+        // Whatever a crashed holder left behind is overwritten
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}\n{}", std::process::id(), Utc::now().to_rfc3339())?;
+        file.flush()?;
+        Ok(Ok(Self { file }))
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        // The OS lock goes with the file handle; emptying the file first keeps a stale pid out of it
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Who holds the lock at `path`. A holder that has only just taken it may not have written
+/// itself yet, so an empty file is read again for a moment before the holder counts as unknown
+fn read_holder(path: &Path) -> LockHolder {
+    let started = std::time::Instant::now();
+    loop {
+        if let Some(holder) = parse_holder(path) {
+            return holder;
+        }
+        if started.elapsed() >= HOLDER_GRACE {
+            return LockHolder { pid: None, since: String::new() };
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn parse_holder(path: &Path) -> Option<LockHolder> {
+    let mut text = String::new();
+    File::open(path).ok()?.read_to_string(&mut text).ok()?;
+    let mut lines = text.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let since = lines.next().unwrap_or_default().trim().to_string();
+    Some(LockHolder { pid: Some(pid), since })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_lock_is_exclusive_and_stale_locks_are_replaced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        let lock = IndexLock::try_acquire(dir).unwrap().unwrap();
+        let holder = IndexLock::try_acquire(dir).unwrap().err().unwrap();
+        assert_eq!(holder.pid, Some(std::process::id()));
+        drop(lock);
+        assert_eq!(fs::read_to_string(dir.join(LOCK_FILE_NAME)).unwrap(), "");
+
+        // Left behind by a crashed run: the file says a pid, but nothing holds the OS lock
+        fs::write(dir.join(LOCK_FILE_NAME), "999999999\n2025-01-01T00:00:00Z\n").unwrap();
+        let lock = IndexLock::try_acquire(dir).unwrap().unwrap();
+        assert!(fs::read_to_string(dir.join(LOCK_FILE_NAME)).unwrap().starts_with(&std::process::id().to_string()));
+        drop(lock);
+    }
+
+    #[test]
+    fn test_empty_lock_file_of_a_live_holder_is_respected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        // A holder between taking the lock and writing its pid
+        let file = File::create(dir.join(LOCK_FILE_NAME)).unwrap();
+        file.lock().unwrap();
+        let holder = IndexLock::try_acquire(dir).unwrap().err().unwrap();
+        assert_eq!(holder.pid, None);
+        assert!(dir.join(LOCK_FILE_NAME).exists());
+
+        file.unlock().unwrap();
+        assert!(IndexLock::try_acquire(dir).unwrap().is_ok());
+    }
+}
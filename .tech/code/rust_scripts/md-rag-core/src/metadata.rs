@@ -0,0 +1,173 @@
+use anyhow::{bail, Result};
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Columns every chunk table has; frontmatter fields can't reuse these names
+const RESERVED_COLUMNS: &[&str] = &[
+    "path", "date", "content", "section", "chunk_index", "total_chunks",
+    "content_hash", "mtime", "start_line", "end_line", "tags", "embedding",
+];
+
+/// A frontmatter key stored as its own column, so searches can filter on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataField {
+    pub name: String,
+    pub kind: FieldKind,
+}
+
+/// Column type of a metadata field, inferred from the values in the journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    Boolean,
+    /// Anything else; lists are stored comma-separated
+    Text,
+}
+
+impl FieldKind {
+    /// The narrowest kind that holds every value; missing and null values don't count
+    pub fn infer<'a>(values: impl Iterator<Item = &'a Value>) -> Self {
+        let mut kind = None;
+        for value in values {
+            let this = match value {
+                Value::Null => continue,
+                Value::Number(_) => FieldKind::Number,
+                Value::Bool(_) => FieldKind::Boolean,
+                _ => FieldKind::Text,
+            };
+            kind = match kind {
+                None => Some(this),
+                Some(k) if k == this => Some(k),
+                Some(_) => return FieldKind::Text,
+            };
+        }
+        kind.unwrap_or(FieldKind::Text)
+    }
+
+    pub fn data_type(self) -> DataType {
+        match self {
+            FieldKind::Number => DataType::Float64,
+            FieldKind::Boolean => DataType::Boolean,
+            FieldKind::Text => DataType::Utf8,
+        }
+    }
+
+    fn from_data_type(data_type: &DataType) -> Option<Self> {
+        match data_type {
+            DataType::Float64 => Some(FieldKind::Number),
+            DataType::Boolean => Some(FieldKind::Boolean),
+            DataType::Utf8 => Some(FieldKind::Text),
+            _ => None,
+        }
+    }
+}
+
+impl MetadataField {
+    pub fn arrow_field(&self) -> Field {
+        Field::new(&self.name, self.kind.data_type(), true)
+    }
+
+    /// Build this field's column, one value per row; rows without the key are null
+    pub fn column(&self, rows: &[&HashMap<String, Value>]) -> ArrayRef {
+        let values = rows.iter().map(|frontmatter| frontmatter.get(&self.name));
+        match self.kind {
+            FieldKind::Number => Arc::new(values.map(|v| v.and_then(as_number)).collect::<Float64Array>()),
+            FieldKind::Boolean => Arc::new(values.map(|v| v.and_then(as_bool)).collect::<BooleanArray>()),
+            FieldKind::Text => Arc::new(values.map(|v| v.and_then(as_text)).collect::<StringArray>()),
+        }
+    }
+}
+
+/// Check that a frontmatter key can be used as a column name in filters like `mood >= 7`
+pub fn validate_field_name(name: &str) -> Result<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!("'{}' can't be a metadata column: use letters, digits and underscores", name);
+    }
+    if RESERVED_COLUMNS.contains(&name) {
+        bail!("'{}' is a built-in column and can't be a metadata field", name);
+    }
+    Ok(())
+}
+
+/// Infer a column type for each of `names` from the frontmatter of every entry
+pub fn infer_fields(names: &[String], documents: &[&HashMap<String, Value>]) -> Result<Vec<MetadataField>> {
+    names
+        .iter()
+        .map(|name| {
+            validate_field_name(name)?;
+            let kind = FieldKind::infer(documents.iter().filter_map(|frontmatter| frontmatter.get(name)));
+            Ok(MetadataField { name: name.clone(), kind })
+        })
+        .collect()
+}
+
+/// The metadata columns of a chunk table, given the names recorded in its schema
+pub fn schema_fields(schema: &Schema, names: &[String]) -> Vec<MetadataField> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let field = schema.field_with_name(name).ok()?;
+            let kind = FieldKind::from_data_type(field.data_type())?;
+            Some(MetadataField { name: name.clone(), kind })
+        })
+        .collect()
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(b) => Some(*b),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Sequence(items) => Some(items.iter().filter_map(as_text).collect::<Vec<_>>().join(", ")),
+        other => serde_yaml::to_string(other).ok().map(|s| s.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Array;
+
+    #[test]
+    fn test_infer_and_build_columns() {
+        let day1: HashMap<String, Value> = serde_yaml::from_str("mood: 7\npeople: [Ana, Rui]\nsick: false").unwrap();
+        let day2: HashMap<String, Value> = serde_yaml::from_str("mood: 4.5\nsick: true").unwrap();
+        let rows = vec![&day1, &day2];
+
+        let names = vec!["mood".to_string(), "people".to_string(), "sick".to_string()];
+        let fields = infer_fields(&names, &rows).unwrap();
+        let kinds: Vec<FieldKind> = fields.iter().map(|f| f.kind).collect();
+        assert_eq!(kinds, vec![FieldKind::Number, FieldKind::Text, FieldKind::Boolean]);
+
+        let people = fields[1].column(&rows);
+        let people = people.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(people.value(0), "Ana, Rui");
+        assert!(people.is_null(1));
+
+        assert!(validate_field_name("date").is_err());
+        assert!(validate_field_name("tags").is_err());
+        assert!(validate_field_name("mood score").is_err());
+    }
+}
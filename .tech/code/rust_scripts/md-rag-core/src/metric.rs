@@ -0,0 +1,109 @@
+use lancedb::DistanceType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How vector similarity is measured, chosen at index time and stored with the table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    /// Euclidean distance; what every index used before the metric was configurable
+    L2,
+    /// Angle between the vectors, ignoring their length
+    #[default]
+    Cosine,
+    /// Inner product of unit vectors; ranks like cosine
+    Dot,
+}
+
+impl Metric {
+    pub fn distance_type(self) -> DistanceType {
+        match self {
+            Metric::L2 => DistanceType::L2,
+            Metric::Cosine => DistanceType::Cosine,
+            Metric::Dot => DistanceType::Dot,
+        }
+    }
+
+    /// Whether vectors are scaled to unit length before they are stored or searched
+    pub fn normalizes(self) -> bool {
+        !matches!(self, Metric::L2)
+    }
+
+    /// Turn a LanceDB `_distance` into a similarity in 0..=1, higher is better.
+    ///
+    /// Cosine and dot distances are `1 - cos`, so this is the cosine mapped from -1..1 onto 0..1.
+    pub fn score(self, distance: f32) -> f32 {
+        match self {
+            Metric::L2 => 1.0 / (1.0 + distance),
+            Metric::Cosine | Metric::Dot => (1.0 - distance / 2.0).clamp(0.0, 1.0),
+        }
+    }
+
+    /// What this metric scores a pair of unit vectors that cosine scores `cosine_score`, so one
+    /// threshold means the same similarity under every metric. LanceDB's L2 distance is squared,
+    /// `2 * (1 - cos)` between unit vectors; for longer ones L2 only approximates it
+    pub fn from_cosine_score(self, cosine_score: f32) -> f32 {
+        match self {
+            Metric::Cosine | Metric::Dot => cosine_score,
+            Metric::L2 => self.score(4.0 * (1.0 - cosine_score)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::L2 => "l2",
+            Metric::Cosine => "cosine",
+            Metric::Dot => "dot",
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "l2" | "euclidean" => Ok(Metric::L2),
+            "cosine" => Ok(Metric::Cosine),
+            "dot" => Ok(Metric::Dot),
+            _ => Err(format!("unknown metric '{}' (expected 'cosine', 'l2' or 'dot')", s)),
+        }
+    }
+}
+
+/// Scale `vector` to unit length in place; zero vectors are left alone
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_are_in_unit_range() {
+        assert_eq!(Metric::Cosine.score(0.0), 1.0);
+        assert_eq!(Metric::Cosine.score(1.0), 0.5);
+        assert_eq!(Metric::Dot.score(2.0), 0.0);
+        assert_eq!(Metric::L2.score(1.0), 0.5);
+        assert_eq!(Metric::Dot.from_cosine_score(0.6), 0.6);
+        // A cosine of 0.2: cosine distance 0.8, squared L2 distance 1.6
+        assert!((Metric::L2.from_cosine_score(0.6) - 1.0 / 2.6).abs() < 1e-6);
+        assert_eq!("COSINE".parse::<Metric>(), Ok(Metric::Cosine));
+        assert!("manhattan".parse::<Metric>().is_err());
+
+        let mut vector = vec![3.0, 4.0];
+        normalize(&mut vector);
+        assert_eq!(vector, vec![0.6, 0.8]);
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter::set_fields;
+
+/// Placeholder the journal template uses for the entry's date
+pub const DATE_PLACEHOLDER: &str = "YYYY-MM-DD";
+
+/// Used when no template file is found
+pub const BUILTIN_TEMPLATE: &str = "---\ndate: YYYY-MM-DD\n---\n\n# YYYY-MM-DD\n\n";
+
+/// Where the entry for `date` belongs: `<journal_dir>/YYYY/MM/DD.md`
+pub fn entry_path(journal_dir: &Path, date: NaiveDate) -> PathBuf {
+    journal_dir.join(date.format("%Y/%m/%d.md").to_string())
+}
+
+/// Fill in a journal template for `date`: every `YYYY-MM-DD` becomes the date, then `date` and
+/// each of `fields` is set in the frontmatter with `frontmatter::set_fields`
+pub fn instantiate(template: &str, date: NaiveDate, fields: &[(String, String)]) -> String {
+    let date = date.format("%Y-%m-%d").to_string();
+    let content = template.replace(DATE_PLACEHOLDER, &date);
+    let fields = [&[("date".to_string(), date)], fields].concat();
+    set_fields(&content, &fields)
+}
+
+/// Add `text` to the section under `heading` (`Gratitude` or `## Gratitude`; titles match
+/// case-insensitively), after its last non-blank line and before any subheading. An empty `-`
+/// bullet left by the template is replaced instead. Without such a heading, a new section is
+/// added at the end. Frontmatter and fenced code are never taken for headings
+pub fn append_to_section(content: &str, heading: &str, text: &str) -> String {
+    let title = heading.trim_start_matches('#').trim();
+    let text = text.trim_matches('\n');
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in lines.iter().enumerate().skip(frontmatter_lines(&lines)) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence && heading_title(line).is_some() {
+            headings.push(i);
+        }
+    }
+
+    let found = headings.iter().position(|&i| heading_title(lines[i]).is_some_and(|t| t.eq_ignore_ascii_case(title)));
+    let Some(k) = found else {
+        let level = match heading.trim_start().len() - heading.trim_start().trim_start_matches('#').len() {
+            0 => "##".to_string(),
+            n => "#".repeat(n.min(6)),
+        };
+        let before = content.trim_end_matches('\n');
+        let separator = if before.is_empty() { "" } else { "\n\n" };
+        return format!("{}{}{} {}\n\n{}\n", before, separator, level, title, text);
+    };
+
+    let start = headings[k];
+    let end = headings.get(k + 1).copied().unwrap_or(lines.len());
+    let mut last = end;
+    while last > start + 1 && lines[last - 1].trim().is_empty() {
+        last -= 1;
+    }
+    let placeholder = last > start + 1 && matches!(lines[last - 1].trim(), "-" | "*");
+    let (keep, resume) = if placeholder { (last - 1, last) } else { (last, last) };
+
+    let mut updated: Vec<&str> = lines[..keep].to_vec();
+    updated.extend(text.lines());
+    updated.extend(&lines[resume..]);
+    let mut updated = updated.join("\n");
+    if content.ends_with('\n') || resume == lines.len() {
+        updated.push('\n');
+    }
+    updated
+}
+
+/// The title of a markdown ATX heading line
+fn heading_title(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then(|| rest.trim())
+}
+
+/// How many lines at the top of an entry are its frontmatter, `---` fences included
+fn frontmatter_lines(lines: &[&str]) -> usize {
+    if lines.first() != Some(&"---") {
+        return 0;
+    }
+    lines.iter().skip(1).position(|line| *line == "---").map_or(0, |end| end + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instantiate() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap();
+        assert_eq!(entry_path(Path::new("journal"), date), Path::new("journal/2025/07/21.md"));
+
+        let template = "---\ndate: YYYY-MM-DD\nmood: # 1-10 scale\ntags: [] # categories\n# weight_kg: \n---\n\n# Daily Reflection - YYYY-MM-DD\n";
+        let fields = [("mood", "7"), ("weight_kg", "71.5"), ("project", "mdrag")]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+        assert_eq!(
+            instantiate(template, date, &fields),
+            "---\ndate: 2025-07-21\nmood: 7 # 1-10 scale\ntags: [] # categories\nweight_kg: 71.5\nproject: mdrag\n---\n\n# Daily Reflection - 2025-07-21\n",
+        );
+        assert_eq!(instantiate("# Notes\n", date, &[]), "---\ndate: 2025-07-21\n---\n\n# Notes\n");
+    }
+
+    #[test]
+    fn test_append_to_section() {
+        let entry = "---\ndate: 2025-07-21\n---\n\n## Work\n- Shipped search\n\n### Highlights of the Day\n- \n\n## Notes\n```\n# not a heading\n```\n";
+        assert_eq!(
+            append_to_section(entry, "work", "- Fixed the watcher"),
+            "---\ndate: 2025-07-21\n---\n\n## Work\n- Shipped search\n- Fixed the watcher\n\n### Highlights of the Day\n- \n\n## Notes\n```\n# not a heading\n```\n",
+        );
+        // The template's empty bullet is filled rather than left above the new item
+        assert!(append_to_section(entry, "### Highlights of the Day", "- Sunset run\n")
+            .contains("### Highlights of the Day\n- Sunset run\n\n## Notes"));
+        assert!(append_to_section(entry, "not a heading", "- x").ends_with("```\n\n## not a heading\n\n- x\n"));
+        assert_eq!(append_to_section("", "### Gratitude", "- Coffee"), "### Gratitude\n\n- Coffee\n");
+    }
+}
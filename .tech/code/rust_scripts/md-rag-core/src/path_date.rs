@@ -0,0 +1,85 @@
+use chrono::NaiveDate;
+use regex::Regex;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Finds `2025/07/21` or `2025-07-21` anywhere in the path, which covers both
+/// `journal/2025/07/21.md` and `journal/2025-07-21.md`
+const DEFAULT_PATTERN: &str = r"(?P<year>\d{4})[/-](?P<month>\d{2})[/-](?P<day>\d{2})";
+
+/// How an entry's date is read from its path when its frontmatter has none.
+///
+/// Tried before the file's mtime, which changes whenever a clone or sync rewrites the file.
+#[derive(Debug, Clone)]
+pub enum PathDatePattern {
+    /// Regex with `year`, `month` and `day` named groups, matched anywhere in the path
+    Regex(Regex),
+    /// strftime format such as `%Y/%m/%d`, matched against the end of the path without `.md`
+    Format(String),
+}
+
+impl Default for PathDatePattern {
+    fn default() -> Self {
+        PathDatePattern::Regex(Regex::new(DEFAULT_PATTERN).unwrap())
+    }
+}
+
+impl PathDatePattern {
+    /// Date encoded in `path`, taken relative to the journal directory
+    pub fn date(&self, path: &Path) -> Option<NaiveDate> {
+        let path = path.with_extension("");
+        let path = path.to_string_lossy().replace('\\', "/");
+
+        match self {
+            PathDatePattern::Regex(regex) => {
+                let captures = regex.captures(&path)?;
+                let part = |name: &str| captures.name(name)?.as_str().parse().ok();
+                NaiveDate::from_ymd_opt(part("year")?, part("month")?, part("day")?)
+            }
+            // Try the whole path, then drop leading directories until the format fits
+            PathDatePattern::Format(format) => std::iter::once(path.as_str())
+                .chain(path.match_indices('/').map(|(i, _)| &path[i + 1..]))
+                .find_map(|tail| NaiveDate::parse_from_str(tail, format).ok()),
+        }
+    }
+}
+
+impl FromStr for PathDatePattern {
+    type Err = String;
+
+    /// Anything containing `%` is a strftime format, the rest are regexes
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('%') {
+            return Ok(PathDatePattern::Format(s.to_string()));
+        }
+
+        let regex = Regex::new(s).map_err(|e| format!("invalid path date regex: {}", e))?;
+        let groups: Vec<&str> = regex.capture_names().flatten().collect();
+        if !["year", "month", "day"].iter().all(|name| groups.contains(name)) {
+            return Err("a path date regex needs (?P<year>..), (?P<month>..) and (?P<day>..) groups".to_string());
+        }
+        Ok(PathDatePattern::Regex(regex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates_from_paths() {
+        let july_21 = NaiveDate::from_ymd_opt(2025, 7, 21);
+        let default = PathDatePattern::default();
+        assert_eq!(default.date(Path::new("2025/07/21.md")), july_21);
+        assert_eq!(default.date(Path::new("daily/2025-07-21.md")), july_21);
+        assert_eq!(default.date(Path::new("2025/13/40.md")), None);
+        assert_eq!(default.date(Path::new("notes/ideas.md")), None);
+
+        let format: PathDatePattern = "%d.%m.%Y".parse().unwrap();
+        assert_eq!(format.date(Path::new("archive/21.07.2025.md")), july_21);
+
+        let regex: PathDatePattern = r"(?P<year>\d{4})_(?P<month>\d\d)(?P<day>\d\d)".parse().unwrap();
+        assert_eq!(regex.date(Path::new("log_2025_0721.md")), july_21);
+        assert!(r"(\d{4})".parse::<PathDatePattern>().is_err());
+    }
+}
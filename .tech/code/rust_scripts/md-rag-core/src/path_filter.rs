@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Ignore file read from the root of the journal directory, in `.gitignore` syntax
+pub const IGNORE_FILE_NAME: &str = ".ragignore";
+
+/// Always skipped unless `.ragignore` re-includes them with a `!` pattern
+const BUILTIN_EXCLUDES: &[&str] = &["template*"];
+
+/// Which markdown files under the journal directory get indexed: everything except
+/// templates, `.ragignore` and `--exclude` matches, narrowed to `--include` globs if any are given
+pub struct PathFilter {
+    root: PathBuf,
+    exclude: Gitignore,
+    include: Override,
+}
+
+impl PathFilter {
+    /// Patterns are relative to `root`; `exclude` lines use `.gitignore` syntax and are applied after `.ragignore`
+    pub fn new(root: &Path, exclude: &[String], include: &[String]) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in BUILTIN_EXCLUDES {
+            builder.add_line(None, pattern)?;
+        }
+
+        let ignore_file = root.join(IGNORE_FILE_NAME);
+        if ignore_file.is_file()
+            && let Some(e) = builder.add(&ignore_file)
+        {
+            return Err(e).with_context(|| format!("Invalid {}", ignore_file.display()));
+        }
+
+        for pattern in exclude {
+            builder.add_line(None, pattern).with_context(|| format!("Invalid --exclude pattern '{}'", pattern))?;
+        }
+
+        let mut overrides = OverrideBuilder::new(root);
+        for pattern in include {
+            overrides.add(pattern).with_context(|| format!("Invalid --include pattern '{}'", pattern))?;
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            exclude: builder.build()?,
+            include: overrides.build()?,
+        })
+    }
+
+    /// Whether the markdown file at `path` should be indexed
+    pub fn is_included(&self, path: &Path) -> bool {
+        // Paths outside the journal are never ours to index
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        !self.exclude.matched_path_or_any_parents(relative, false).is_ignore()
+            && !self.include.matched(relative, false).is_ignore()
+    }
+
+    /// Every markdown file under the root that passes the filter, without descending into excluded directories
+    pub fn markdown_files(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        WalkDir::new(&self.root)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|entry| {
+                !entry.file_type().is_dir()
+                    || entry.path().strip_prefix(&self.root).is_ok_and(|relative| {
+                        relative.as_os_str().is_empty() || !self.exclude.matched(relative, true).is_ignore()
+                    })
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("md"))
+            .map(|e| e.into_path())
+            .filter(|path| self.is_included(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ragignore_and_globs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for dir in ["2025", "drafts", "private"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["2025/01.md", "2025/template-daily.md", "drafts/idea.md", "private/secret.md", "notes.md"] {
+            fs::write(root.join(file), "# Entry\n").unwrap();
+        }
+        fs::write(root.join(IGNORE_FILE_NAME), "private/\n").unwrap();
+
+        let files = |exclude: &[&str], include: &[&str]| {
+            let owned = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+            let filter = PathFilter::new(root, &owned(exclude), &owned(include)).unwrap();
+            let mut files: Vec<String> = filter
+                .markdown_files()
+                .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(files(&[], &[]), vec!["2025/01.md", "drafts/idea.md", "notes.md"]);
+        assert_eq!(files(&["drafts/"], &[]), vec!["2025/01.md", "notes.md"]);
+        assert_eq!(files(&[], &["2025/**"]), vec!["2025/01.md"]);
+
+        let filter = PathFilter::new(root, &[], &[]).unwrap();
+        assert!(!filter.is_included(&root.join("private/secret.md")));
+    }
+}
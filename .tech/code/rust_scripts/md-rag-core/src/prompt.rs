@@ -0,0 +1,191 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::embeddings::{estimate_tokens, TokenCounter};
+use crate::search::SearchResult;
+
+/// A source is cut to fit the budget only if at least this many tokens of it would remain;
+/// otherwise the next, possibly shorter, result gets the room
+const MIN_TRIMMED_TOKENS: usize = 40;
+
+/// Marks a source cut short to fit the budget
+const TRIMMED_MARKER: &str = " […]";
+
+/// A search result given to a language model, numbered so its answer can cite it as `[n]`
+#[derive(Debug, Clone, Serialize)]
+pub struct Source {
+    pub number: usize,
+    pub path: PathBuf,
+    pub date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub text: String,
+    /// Tokens of the rendered source, header included
+    pub tokens: usize,
+    /// Cut short to fit the budget
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub trimmed: bool,
+}
+
+impl Source {
+    /// `[n] path (date · section)` followed by the text, as the model sees it
+    pub fn render(&self) -> String {
+        let mut header = format!("[{}] {} ({}", self.number, self.path.display(), self.date);
+        if let Some(section) = &self.section {
+            header.push_str(&format!(" · {}", section));
+        }
+        format!("{})\n{}", header, self.text.trim())
+    }
+}
+
+/// Counts tokens with OpenAI's `cl100k_base` encoding, close enough for most chat models.
+/// Falls back to four characters a token if the encoding can't be loaded
+pub fn llm_token_counter() -> TokenCounter {
+    match tiktoken_rs::cl100k_base() {
+        Ok(bpe) => Arc::new(move |text| bpe.encode_ordinary(text).len()),
+        Err(_) => Arc::new(estimate_tokens),
+    }
+}
+
+/// Turn ranked results into sources fitting in `max_tokens`, best first.
+///
+/// Each result brings its `context` when the search asked for one, otherwise its snippet.
+/// Text already given by an earlier source of the same entry is left out. A result too large for
+/// what is left is cut at a word boundary, or skipped if little of it would remain, so a
+/// smaller one further down can still fit.
+pub fn pack_sources(results: &[SearchResult], max_tokens: usize, count_tokens: &dyn Fn(&str) -> usize) -> Vec<Source> {
+    let mut sources: Vec<Source> = Vec::new();
+    let mut used = 0;
+    for result in results {
+        let text = result.context.as_deref().unwrap_or(&result.snippet).trim();
+        let repeated = sources.iter().any(|s| s.path == result.path && s.text.contains(text));
+        if text.is_empty() || repeated {
+            continue;
+        }
+        let mut source = Source {
+            number: sources.len() + 1,
+            path: result.path.clone(),
+            date: result.date,
+            section: result.section.clone(),
+            text: text.to_string(),
+            tokens: 0,
+            trimmed: false,
+        };
+        // The blank line between sources counts too
+        let left = max_tokens.saturating_sub(used + 1);
+        source.tokens = count_tokens(&source.render());
+        if source.tokens > left {
+            if !trim_to_fit(&mut source, left, count_tokens) {
+                continue;
+            }
+            source.tokens = count_tokens(&source.render());
+        }
+        used += source.tokens + 1;
+        sources.push(source);
+    }
+    sources
+}
+
+/// Cut `source` to the most whole words that fit in `budget` tokens with the marker; false if
+/// fewer than [`MIN_TRIMMED_TOKENS`] of its text would be kept
+fn trim_to_fit(source: &mut Source, budget: usize, count_tokens: &dyn Fn(&str) -> usize) -> bool {
+    let full = std::mem::take(&mut source.text);
+    let ends: Vec<usize> = full
+        .char_indices()
+        .filter(|(i, c)| c.is_whitespace() && *i > 0 && !full[..*i].ends_with(char::is_whitespace))
+        .map(|(i, _)| i)
+        .collect();
+
+    // The longest prefix of whole words that fits
+    let fits = |words: usize| {
+        let mut text = full[..ends[words - 1]].to_string();
+        text.push_str(TRIMMED_MARKER);
+        count_tokens(&Source { text, ..source.clone() }.render()) <= budget
+    };
+    let (mut low, mut high) = (0, ends.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    if low == 0 || count_tokens(&full[..ends[low - 1]]) < MIN_TRIMMED_TOKENS {
+        return false;
+    }
+    source.text = format!("{}{}", &full[..ends[low - 1]], TRIMMED_MARKER);
+    source.trimmed = true;
+    true
+}
+
+/// Sources one after another, separated by blank lines
+pub fn render_sources(sources: &[Source]) -> String {
+    sources.iter().map(Source::render).collect::<Vec<_>>().join("\n\n")
+}
+
+/// Source numbers an answer cites as `[2]` or `[1, 3]`, in order of first citation
+pub fn cited(answer: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for group in answer.split('[').skip(1) {
+        let Some((inside, _)) = group.split_once(']') else {
+            continue;
+        };
+        let parsed: Option<Vec<usize>> = inside.split(',').map(|n| n.trim().parse().ok()).collect();
+        for number in parsed.unwrap_or_default() {
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+    }
+    numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, context: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "path": path,
+            "date": "2025-07-21",
+            "score": 0.8,
+            "snippet": "snippet",
+            "context": context,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pack_dedupes_trims_and_skips() {
+        let long = "word ".repeat(200);
+        let results = vec![
+            result("a.md", "short"),
+            result("a.md", "short"),
+            result("b.md", &long),
+            result("c.md", "also short"),
+        ];
+        // One token per word in this test, and the header is 3 words
+        let words = |text: &str| text.split_whitespace().count();
+
+        let sources = pack_sources(&results, 60, &words);
+        let packed: Vec<(usize, &str, bool)> =
+            sources.iter().map(|s| (s.number, s.path.to_str().unwrap(), s.trimmed)).collect();
+        assert_eq!(packed, vec![(1, "a.md", false), (2, "b.md", true)]);
+        assert_eq!(sources[0].render(), "[1] a.md (2025-07-21)\nshort");
+        assert!(sources[1].text.ends_with("word […]"));
+        assert!(sources.iter().map(|s| s.tokens + 1).sum::<usize>() <= 60);
+
+        // Too little room left to be worth cutting, so the short one after it fits instead
+        let sources = pack_sources(&results, 30, &words);
+        let packed: Vec<&str> = sources.iter().map(|s| s.path.to_str().unwrap()).collect();
+        assert_eq!(packed, vec!["a.md", "c.md"]);
+    }
+
+    #[test]
+    fn test_cited() {
+        assert_eq!(cited("Switched for reproducibility [2][1]. Also flakes [1, 3]. See [note] and [4"), vec![2, 1, 3]);
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+use std::cell::RefCell;
+
+/// How many candidates are fetched for the reranker to choose from
+pub const RERANK_CANDIDATES: usize = 50;
+
+/// Cross-encoder that scores (query, chunk) pairs directly.
+///
+/// Much slower than vector similarity, so it only reorders a shortlist.
+pub struct Reranker {
+    model: RefCell<TextRerank>,
+}
+
+impl Reranker {
+    /// Load the BGE reranker base model
+    pub fn new() -> Result<Self> {
+        // Status goes to stderr so JSON and MCP output on stdout stay clean
+        eprintln!("🤖 Loading reranker model (BAAI/bge-reranker-base)...");
+
+        let model = TextRerank::try_new(RerankInitOptions::new(RerankerModel::BGERerankerBase))?;
+
+        eprintln!("✅ Reranker model loaded successfully!");
+
+        Ok(Self { model: RefCell::new(model) })
+    }
+
+    /// Score each document against the query.
+    ///
+    /// Returns `(index into documents, score in 0..1)`, best first.
+    pub fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<(usize, f32)>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ranked = self.model.borrow_mut().rerank(query, documents.to_vec(), false, None)?;
+
+        // The model outputs logits; squash them so scores read like the vector ones
+        Ok(ranked.into_iter().map(|r| (r.index, sigmoid(r.score))).collect())
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigmoid() {
+        assert_eq!(sigmoid(0.0), 0.5);
+        assert!(sigmoid(8.0) > 0.99);
+        assert!(sigmoid(-8.0) < 0.01);
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::user_dir;
+
+/// File in the user dir holding the saved searches
+pub const SAVED_SEARCHES_FILE: &str = "saved-searches.toml";
+
+/// Searches stored with `mdrag search --save NAME`, by name
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSearches {
+    #[serde(flatten)]
+    searches: BTreeMap<String, SavedSearch>,
+}
+
+/// A search as it was typed, so relative dates like `--during "last week"` move with the calendar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    /// Arguments after `mdrag search`, without global options
+    pub args: Vec<String>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl SavedSearches {
+    /// Where saved searches live, if there is a user dir
+    pub fn default_path() -> Option<PathBuf> {
+        user_dir().map(|dir| dir.join(SAVED_SEARCHES_FILE))
+    }
+
+    /// Read the saved searches; none if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).with_context(|| format!("Invalid saved searches in {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(path, toml::to_string(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.get(name)
+    }
+
+    /// Save `args` under `name`, replacing any search saved as that before
+    pub fn insert(&mut self, name: &str, args: Vec<String>) {
+        self.searches.insert(name.to_string(), SavedSearch { args, saved_at: Utc::now() });
+    }
+
+    /// Names in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        self.searches.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_searches_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join(SAVED_SEARCHES_FILE);
+        assert!(SavedSearches::load(&path).unwrap().names().is_empty());
+
+        let mut saved = SavedSearches::default();
+        saved.insert("weekly-review", vec!["wins and worries".to_string(), "--during".to_string(), "last week".to_string()]);
+        saved.insert("sleep", vec!["sleep".to_string()]);
+        saved.write(&path).unwrap();
+
+        let loaded = SavedSearches::load(&path).unwrap();
+        assert_eq!(loaded.names(), vec!["sleep", "weekly-review"]);
+        assert_eq!(loaded.get("weekly-review"), saved.get("weekly-review"));
+    }
+}
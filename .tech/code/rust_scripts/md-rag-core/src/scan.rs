@@ -0,0 +1,224 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
+
+use crate::frontmatter::{extract_frontmatter, parse_date_from_frontmatter, split_frontmatter};
+use crate::path_date::PathDatePattern;
+use crate::path_filter::PathFilter;
+use crate::schema::date_to_days;
+use crate::tags::extract_tags;
+
+/// A journal file ready for chunking
+pub struct ScanDocument {
+    pub path: String,
+    /// Days since the Unix epoch
+    pub date: i32,
+    /// Markdown body with the frontmatter removed; shared with the threads that chunk it
+    pub content: Arc<str>,
+    /// Line of the file (1-based) where `content` starts
+    pub first_line: usize,
+    /// File modification time, seconds since the Unix epoch
+    pub mtime: i64,
+    /// Parsed frontmatter; empty if the entry has none or it isn't valid YAML
+    pub frontmatter: HashMap<String, serde_yaml::Value>,
+    /// Frontmatter `tags:` and `#hashtags` from the body, lowercased
+    pub tags: Vec<String>,
+}
+
+/// How entries are dated when they're scanned
+#[derive(Debug, Clone, Default)]
+pub struct EntryDates {
+    /// strftime formats accepted for the frontmatter `date` besides `YYYY-MM-DD`
+    pub formats: Vec<String>,
+    /// Where the date is read from the path of an entry without one in its frontmatter
+    pub path: PathDatePattern,
+}
+
+/// Why a markdown file that passed the path filter didn't become a [`ScanDocument`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The file couldn't be read as UTF-8 text
+    Unreadable,
+    /// Dated before `--since`
+    BeforeSince,
+}
+
+/// Threads used for reading and chunking entries when `--jobs` isn't given: one per CPU
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Walk `dir` and yield every markdown file
+pub fn markdown_files(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("md"))
+        .map(|e| e.into_path())
+}
+
+/// Like `markdown_files`, but each directory's entries in file name order, so a
+/// `YYYY/MM/DD.md` journal comes out in date order without collecting it first
+pub fn markdown_files_sorted(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(dir)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("md"))
+        .map(|e| e.into_path())
+}
+
+/// File modification time in seconds since the Unix epoch
+pub fn get_file_mtime(path: &Path) -> Result<i64> {
+    use chrono::{DateTime, Utc};
+
+    let datetime: DateTime<Utc> = fs::metadata(path)?.modified()?.into();
+    Ok(datetime.timestamp())
+}
+
+/// Get the date from a file's metadata (modification time)
+pub fn get_file_date(path: &Path, verbose: bool) -> Result<NaiveDate> {
+    use chrono::{DateTime, Utc};
+
+    let metadata = fs::metadata(path)?;
+    let modified = metadata.modified()?;
+
+    // Convert SystemTime to DateTime<Utc>
+    let datetime: DateTime<Utc> = modified.into();
+    let date = datetime.naive_utc().date();
+
+    if verbose {
+        println!("    → File modified date: {}", date);
+    }
+
+    Ok(date)
+}
+
+/// Read every journal entry that `paths` lets through, dated by frontmatter, then by path, then by mtime.
+///
+/// Files are read on up to `jobs` threads, calling `on_file` after each one with the reason it was skipped, if it was.
+pub fn scan_journal_directory(
+    dir: &Path,
+    paths: &PathFilter,
+    since: Option<&str>,
+    dates: &EntryDates,
+    jobs: usize,
+    verbose: bool,
+    on_file: &(dyn Fn(Option<SkipReason>) + Sync),
+) -> Result<Vec<ScanDocument>> {
+    // Parse since date if provided
+    let since_date = since
+        .map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d"))
+        .transpose()?;
+
+    let files: Vec<PathBuf> = paths.markdown_files().collect();
+    let per_thread = files.len().div_ceil(jobs.max(1)).max(1);
+    let scanned: Vec<Result<Vec<ScanDocument>>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = files
+            .chunks(per_thread)
+            .map(|files| {
+                scope.spawn(move || {
+                    let mut documents = Vec::new();
+                    for path in files {
+                        match scan_file(dir, path, since_date, dates, verbose)? {
+                            Ok(document) => {
+                                documents.push(document);
+                                on_file(None);
+                            }
+                            Err(reason) => on_file(Some(reason)),
+                        }
+                    }
+                    Ok(documents)
+                })
+            })
+            .collect();
+        workers.into_iter().map(|worker| worker.join().expect("scan thread panicked")).collect()
+    });
+
+    let mut documents = Vec::new();
+    for batch in scanned {
+        documents.extend(batch?);
+    }
+
+    // Sort by date
+    documents.sort_by_key(|d| d.date);
+
+    Ok(documents)
+}
+
+/// Read one entry, or say why it was skipped
+fn scan_file(
+    dir: &Path,
+    path: &Path,
+    since: Option<NaiveDate>,
+    dates: &EntryDates,
+    verbose: bool,
+) -> Result<Result<ScanDocument, SkipReason>> {
+    if verbose {
+        println!("  Checking: {}", path.display());
+    }
+
+    // Read file content
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("  ⚠️  Error reading {}: {}", path.display(), e);
+            return Ok(Err(SkipReason::Unreadable));
+        }
+    };
+
+    // Extract date from frontmatter, else from the path, else use file modification time
+    let frontmatter = extract_frontmatter(&content);
+    let frontmatter_date = match &frontmatter {
+        Ok(frontmatter) => parse_date_from_frontmatter(frontmatter, &dates.formats)
+            .inspect_err(|e| if frontmatter.contains_key("date") {
+                eprintln!("  ⚠️  Invalid date in frontmatter for {}: {}", path.display(), e);
+            })
+            .ok(),
+        Err(_) => None,
+    };
+    let date = match frontmatter_date {
+        Some(date) => date,
+        None => match dates.path.date(path.strip_prefix(dir).unwrap_or(path)) {
+            Some(date) => {
+                if verbose {
+                    println!("  📅 Using date from path for: {} ({})", path.display(), date);
+                }
+                date
+            }
+            None => {
+                if verbose {
+                    println!("  📅 Using file modification time for: {} (no date in frontmatter or path)", path.display());
+                }
+                get_file_date(path, verbose)?
+            }
+        },
+    };
+
+    // Check if file is too old
+    if let Some(since) = since && date < since {
+        if verbose {
+            println!("  ⏭️  Skipping {} (older than {})", path.display(), since);
+        }
+        return Ok(Err(SkipReason::BeforeSince));
+    }
+
+    let (_, body) = split_frontmatter(&content);
+    let frontmatter = frontmatter.unwrap_or_default();
+
+    Ok(Ok(ScanDocument {
+        path: path.to_string_lossy().to_string(),
+        date: date_to_days(date),
+        content: body.into(),
+        first_line: content[..content.len() - body.len()].matches('\n').count() + 1,
+        mtime: get_file_mtime(path)?,
+        tags: extract_tags(body, &frontmatter),
+        frontmatter,
+    }))
+}
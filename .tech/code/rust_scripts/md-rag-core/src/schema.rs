@@ -0,0 +1,304 @@
+use arrow::array::{FixedSizeListArray, ListArray, ListBuilder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Float32Type, Schema};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::metadata::{schema_fields, MetadataField};
+use crate::metric::Metric;
+
+/// LanceDB database directory inside the configured lance dir
+pub const DB_NAME: &str = "journal.lance";
+
+/// Table holding the document chunks unless `--table` names another
+pub const DEFAULT_TABLE_NAME: &str = "documents";
+
+/// Table caching embeddings by chunk text and model, shared across rebuilds. Each embedding
+/// dimension has its own, named by [`cache_table_name`]; an unsuffixed one is from older versions
+pub const CACHE_TABLE_NAME: &str = "embedding_cache";
+
+/// Suffix of the table a rebuild is written to before it replaces the live one
+const REBUILD_SUFFIX: &str = "_tmp";
+
+/// Suffix the live table is renamed to while its rebuild is swapped in
+const RETIRED_SUFFIX: &str = "_old";
+
+/// Schema metadata key holding the model the chunk table was embedded with
+pub const MODEL_METADATA_KEY: &str = "embedding_model";
+
+/// Schema metadata key holding the embedding dimension
+pub const DIMENSION_METADATA_KEY: &str = "embedding_dimension";
+
+/// Schema metadata key recording whether the model produces unit-length vectors
+pub const NORMALIZED_METADATA_KEY: &str = "embedding_normalized";
+
+/// Schema metadata key holding the distance metric the table is searched with
+pub const METRIC_METADATA_KEY: &str = "distance_metric";
+
+/// Schema metadata key naming the cipher the `content` column is encrypted with, if it is
+pub const ENCRYPTION_METADATA_KEY: &str = "content_encryption";
+
+/// Schema metadata key present when entries were cleaned with fenced code blocks left out
+pub const CODE_BLOCKS_METADATA_KEY: &str = "skip_code_blocks";
+
+/// The only cipher `content` is encrypted with, by `crypto::ContentCipher`
+pub const CONTENT_CIPHER: &str = "aes-256-gcm";
+
+/// Schema metadata key listing the frontmatter fields stored as columns
+pub const FIELDS_METADATA_KEY: &str = "metadata_fields";
+
+/// Full path to the LanceDB database under `lance_dir`
+pub fn db_path(lance_dir: &Path) -> PathBuf {
+    lance_dir.join(DB_NAME)
+}
+
+/// Directory holding a table's files in the database at `lance_path`
+pub fn table_dir(lance_path: &Path, table: &str) -> PathBuf {
+    lance_path.join(format!("{}.lance", table))
+}
+
+/// Where a rebuild of `table` is written, e.g. `documents_tmp`
+pub fn rebuild_table_name(table: &str) -> String {
+    format!("{}{}", table, REBUILD_SUFFIX)
+}
+
+/// Where `table` is moved while its rebuild takes its place
+pub fn retired_table_name(table: &str) -> String {
+    format!("{}{}", table, RETIRED_SUFFIX)
+}
+
+/// The embedding cache for vectors of `embedding_dim`, e.g. `embedding_cache_384`
+pub fn cache_table_name(embedding_dim: usize) -> String {
+    format!("{}_{}", CACHE_TABLE_NAME, embedding_dim)
+}
+
+/// Whether `name` is an embedding cache rather than a collection
+pub fn is_cache_table(name: &str) -> bool {
+    name.strip_prefix(CACHE_TABLE_NAME).is_some_and(|rest| {
+        rest.is_empty() || rest.strip_prefix('_').is_some_and(|dim| dim.parse::<usize>().is_ok())
+    })
+}
+
+/// Whether `name` is a rebuild or retired copy of another table rather than a collection
+pub fn is_swap_table(name: &str) -> bool {
+    name.ends_with(REBUILD_SUFFIX) || name.ends_with(RETIRED_SUFFIX)
+}
+
+/// Arrow schema for the chunk table, tagged with the embedding model and metric.
+///
+/// `metadata_fields` become nullable columns after the built-in ones; `encrypted` records
+/// that `content` holds `crypto::ContentCipher` ciphertext, and `skip_code_blocks` that entries
+/// were cleaned (and hashed) without their fenced code.
+pub fn documents_schema(
+    model_id: &str,
+    embedding_dim: usize,
+    normalized: bool,
+    metric: Metric,
+    metadata_fields: &[MetadataField],
+    encrypted: bool,
+    skip_code_blocks: bool,
+) -> Arc<Schema> {
+    let field_names: Vec<&str> = metadata_fields.iter().map(|f| f.name.as_str()).collect();
+    let mut metadata = HashMap::from([
+        (MODEL_METADATA_KEY.to_string(), model_id.to_string()),
+        (DIMENSION_METADATA_KEY.to_string(), embedding_dim.to_string()),
+        (NORMALIZED_METADATA_KEY.to_string(), normalized.to_string()),
+        (METRIC_METADATA_KEY.to_string(), metric.to_string()),
+        (FIELDS_METADATA_KEY.to_string(), field_names.join(",")),
+    ]);
+    if encrypted {
+        metadata.insert(ENCRYPTION_METADATA_KEY.to_string(), CONTENT_CIPHER.to_string());
+    }
+    if skip_code_blocks {
+        metadata.insert(CODE_BLOCKS_METADATA_KEY.to_string(), true.to_string());
+    }
+
+    let mut fields = vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("date", DataType::Int32, false),
+        Field::new("content", DataType::Utf8, false),
+        Field::new("section", DataType::Utf8, false),       // Heading path, e.g. "Evening > Notes"
+        Field::new("chunk_index", DataType::Int32, false),  // Which chunk in document
+        Field::new("total_chunks", DataType::Int32, false), // Total chunks in document
+        Field::new("content_hash", DataType::Utf8, false),  // SHA-256 of the cleaned entry
+        Field::new("mtime", DataType::Int64, false),        // File mtime, seconds since epoch
+        Field::new("start_line", DataType::Int32, true),    // First line of the chunk in the file
+        Field::new("end_line", DataType::Int32, true),      // Last line of the chunk in the file
+        tags_field(),                                       // The entry's tags, lowercased
+        embedding_field(embedding_dim),
+    ];
+    fields.extend(metadata_fields.iter().map(MetadataField::arrow_field));
+
+    Arc::new(Schema::new_with_metadata(fields, metadata))
+}
+
+/// Model a chunk table was embedded with; `None` for indexes built before models were configurable
+pub fn schema_model(schema: &Schema) -> Option<&str> {
+    schema.metadata().get(MODEL_METADATA_KEY).map(String::as_str)
+}
+
+/// Length of the vectors in a table's `embedding` column
+pub fn schema_dimension(schema: &Schema) -> Option<usize> {
+    match schema.field_with_name("embedding").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(_, size)) => Some(*size as usize),
+        _ => None,
+    }
+}
+
+/// Whether a chunk table holds unit-length vectors; `None` if it was never recorded
+pub fn schema_normalized(schema: &Schema) -> Option<bool> {
+    schema.metadata().get(NORMALIZED_METADATA_KEY).and_then(|v| v.parse().ok())
+}
+
+/// Metric a chunk table was built for; `None` for indexes from before it was configurable, which used L2
+pub fn schema_metric(schema: &Schema) -> Option<Metric> {
+    schema.metadata().get(METRIC_METADATA_KEY).and_then(|v| v.parse().ok())
+}
+
+/// Whether a chunk table's `content` column is encrypted
+pub fn schema_encrypted(schema: &Schema) -> bool {
+    schema.metadata().contains_key(ENCRYPTION_METADATA_KEY)
+}
+
+/// Whether a chunk table's entries were cleaned without their fenced code blocks
+pub fn schema_skips_code_blocks(schema: &Schema) -> bool {
+    schema.metadata().contains_key(CODE_BLOCKS_METADATA_KEY)
+}
+
+/// Frontmatter fields a chunk table stores as columns, with their types
+pub fn schema_metadata_fields(schema: &Schema) -> Vec<MetadataField> {
+    let names: Vec<String> = schema.metadata()
+        .get(FIELDS_METADATA_KEY)
+        .map(|list| list.split(',').filter(|n| !n.is_empty()).map(String::from).collect())
+        .unwrap_or_default();
+    schema_fields(schema, &names)
+}
+
+/// Arrow schema for the embedding cache table
+pub fn cache_schema(embedding_dim: usize) -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Utf8, false), // SHA-256 of model id + chunk text
+        embedding_field(embedding_dim),
+    ]))
+}
+
+fn embedding_field(embedding_dim: usize) -> Field {
+    Field::new(
+        "embedding",
+        DataType::FixedSizeList(
+            Arc::new(Field::new("item", DataType::Float32, true)),
+            embedding_dim as i32,
+        ),
+        false,
+    )
+}
+
+fn tags_field() -> Field {
+    Field::new("tags", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false)
+}
+
+/// Build the `tags` column from one tag list per row
+pub fn tags_array(rows: &[&[String]]) -> ListArray {
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for tags in rows {
+        for tag in *tags {
+            builder.values().append_value(tag);
+        }
+        builder.append(true);
+    }
+    builder.finish()
+}
+
+/// Build the `embedding` column from one vector per row
+pub fn embedding_array(embeddings: Vec<Vec<f32>>, embedding_dim: usize) -> FixedSizeListArray {
+    FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+        embeddings.into_iter().map(|v| Some(v.into_iter().map(Some).collect::<Vec<_>>())),
+        embedding_dim as i32,
+    )
+}
+
+/// Quote a string for use in a LanceDB filter expression
+pub fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+/// Dates are stored as days since the Unix epoch
+pub fn date_to_days(date: NaiveDate) -> i32 {
+    (date - epoch()).num_days() as i32
+}
+
+/// Convert a stored `date` column value back to a date
+pub fn days_to_date(days: i32) -> NaiveDate {
+    epoch() + chrono::Duration::days(days as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::FieldKind;
+    use arrow::array::Array;
+
+    #[test]
+    fn test_date_round_trip() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap();
+        assert_eq!(days_to_date(date_to_days(date)), date);
+        assert_eq!(date_to_days(epoch()), 0);
+    }
+
+    #[test]
+    fn test_schema_records_model() {
+        let mood = MetadataField { name: "mood".to_string(), kind: FieldKind::Number };
+        let schema = documents_schema("BAAI/bge-small-en-v1.5", 384, true, Metric::Dot, std::slice::from_ref(&mood), false, false);
+        assert_eq!(schema_model(&schema), Some("BAAI/bge-small-en-v1.5"));
+        assert_eq!(schema.metadata()[DIMENSION_METADATA_KEY], "384");
+        assert_eq!(schema_dimension(&schema), Some(384));
+        assert_eq!(schema_normalized(&schema), Some(true));
+        assert_eq!(schema_model(&Schema::empty()), None);
+        assert_eq!(schema_normalized(&Schema::empty()), None);
+        assert_eq!(schema_metric(&schema), Some(Metric::Dot));
+        assert_eq!(schema_metric(&Schema::empty()), None);
+        assert_eq!(schema_metadata_fields(&schema), vec![mood]);
+        assert!(schema_metadata_fields(&Schema::empty()).is_empty());
+        assert!(!schema_encrypted(&schema));
+        assert!(schema_encrypted(&documents_schema("m", 4, true, Metric::Cosine, &[], true, false)));
+        assert!(!schema_skips_code_blocks(&schema));
+        assert!(schema_skips_code_blocks(&documents_schema("m", 4, true, Metric::Cosine, &[], false, true)));
+    }
+
+    #[test]
+    fn test_tags_array_matches_schema() {
+        let schema = documents_schema("m", 4, true, Metric::Cosine, &[], false, false);
+        let tags = vec!["rust".to_string(), "work".to_string()];
+        let array = tags_array(&[&tags, &[]]);
+        assert_eq!(array.data_type(), schema.field_with_name("tags").unwrap().data_type());
+        assert_eq!(array.value_length(0), 2);
+        assert_eq!(array.value_length(1), 0);
+    }
+
+    #[test]
+    fn test_swap_table_names() {
+        assert_eq!(rebuild_table_name("documents"), "documents_tmp");
+        assert!(is_swap_table(&retired_table_name("notes")));
+        assert!(!is_swap_table("documents"));
+        assert_eq!(table_dir(Path::new("db"), "notes"), Path::new("db/notes.lance"));
+    }
+
+    #[test]
+    fn test_cache_table_names() {
+        assert_eq!(cache_table_name(384), "embedding_cache_384");
+        assert!(is_cache_table(&cache_table_name(768)));
+        assert!(is_cache_table(CACHE_TABLE_NAME));
+        assert!(!is_cache_table("embedding_cache_notes"));
+        assert!(!is_cache_table("documents"));
+    }
+
+    #[test]
+    fn test_sql_string_escapes_quotes() {
+        assert_eq!(sql_string("journal/it's.md"), "'journal/it''s.md'");
+    }
+}
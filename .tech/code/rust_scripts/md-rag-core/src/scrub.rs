@@ -0,0 +1,144 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::chunk::fence_marker;
+use crate::config::ScrubConfig;
+
+/// A Dataview inline field on a line of its own, like `mood:: 7` or `- energy:: high`
+static INLINE_FIELD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:[-*+]\s+)?[A-Za-z_][\w-]*::(?:\s|$)").unwrap()
+});
+
+/// A top-level `key:` line of a YAML mapping
+static YAML_KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z_][\w-]*:(?:\s|$)").unwrap()
+});
+
+/// Comment delimiters: opening, closing
+const HTML_COMMENT: (&str, &str) = ("<!--", "-->");
+const OBSIDIAN_COMMENT: (&str, &str) = ("%%", "%%");
+
+/// Remove private annotations and machine metadata from an entry body before it is embedded or
+/// shown: HTML and Obsidian comments (inline or spanning lines), ```dataview/```dataviewjs blocks
+/// and `key:: value` field lines, and `---` blocks of YAML keys left in the body. Lines emptied
+/// by a comment are dropped; other fenced code is kept as it is, unless `code_blocks` drops it too
+pub fn scrub(content: &str, options: &ScrubConfig) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut scrubbed = String::with_capacity(content.len());
+    // Marker of the open fence and whether its block is dropped
+    let mut fence: Option<(&str, bool)> = None;
+    // Closing delimiter of the open comment
+    let mut comment_end: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        let trimmed = line.trim_start();
+
+        if comment_end.is_none() {
+            if let Some((marker, dropped)) = fence {
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+                if !dropped {
+                    scrubbed.push_str(line);
+                    scrubbed.push('\n');
+                }
+                continue;
+            }
+            if let Some(marker) = fence_marker(line) {
+                let info = trimmed.trim_start_matches(marker.chars().next().unwrap()).trim();
+                let dropped = options.code_blocks || (options.dataview && matches!(info, "dataview" | "dataviewjs"));
+                fence = Some((marker, dropped));
+                if !dropped {
+                    scrubbed.push_str(line);
+                    scrubbed.push('\n');
+                }
+                continue;
+            }
+            if options.frontmatter && line.trim_end() == "---" && let Some(len) = yaml_block_len(&lines[i..]) {
+                i += len;
+                continue;
+            }
+            if options.dataview && INLINE_FIELD_RE.is_match(line) {
+                continue;
+            }
+        }
+
+        let (text, removed) = strip_comments(line, &mut comment_end, options);
+        if removed && text.trim().is_empty() {
+            continue;
+        }
+        scrubbed.push_str(&text);
+        scrubbed.push('\n');
+    }
+    scrubbed
+}
+
+/// A line with the enabled kinds of comments cut out, and whether anything was. `comment_end`
+/// carries a comment that is still open over to the next line
+fn strip_comments(line: &str, comment_end: &mut Option<&'static str>, options: &ScrubConfig) -> (String, bool) {
+    let delimiters = [(HTML_COMMENT, options.html_comments), (OBSIDIAN_COMMENT, options.obsidian_comments)];
+    let mut text = String::new();
+    let mut rest = line;
+    let mut removed = false;
+    loop {
+        if let Some(end) = *comment_end {
+            removed = true;
+            let Some(pos) = rest.find(end) else {
+                return (text, removed);
+            };
+            rest = &rest[pos + end.len()..];
+            *comment_end = None;
+        }
+        let open = delimiters
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .filter_map(|((open, close), _)| rest.find(open).map(|pos| (pos, *open, *close)))
+            .min_by_key(|(pos, _, _)| *pos);
+        let Some((pos, open, close)) = open else {
+            text.push_str(rest);
+            return (text, removed);
+        };
+        text.push_str(&rest[..pos]);
+        rest = &rest[pos + open.len()..];
+        *comment_end = Some(close);
+    }
+}
+
+/// Lines up to and including the closing `---` when `lines` (after an opening `---`) hold a YAML
+/// mapping of `key:` lines, as a second frontmatter pasted into the body does
+fn yaml_block_len(lines: &[&str]) -> Option<usize> {
+    let end = lines.iter().position(|line| line.trim_end() == "---")?;
+    let block = &lines[..end];
+    if !block.first().is_some_and(|line| YAML_KEY_RE.is_match(line)) {
+        return None;
+    }
+    let keyed = block.iter().all(|line| {
+        line.trim().is_empty() || line.starts_with([' ', '\t', '-', '#']) || YAML_KEY_RE.is_match(line)
+    });
+    let mapping = matches!(serde_yaml::from_str(&block.join("\n")), Ok(serde_yaml::Value::Mapping(_)));
+    (keyed && mapping).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub() {
+        let content = "## Evening\nWalked home <!-- with J, don't index --> in the rain.\n<!--\nprivate\n-->\n%% todo: ask Ana %%\nSlept early. %%half true%%\nmood:: 6\n- energy:: low\n```dataview\nTABLE mood FROM \"journal\"\n```\n```html\n<!-- kept: it's code -->\n```\n---\ndate: 2025-07-01\ntags: [pasted]\n---\nRead `std::fmt` docs.\n\n---\nA rule, then prose: kept.\n";
+        let scrubbed = scrub(content, &ScrubConfig::default());
+        assert_eq!(
+            scrubbed,
+            "## Evening\nWalked home  in the rain.\nSlept early. \n```html\n<!-- kept: it's code -->\n```\nRead `std::fmt` docs.\n\n---\nA rule, then prose: kept.\n",
+        );
+
+        let options = ScrubConfig { html_comments: false, dataview: false, ..Default::default() };
+        let scrubbed = scrub(content, &options);
+        assert!(scrubbed.contains("<!-- with J"));
+        assert!(scrubbed.contains("- energy:: low\n```dataview\n"));
+        assert!(!scrubbed.contains("ask Ana"));
+        assert!(!scrubbed.contains("tags: [pasted]"));
+    }
+}
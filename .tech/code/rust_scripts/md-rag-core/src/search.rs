@@ -0,0 +1,1327 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use lancedb::index::scalar::FullTextSearchQuery;
+use lancedb::query::{QueryBase, ExecutableQuery, Select};
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use lancedb::Table;
+use arrow::array::{Array, FixedSizeListArray, Float32Array, Int32Array, StringArray};
+use futures::TryStreamExt;
+use regex::Regex;
+
+use crate::crypto::reveal;
+use crate::embeddings::{Backend, EmbeddingGenerator, DEFAULT_MODEL};
+use crate::frontmatter::split_frontmatter;
+use crate::fusion::{reciprocal_rank_fusion, RRF_K};
+use crate::hash::content_hash;
+use crate::metric::{normalize, Metric};
+use crate::rerank::{Reranker, RERANK_CANDIDATES};
+use crate::schema::{
+    date_to_days, days_to_date, is_swap_table, schema_dimension, schema_encrypted, schema_metric, schema_model,
+    schema_normalized, sql_string, is_cache_table,
+};
+use crate::snippet::{extract_snippet, extract_snippets, query_terms, Snippet, SNIPPET_CHARS};
+use crate::template_filter::TemplateFilter;
+
+/// How many candidates each retriever contributes per requested result in hybrid mode
+const HYBRID_CANDIDATE_FACTOR: usize = 4;
+
+/// Chunks fetched per requested result when grouping, so a few verbose entries can't starve the rest
+const GROUP_CANDIDATE_FACTOR: usize = 5;
+
+/// Candidates per requested result with recency weighting, so recent but slightly less similar chunks can rise
+const RECENCY_CANDIDATE_FACTOR: usize = 4;
+
+/// Share of the score that decays with age; the rest is pure similarity
+const RECENCY_WEIGHT: f32 = 0.5;
+
+/// Candidates per requested result when `must_contain` or `must_match` will drop some
+const CONTENT_FILTER_CANDIDATE_FACTOR: usize = 10;
+
+/// Default for `keyword_fallback`: a cosine similarity of 0.2, which relevant chunks clear easily
+pub const KEYWORD_FALLBACK_SCORE: f32 = 0.6;
+
+/// Earliest year `on_this_day` looks back to
+const ON_THIS_DAY_FIRST_YEAR: i32 = 1900;
+
+/// What to search for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub query: String,
+    pub after: Option<NaiveDate>,
+    pub before: Option<NaiveDate>,
+    pub limit: usize,
+    /// Results to skip, for fetching the pages after the first
+    #[serde(default)]
+    pub offset: usize,
+    /// Fuse vector results with BM25 full-text results
+    #[serde(default)]
+    pub hybrid: bool,
+    /// Reorder a larger candidate pool with a cross-encoder
+    #[serde(default)]
+    pub rerank: bool,
+    /// Return one result per entry, showing its best chunk
+    #[serde(default)]
+    pub group_by_doc: bool,
+    /// Include this many chunks either side of each hit as `context`
+    #[serde(default)]
+    pub expand: usize,
+    /// Include the whole cleaned entry as `context`
+    #[serde(default)]
+    pub full_doc: bool,
+    /// Include the hit's whole chunk as `context` when neither `expand` nor `full_doc` gives more
+    #[serde(default)]
+    pub chunk_text: bool,
+    /// Also return up to this many non-overlapping passages of each hit as `snippets`
+    #[serde(default)]
+    pub snippets: usize,
+    /// Favor recent entries: an entry this many days old keeps 75% of its score
+    #[serde(default)]
+    pub recency_halflife: Option<f32>,
+    /// Drop results scoring below this, even if fewer than `limit` remain
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Override the metric recorded with the index
+    #[serde(default)]
+    pub metric: Option<Metric>,
+    /// ANN index partitions to probe; more is slower and more accurate
+    #[serde(default)]
+    pub nprobes: Option<usize>,
+    /// Re-score this many times `limit` ANN candidates with exact distances
+    #[serde(default)]
+    pub refine_factor: Option<u32>,
+    /// Apply the date filter before the vector search (exact) rather than to its results
+    /// (faster with an ANN index, but may return fewer than `limit` hits)
+    #[serde(default = "default_prefilter")]
+    pub prefilter: bool,
+    /// SQL condition on metadata columns, e.g. `mood >= 7 AND project = 'mdrag'`
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Only entries carrying every one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only chunks containing every one of these strings, ignoring case
+    #[serde(default)]
+    pub must_contain: Vec<String>,
+    /// Only chunks matching this regular expression
+    #[serde(default)]
+    pub must_match: Option<String>,
+    /// Only entries whose path starts with this, e.g. `journal/2025`
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    /// Only entries whose path matches this glob, e.g. `**/topics/*.md`
+    #[serde(default)]
+    pub path_glob: Option<String>,
+    /// Only entries from this month and day, in any year
+    #[serde(default)]
+    pub on_this_day: Option<(u32, u32)>,
+    /// When no vector hit scores at least this, scan chunk content for the query's words instead.
+    /// On the cosine metric's scale; other metrics compare with `Metric::from_cosine_score` of it
+    #[serde(default)]
+    pub keyword_fallback: Option<f32>,
+    /// Search with the mean vector of this indexed entry instead of the query, leaving the entry out
+    #[serde(default)]
+    pub like: Option<String>,
+    /// Other phrasings searched alongside `query`, their rankings fused with reciprocal rank fusion
+    #[serde(default)]
+    pub alternate_queries: Vec<String>,
+}
+
+fn default_prefilter() -> bool {
+    true
+}
+
+/// Ten results for `query` over the whole index, with date prefiltering and the keyword fallback on
+impl Default for SearchQuery {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            after: None,
+            before: None,
+            limit: 10,
+            offset: 0,
+            hybrid: false,
+            rerank: false,
+            group_by_doc: false,
+            expand: 0,
+            full_doc: false,
+            chunk_text: false,
+            snippets: 0,
+            recency_halflife: None,
+            min_score: None,
+            metric: None,
+            nprobes: None,
+            refine_factor: None,
+            prefilter: default_prefilter(),
+            filter: None,
+            tags: Vec::new(),
+            must_contain: Vec::new(),
+            must_match: None,
+            path_prefix: None,
+            path_glob: None,
+            on_this_day: None,
+            keyword_fallback: Some(KEYWORD_FALLBACK_SCORE),
+            like: None,
+            alternate_queries: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub path: PathBuf,
+    pub date: NaiveDate,
+    pub score: f32,
+    pub snippet: String,
+    /// Headings above the matching chunk, e.g. `Evening > Notes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// First line of the matching chunk in the file (1-based)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    /// Last line of the matching chunk in the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// With `group_by_doc`: how many of the entry's chunks matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching_chunks: Option<usize>,
+    /// With `group_by_doc`: sum of the matching chunks' scores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate_score: Option<f32>,
+    /// With `snippets`: the chunk's best passages in order, each with its character offsets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippets: Option<Vec<Snippet>>,
+    /// With `expand` or `full_doc`: the surrounding chunks or the whole entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Found by the keyword fallback rather than by similarity; `score` is then the share of query words it contains
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyword_match: bool,
+    /// When searching several collections: the one this result came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+}
+
+/// One page of results and how many the search found in all
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Results across all pages: every chunk (or entry) passing the filters, or, when content,
+    /// glob or score checks drop candidates after the search, those found so far, which is one
+    /// more than this page reaches while more remain
+    pub total: usize,
+}
+
+/// Order results are shown in. Which results are returned is always decided by score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Best match first
+    #[default]
+    Score,
+    /// Oldest entry first, for following a project or a mood over time
+    Date,
+}
+
+impl SortOrder {
+    /// Put already ranked results in this order; results from the same day keep their ranking
+    pub fn apply(self, results: &mut [SearchResult]) {
+        if self == SortOrder::Date {
+            results.sort_by(|a, b| a.date.cmp(&b.date));
+        }
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "score" | "relevance" => Ok(SortOrder::Score),
+            "date" => Ok(SortOrder::Date),
+            _ => Err(format!("unknown sort order '{}' (expected 'score' or 'date')", s)),
+        }
+    }
+}
+
+/// Shown wherever a search finds no index to search
+pub const MISSING_INDEX_HINT: &str = "Build the index first with `mdrag index`";
+
+/// Whether the chunk table `table_name` has been built at `lance_path`
+pub async fn index_exists(lance_path: &str, table_name: &str) -> Result<bool> {
+    // Connecting would create the directory, so check before touching it
+    if !Path::new(lance_path).exists() {
+        return Ok(false);
+    }
+    let db = lancedb::connect(lance_path)
+        .execute()
+        .await?;
+    let tables = db.table_names().execute().await?;
+    Ok(tables.iter().any(|name| name == table_name))
+}
+
+/// Every collection (chunk table) at `lance_path`, leaving out the embedding cache
+pub async fn list_collections(lance_path: &str) -> Result<Vec<String>> {
+    if !Path::new(lance_path).exists() {
+        return Ok(Vec::new());
+    }
+    let db = lancedb::connect(lance_path)
+        .execute()
+        .await?;
+    let mut tables = db.table_names().execute().await?;
+    tables.retain(|name| !is_cache_table(name) && !is_swap_table(name));
+    Ok(tables)
+}
+
+/// Combine the results of searching each collection into one ranking, tagging each result with
+/// its collection.
+///
+/// Scores don't compare across collections: they may come from other models or metrics, from
+/// fusion (hybrid, several queries) or from a reranker. So the rankings are merged by position
+/// with reciprocal rank fusion, which interleaves them, earlier collections first among results
+/// of the same rank; each result keeps the score its own collection gave it.
+///
+/// Each collection must have been searched for its first `offset + limit` results.
+pub fn merge_collections(results: Vec<(String, SearchPage)>, offset: usize, limit: usize) -> SearchPage {
+    let total = results.iter().map(|(_, page)| page.total).sum();
+    let rankings: Vec<Vec<(usize, usize)>> = results
+        .iter()
+        .enumerate()
+        .map(|(c, (_, page))| (0..page.results.len()).map(|r| (c, r)).collect())
+        .collect();
+    let order: Vec<(usize, usize)> = reciprocal_rank_fusion(&rankings, RRF_K)
+        .into_iter()
+        .map(|(key, _)| key)
+        .skip(offset)
+        .take(limit)
+        .collect();
+
+    let mut pages: Vec<(String, Vec<Option<SearchResult>>)> = results
+        .into_iter()
+        .map(|(collection, page)| (collection, page.results.into_iter().map(Some).collect()))
+        .collect();
+    let merged = order
+        .into_iter()
+        .filter_map(|(c, r)| {
+            let (collection, results) = &mut pages[c];
+            let result = results[r].take()?;
+            Some(SearchResult { collection: Some(collection.clone()), ..result })
+        })
+        .collect();
+    SearchPage { results: merged, total }
+}
+
+/// Opaque token for the page starting at `offset`, tied to the rest of the request so it
+/// can't be replayed against a different search
+pub fn page_cursor(request: &SearchQuery, offset: usize) -> String {
+    format!("{}.{}", offset, request_fingerprint(request))
+}
+
+/// The offset a cursor from [`page_cursor`] points at
+pub fn cursor_offset(request: &SearchQuery, cursor: &str) -> Result<usize> {
+    let (offset, fingerprint) = cursor.split_once('.').context("Malformed cursor")?;
+    if fingerprint != request_fingerprint(request) {
+        bail!("This cursor belongs to a different search; repeat the query and filters it came from");
+    }
+    offset.parse().context("Malformed cursor")
+}
+
+fn request_fingerprint(request: &SearchQuery) -> String {
+    let unpaged = SearchQuery { offset: 0, ..request.clone() };
+    content_hash(&serde_json::to_string(&unpaged).unwrap_or_default())[..12].to_string()
+}
+
+/// Chunks in a collection passing `request`'s date, tag, path and `--where` filters, or the
+/// entries they belong to when `by_entry` is set
+pub async fn count_filtered(lance_path: &str, table_name: &str, request: &SearchQuery, by_entry: bool) -> Result<usize> {
+    let db = lancedb::connect(lance_path).execute().await?;
+    let table = db.open_table(table_name).execute().await?;
+    count_matching(&table, row_filter(request).as_deref(), by_entry).await
+}
+
+/// Whether counting `request`'s results takes a search rather than [`count_filtered`]: query text
+/// can turn to the keyword fallback, and a score threshold, content patterns or a path glob are
+/// only checked on what the search finds
+pub fn count_needs_search(request: &SearchQuery) -> bool {
+    !request.query.is_empty()
+        || request.like.is_some()
+        || request.min_score.is_some()
+        || !request.must_contain.is_empty()
+        || request.must_match.is_some()
+        || request.path_glob.is_some()
+}
+
+/// `request` as a single page with room for all `chunks` that pass its filters, so the page's
+/// total is exact. Context and extra snippets are left out, and so is reranking, which would run
+/// the cross-encoder over every candidate; a score threshold then applies to the search scores
+pub fn count_query(request: &SearchQuery, chunks: usize) -> SearchQuery {
+    SearchQuery {
+        offset: 0,
+        limit: chunks,
+        expand: 0,
+        full_doc: false,
+        chunk_text: false,
+        snippets: 0,
+        rerank: false,
+        ..request.clone()
+    }
+}
+
+/// Load the embedding model for searching: `requested` if given, otherwise the
+/// model the index was built with.
+///
+/// Fails if the model cannot produce vectors comparable to the index, unless `force` is set.
+pub async fn load_search_model(
+    lance_path: &str,
+    table_name: &str,
+    backend: Backend,
+    requested: Option<&str>,
+    force: bool,
+) -> Result<EmbeddingGenerator> {
+    let db = lancedb::connect(lance_path)
+        .execute()
+        .await?;
+    let table = db.open_table(table_name)
+        .execute()
+        .await?;
+    let schema = table.schema().await?;
+
+    let model_id = requested.unwrap_or(schema_model(&schema).unwrap_or(DEFAULT_MODEL));
+    let generator = EmbeddingGenerator::load(backend, model_id)?;
+    if !force {
+        verify_index_model(&schema, &generator)?;
+    }
+
+    Ok(generator)
+}
+
+/// Check that `generator` produces vectors comparable to those stored in a chunk table
+pub fn verify_index_model(schema: &Schema, generator: &EmbeddingGenerator) -> Result<()> {
+    // Tables from before models were configurable were always embedded with the default
+    let indexed_model = schema_model(schema).unwrap_or(DEFAULT_MODEL);
+    let indexed_dim = schema_dimension(schema);
+
+    let mut problems = Vec::new();
+    if !indexed_model.eq_ignore_ascii_case(generator.model_id()) {
+        problems.push(format!("model {} vs {}", indexed_model, generator.model_id()));
+    }
+    if let Some(dim) = indexed_dim && dim != generator.embedding_dimension() {
+        problems.push(format!("{} vs {} dimensions", dim, generator.embedding_dimension()));
+    }
+    if let Some(normalized) = schema_normalized(schema) && normalized != generator.is_normalized() {
+        problems.push(format!(
+            "{} vs {} vectors",
+            if normalized { "normalized" } else { "unnormalized" },
+            if generator.is_normalized() { "normalized" } else { "unnormalized" },
+        ));
+    }
+
+    if !problems.is_empty() {
+        bail!(
+            "Index is incompatible with the search model ({}). Search without --model to use {}, \
+             re-index with `mdrag index --rebuild --model {}`, or pass --force to search anyway.",
+            problems.join(", "),
+            indexed_model,
+            generator.model_id(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Run a search against the journal index.
+///
+/// The models are passed in so long-running callers (the MCP server) can keep
+/// them loaded between queries. `reranker` is required when `request.rerank` is set.
+/// `template` is the configured template filter, used to clean `--full-doc` context.
+pub async fn search_index(
+    lance_path: &str,
+    table_name: &str,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: Option<&Reranker>,
+    template: &TemplateFilter,
+    request: &SearchQuery,
+) -> Result<Vec<SearchResult>> {
+    Ok(search_index_page(lance_path, table_name, embedding_generator, reranker, template, request).await?.results)
+}
+
+/// [`search_index`], also reporting how many results there were before paging
+pub async fn search_index_page(
+    lance_path: &str,
+    table_name: &str,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: Option<&Reranker>,
+    template: &TemplateFilter,
+    request: &SearchQuery,
+) -> Result<SearchPage> {
+    // Connect to database
+    let db = lancedb::connect(lance_path)
+        .execute()
+        .await?;
+
+    // Open table
+    let table = db.open_table(table_name)
+        .execute()
+        .await?;
+
+    search_table_page(&table, embedding_generator, reranker, template, request).await
+}
+
+/// Mean of the stored vectors of every chunk of the entry at `path`
+async fn document_vector(table: &Table, path: &str) -> Result<Vec<f32>> {
+    let batches: Vec<RecordBatch> = table
+        .query()
+        .select(Select::columns(&["embedding"]))
+        .only_if(format!("path = {}", sql_string(path)))
+        .execute()
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut sum: Vec<f32> = Vec::new();
+    let mut chunks = 0;
+    for batch in &batches {
+        let embeddings = batch.column_by_name("embedding")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+            .ok_or_else(|| anyhow::anyhow!("Missing embedding column"))?;
+        for i in 0..batch.num_rows() {
+            let vector = embeddings.value(i);
+            let vector = vector.as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow::anyhow!("Failed to cast embedding column"))?;
+            sum.resize(vector.len(), 0.0);
+            sum.iter_mut().zip(vector.values()).for_each(|(total, x)| *total += x);
+            chunks += 1;
+        }
+    }
+    if chunks == 0 {
+        bail!("'{}' is not in the index; pass its path as search results show it", path);
+    }
+
+    sum.iter_mut().for_each(|total| *total /= chunks as f32);
+    Ok(sum)
+}
+
+/// Run a search against an already opened chunk table
+pub async fn search_table(
+    table: &Table,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: Option<&Reranker>,
+    template: &TemplateFilter,
+    request: &SearchQuery,
+) -> Result<Vec<SearchResult>> {
+    Ok(search_table_page(table, embedding_generator, reranker, template, request).await?.results)
+}
+
+/// [`search_table`], also reporting how many results there were before paging
+pub async fn search_table_page(
+    table: &Table,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: Option<&Reranker>,
+    template: &TemplateFilter,
+    request: &SearchQuery,
+) -> Result<SearchPage> {
+    let reranker = match (request.rerank, reranker) {
+        (true, Some(reranker)) => Some(reranker),
+        (true, None) => bail!("Reranking was requested but no reranker model is loaded"),
+        (false, _) => None,
+    };
+    if request.like.is_some() && (request.hybrid || reranker.is_some() || !request.alternate_queries.is_empty()) {
+        bail!("Searching by a document can't be combined with hybrid search, reranking or more queries, which need query text");
+    }
+    let schema = table.schema().await?;
+    let encrypted = schema_encrypted(&schema);
+    if request.hybrid && encrypted {
+        bail!("Hybrid search needs a full-text index, which an index with encrypted content doesn't have");
+    }
+    let filter = row_filter(request);
+
+    // Chunks wanted before grouping collapses them into entries, for every page up to the requested
+    // one and one result past it, so a following page can be told apart from none
+    let through = request.offset + request.limit + 1;
+    let wanted = if request.group_by_doc {
+        through * GROUP_CANDIDATE_FACTOR
+    } else {
+        through
+    };
+
+    // The reranker and recency weighting need a shortlist to reorder
+    let mut pool = wanted;
+    if reranker.is_some() {
+        pool = pool.max(RERANK_CANDIDATES);
+    }
+    if request.recency_halflife.is_some() {
+        pool = pool.max(wanted * RECENCY_CANDIDATE_FACTOR);
+    }
+
+    // Fusion works on rankings, so give each retriever a deeper pool than we return
+    let mut candidates = if request.hybrid {
+        pool * HYBRID_CANDIDATE_FACTOR
+    } else {
+        pool
+    };
+
+    // Checked on the candidates, so fetch enough that some survive
+    let content_filter = ContentFilter::new(request)?;
+    if content_filter.is_some() {
+        candidates *= CONTENT_FILTER_CANDIDATE_FACTOR;
+    }
+    // The glob's LIKE prefilter lets `*` cross directories; this is the exact check
+    let path_glob = request.path_glob.as_deref().map(glob_regex).transpose()?;
+    let passes = |hit: &Hit| {
+        content_filter.as_ref().is_none_or(|f| f.matches(&hit.content))
+            && path_glob.as_ref().is_none_or(|glob| glob.is_match(&hit.path))
+    };
+
+    // Indexes from before the metric was configurable were searched with L2
+    let metric = match request.metric {
+        Some(metric) => metric,
+        None => schema_metric(&schema).unwrap_or(Metric::L2),
+    };
+
+    // Each phrasing is searched on its own and the rankings are fused; `like` has just its vector
+    let phrasings: Vec<&str> = std::iter::once(request.query.as_str())
+        .chain(request.alternate_queries.iter().map(String::as_str))
+        .collect();
+    let mut rankings = Vec::new();
+    let mut best_similarity = 0.0_f32;
+    for phrasing in &phrasings {
+        let mut query_embedding = match &request.like {
+            Some(path) => document_vector(table, path).await?,
+            None => embedding_generator.generate_embedding(phrasing)?,
+        };
+        if metric.normalizes() {
+            normalize(&mut query_embedding);
+        }
+
+        // Build vector query
+        let mut vector_query = table.vector_search(query_embedding)?
+            .column("embedding")
+            .distance_type(metric.distance_type())
+            .limit(candidates);
+
+        // Only take effect once `mdrag index --build-ann` has built a vector index
+        if let Some(nprobes) = request.nprobes {
+            vector_query = vector_query.nprobes(nprobes);
+        }
+        if let Some(refine_factor) = request.refine_factor {
+            vector_query = vector_query.refine_factor(refine_factor);
+        }
+
+        if let Some(filter) = &filter {
+            vector_query = vector_query.only_if(filter.clone());
+            if !request.prefilter {
+                vector_query = vector_query.postfilter();
+            }
+        }
+
+        // Execute vector search
+        let batches: Vec<RecordBatch> = vector_query.execute().await?.try_collect().await?;
+        let mut vector_hits = Vec::new();
+        for batch in &batches {
+            // Convert distance to similarity score (0-1, higher is better)
+            vector_hits.extend(batch_hits(batch, encrypted, "_distance", |distance| metric.score(distance))?);
+        }
+        vector_hits.retain(passes);
+        best_similarity = vector_hits.iter().map(|hit| hit.score).fold(best_similarity, f32::max);
+        rankings.push(vector_hits);
+
+        if request.hybrid {
+            let mut text_query = table.query()
+                .full_text_search(FullTextSearchQuery::new(phrasing.to_string()))
+                .limit(candidates);
+
+            if let Some(filter) = &filter {
+                text_query = text_query.only_if(filter.clone());
+            }
+
+            let batches: Vec<RecordBatch> = text_query
+                .execute()
+                .await
+                .context("Full-text search failed. Run `mdrag index` to build the full-text index")?
+                .try_collect()
+                .await?;
+            let mut text_hits = Vec::new();
+            for batch in &batches {
+                text_hits.extend(batch_hits(batch, encrypted, "_score", |score| score)?);
+            }
+            text_hits.retain(passes);
+            rankings.push(text_hits);
+        }
+    }
+
+    // Snippets show the terms of every phrasing
+    let snippet_query = phrasings.join(" ");
+
+    let hits = if rankings.len() > 1 {
+        fuse_hits(rankings, pool)
+    } else {
+        rankings.pop().unwrap_or_default()
+    };
+
+    let mut hits = match reranker {
+        Some(reranker) => rerank_hits(reranker, &request.query, hits, pool)?,
+        None => hits,
+    };
+
+    // Rare names and terms the model has no sense of. Judged on the vector similarities, since
+    // fused and reranked scores are on another scale
+    let mut keyword_match = false;
+    let mut found_total = None;
+    if let Some(threshold) = request.keyword_fallback
+        && !request.hybrid
+        && request.like.is_none()
+        && best_similarity < metric.from_cosine_score(threshold)
+    {
+        let mut found = keyword_hits(table, &request.query, filter.as_deref(), usize::MAX).await?;
+        found.retain(passes);
+        if !found.is_empty() {
+            found_total = Some(if request.group_by_doc { distinct_paths(&found) } else { found.len() });
+            found.truncate(pool);
+            hits = found;
+            keyword_match = true;
+        }
+    }
+
+    // Every row passing the filters is a result unless a check after the search drops some;
+    // then only the results found so far are known
+    let exact_total = match found_total {
+        Some(total) => Some(total),
+        None if content_filter.is_none() && path_glob.is_none() && request.min_score.is_none() => {
+            Some(count_matching(table, filter.as_deref(), request.group_by_doc).await?)
+        }
+        None => None,
+    };
+
+    if let Some(halflife) = request.recency_halflife {
+        let today = date_to_days(Local::now().date_naive());
+        for hit in &mut hits {
+            let age = (today - hit.date).max(0) as f32;
+            hit.score *= recency_factor(age, halflife);
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    let to_result = |hit: Hit| SearchResult {
+        path: PathBuf::from(hit.path),
+        date: days_to_date(hit.date),
+        score: hit.score,
+        snippet: extract_snippet(&hit.content, &snippet_query, SNIPPET_CHARS),
+        snippets: (request.snippets > 0)
+            .then(|| extract_snippets(&hit.content, &snippet_query, SNIPPET_CHARS, request.snippets)),
+        section: hit.section.filter(|section| !section.is_empty()),
+        start_line: hit.lines.map(|(start, _)| start),
+        end_line: hit.lines.map(|(_, end)| end),
+        metadata: None,
+        matching_chunks: None,
+        aggregate_score: None,
+        context: request.chunk_text.then(|| hit.content.clone()),
+        keyword_match,
+        collection: None,
+    };
+
+    let mut ranked: Vec<(SearchResult, i32)> = if request.group_by_doc {
+        group_hits(hits)
+            .into_iter()
+            .map(|group| {
+                let chunk_index = group.best.chunk_index;
+                let result = SearchResult {
+                    matching_chunks: Some(group.matching_chunks),
+                    aggregate_score: Some(group.aggregate_score),
+                    ..to_result(group.best)
+                };
+                (result, chunk_index)
+            })
+            .collect()
+    } else {
+        hits.into_iter()
+            .map(|hit| {
+                let chunk_index = hit.chunk_index;
+                (to_result(hit), chunk_index)
+            })
+            .collect()
+    };
+
+    if let Some(min_score) = request.min_score {
+        ranked.retain(|(result, _)| result.score >= min_score);
+    }
+    let total = exact_total.unwrap_or(ranked.len()).max(ranked.len());
+    let mut results: Vec<(SearchResult, i32)> = ranked.into_iter().skip(request.offset).take(request.limit).collect();
+
+    if request.full_doc {
+        for (result, _) in &mut results {
+            result.context = full_document(template, &result.path);
+        }
+    } else if request.expand > 0 {
+        for (result, chunk_index) in &mut results {
+            let path = result.path.to_string_lossy();
+            result.context = Some(neighbor_chunks(table, encrypted, &path, *chunk_index, request.expand).await?);
+        }
+    }
+
+    Ok(SearchPage { results: results.into_iter().map(|(result, _)| result).collect(), total })
+}
+
+/// Chunks passing `filter`, or the entries they belong to
+async fn count_matching(table: &Table, filter: Option<&str>, by_entry: bool) -> Result<usize> {
+    if !by_entry {
+        return Ok(table.count_rows(filter.map(String::from)).await?);
+    }
+    let mut scan = table.query().select(Select::columns(&["path"]));
+    if let Some(filter) = filter {
+        scan = scan.only_if(filter);
+    }
+    let mut paths = HashSet::new();
+    let mut batches = scan.execute().await?;
+    while let Some(batch) = batches.try_next().await? {
+        let column = batch
+            .column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .context("Missing path column")?;
+        paths.extend(column.iter().flatten().map(String::from));
+    }
+    Ok(paths.len())
+}
+
+fn distinct_paths(hits: &[Hit]) -> usize {
+    hits.iter().map(|hit| hit.path.as_str()).collect::<HashSet<_>>().len()
+}
+
+/// `text` with LIKE's wildcards and escape character escaped
+fn like_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// A LIKE pattern matching at least every path `glob` matches: `*`, `**` and `**/` become `%`, `?` becomes `_`
+fn glob_like(glob: &str) -> String {
+    let glob = glob.strip_prefix("./").unwrap_or(glob);
+    let mut pattern = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                let mut stars = 1;
+                while chars.next_if_eq(&'*').is_some() {
+                    stars += 1;
+                }
+                // `**/` may match no directory at all, so its slash is optional too
+                if stars > 1 {
+                    chars.next_if_eq(&'/');
+                }
+                pattern.push('%');
+            }
+            '?' => pattern.push('_'),
+            c => pattern.push_str(&like_escape(&c.to_string())),
+        }
+    }
+    pattern
+}
+
+/// The exact regex for `glob`: `**/` spans any directories (or none), `*` and `?` stay within one
+fn glob_regex(glob: &str) -> Result<Regex> {
+    let glob = glob.strip_prefix("./").unwrap_or(glob);
+    let mut pattern = String::from("^");
+    let mut rest = glob;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            pattern.push_str("(?:.*/)?");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("**") {
+            pattern.push_str(".*");
+            rest = after;
+        } else {
+            match c {
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).with_context(|| format!("Invalid path glob '{}'", glob))
+}
+
+/// The lexical constraints of a request, checked on candidate chunks
+struct ContentFilter {
+    /// Lowercased
+    must_contain: Vec<String>,
+    must_match: Option<Regex>,
+}
+
+impl ContentFilter {
+    /// `None` when the request has no constraints
+    fn new(request: &SearchQuery) -> Result<Option<Self>> {
+        let must_contain: Vec<String> = request.must_contain
+            .iter()
+            .filter(|text| !text.is_empty())
+            .map(|text| text.to_lowercase())
+            .collect();
+        let must_match = request.must_match
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .context("Invalid --must-match-regex")?;
+        if must_contain.is_empty() && must_match.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self { must_contain, must_match }))
+    }
+
+    fn matches(&self, content: &str) -> bool {
+        let lower = content.to_lowercase();
+        self.must_contain.iter().all(|text| lower.contains(text.as_str()))
+            && self.must_match.as_ref().is_none_or(|regex| regex.is_match(content))
+    }
+}
+
+/// Chunks containing the most of the query's words (case-insensitive), newest first among equals.
+///
+/// Scans `content` of every row passing `filter`; only used when vector search found nothing good.
+async fn keyword_hits(table: &Table, query: &str, filter: Option<&str>, limit: usize) -> Result<Vec<Hit>> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Everything but the vectors; older indexes lack some of these columns
+    let schema = table.schema().await?;
+    let columns: Vec<&str> = ["path", "chunk_index", "date", "content", "section", "start_line", "end_line"]
+        .into_iter()
+        .filter(|column| schema.field_with_name(column).is_ok())
+        .collect();
+    let encrypted = schema_encrypted(&schema);
+    let mut scan = table.query().select(Select::columns(&columns));
+    if let Some(filter) = filter {
+        scan = scan.only_if(filter);
+    }
+
+    let mut hits = Vec::new();
+    let mut batches = scan.execute().await?;
+    while let Some(batch) = batches.try_next().await? {
+        for mut hit in batch_hits(&batch, encrypted, "_score", |score| score)? {
+            let content = hit.content.to_lowercase();
+            let found = terms.iter().filter(|term| content.contains(term.as_str())).count();
+            if found > 0 {
+                hit.score = found as f32 / terms.len() as f32;
+                hits.push(hit);
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.date.cmp(&a.date)));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// The chunks within `radius` of `chunk_index` in one entry, joined in order
+async fn neighbor_chunks(table: &Table, encrypted: bool, path: &str, chunk_index: i32, radius: usize) -> Result<String> {
+    let radius = radius as i32;
+    let batches: Vec<RecordBatch> = table
+        .query()
+        .select(Select::columns(&["path", "chunk_index", "date", "content"]))
+        .only_if(format!(
+            "path = {} AND chunk_index >= {} AND chunk_index <= {}",
+            sql_string(path),
+            chunk_index - radius,
+            chunk_index + radius,
+        ))
+        .execute()
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut chunks = Vec::new();
+    for batch in &batches {
+        chunks.extend(batch_hits(batch, encrypted, "_score", |score| score)?);
+    }
+    chunks.sort_by_key(|chunk| chunk.chunk_index);
+
+    Ok(chunks
+        .into_iter()
+        .map(|chunk| chunk.content)
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+/// The whole entry with frontmatter and template noise removed, read from disk
+fn full_document(filter: &TemplateFilter, path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let (_, body) = split_frontmatter(&content);
+            Some(filter.clean_content(body))
+        }
+        Err(e) => {
+            eprintln!("  ⚠️  Cannot read {} for full-document context: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// A matching chunk before it is turned into a result
+struct Hit {
+    path: String,
+    chunk_index: i32,
+    date: i32,
+    content: String,
+    /// Empty outside any heading; `None` for indexes from before sections were stored
+    section: Option<String>,
+    lines: Option<(usize, usize)>,
+    score: f32,
+}
+
+/// Read the hits out of a result batch, scoring each row from `score_column`. `encrypted` comes
+/// from the table's schema, since result batches don't carry its metadata
+fn batch_hits(batch: &RecordBatch, encrypted: bool, score_column: &str, to_score: impl Fn(f32) -> f32) -> Result<Vec<Hit>> {
+    let path_array = batch.column_by_name("path")
+        .ok_or(anyhow::anyhow!("Missing path column"))?
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or(anyhow::anyhow!("Failed to cast path column"))?;
+
+    let chunk_index_array = batch.column_by_name("chunk_index")
+        .ok_or(anyhow::anyhow!("Missing chunk_index column"))?
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .ok_or(anyhow::anyhow!("Failed to cast chunk_index column"))?;
+
+    let date_array = batch.column_by_name("date")
+        .ok_or(anyhow::anyhow!("Missing date column"))?
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .ok_or(anyhow::anyhow!("Failed to cast date column"))?;
+
+    let content_array = batch.column_by_name("content")
+        .ok_or(anyhow::anyhow!("Missing content column"))?
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or(anyhow::anyhow!("Failed to cast content column"))?;
+
+    let section_array = batch.column_by_name("section")
+        .and_then(|col| col.as_any().downcast_ref::<StringArray>());
+    let start_line_array = batch.column_by_name("start_line")
+        .and_then(|col| col.as_any().downcast_ref::<Int32Array>());
+    let end_line_array = batch.column_by_name("end_line")
+        .and_then(|col| col.as_any().downcast_ref::<Int32Array>());
+
+    // Get scores if available
+    let score_array = batch.column_by_name(score_column)
+        .and_then(|col| col.as_any().downcast_ref::<Float32Array>());
+
+    (0..batch.num_rows())
+        .map(|i| Ok(Hit {
+            path: path_array.value(i).to_string(),
+            chunk_index: chunk_index_array.value(i),
+            date: date_array.value(i),
+            // Decrypted here when the index encrypts chunk text
+            content: reveal(content_array.value(i), encrypted, path_array.value(i), chunk_index_array.value(i))?.into_owned(),
+            section: section_array.map(|sections| sections.value(i).to_string()),
+            lines: match (start_line_array, end_line_array) {
+                (Some(starts), Some(ends)) if starts.is_valid(i) && ends.is_valid(i) => {
+                    Some((starts.value(i) as usize, ends.value(i) as usize))
+                }
+                _ => None,
+            },
+            // Default score if the column is not available
+            score: score_array.map_or(0.5, |scores| to_score(scores.value(i))),
+        }))
+        .collect()
+}
+
+/// The hits of one entry, represented by its best chunk
+struct GroupedHit {
+    best: Hit,
+    matching_chunks: usize,
+    aggregate_score: f32,
+}
+
+/// Collapse hits from the same path, keeping the ranking of each entry's best chunk.
+///
+/// `hits` must be sorted best first.
+fn group_hits(hits: Vec<Hit>) -> Vec<GroupedHit> {
+    let mut groups: Vec<GroupedHit> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for hit in hits {
+        match positions.get(&hit.path) {
+            Some(&i) => {
+                groups[i].matching_chunks += 1;
+                groups[i].aggregate_score += hit.score;
+            }
+            None => {
+                positions.insert(hit.path.clone(), groups.len());
+                groups.push(GroupedHit {
+                    aggregate_score: hit.score,
+                    matching_chunks: 1,
+                    best: hit,
+                });
+            }
+        }
+    }
+
+    groups
+}
+
+/// Merge vector and full-text rankings with reciprocal rank fusion
+fn fuse_hits(hit_rankings: Vec<Vec<Hit>>, limit: usize) -> Vec<Hit> {
+    let key = |hit: &Hit| (hit.path.clone(), hit.chunk_index);
+    let rankings: Vec<Vec<(String, i32)>> = hit_rankings
+        .iter()
+        .map(|hits| hits.iter().map(key).collect())
+        .collect();
+
+    let mut by_key: HashMap<(String, i32), Hit> = HashMap::new();
+    for hit in hit_rankings.into_iter().flatten() {
+        by_key.entry(key(&hit)).or_insert(hit);
+    }
+
+    // A chunk ranked first by every ranking scores 1.0
+    let best = rankings.len() as f32 / (RRF_K + 1.0);
+    reciprocal_rank_fusion(&rankings, RRF_K)
+        .into_iter()
+        .take(limit)
+        .filter_map(|(key, score)| {
+            by_key.remove(&key).map(|hit| Hit { score: score / best, ..hit })
+        })
+        .collect()
+}
+
+/// Reorder hits by cross-encoder score and keep the best `limit`
+fn rerank_hits(reranker: &Reranker, query: &str, hits: Vec<Hit>, limit: usize) -> Result<Vec<Hit>> {
+    let documents: Vec<&str> = hits.iter().map(|hit| hit.content.as_str()).collect();
+    let ranked = reranker.rerank(query, &documents)?;
+
+    let mut hits: Vec<Option<Hit>> = hits.into_iter().map(Some).collect();
+    Ok(ranked
+        .into_iter()
+        .take(limit)
+        .filter_map(|(index, score)| {
+            hits.get_mut(index)
+                .and_then(Option::take)
+                .map(|hit| Hit { score, ..hit })
+        })
+        .collect())
+}
+
+/// Score multiplier for an entry `age_days` old: 1.0 today, falling towards
+/// `1 - RECENCY_WEIGHT` as the exponential decay halves every `halflife` days
+fn recency_factor(age_days: f32, halflife: f32) -> f32 {
+    let decay = 0.5_f32.powf(age_days / halflife);
+    1.0 - RECENCY_WEIGHT + RECENCY_WEIGHT * decay
+}
+
+/// Filter expression for the date range, tags and any `--where` condition
+fn row_filter(request: &SearchQuery) -> Option<String> {
+    let mut conditions = Vec::new();
+
+    if let Some(path) = &request.like {
+        conditions.push(format!("path != {}", sql_string(path)));
+    }
+
+    if let Some(after_date) = request.after {
+        conditions.push(format!("date >= {}", date_to_days(after_date)));
+    }
+
+    if let Some(before_date) = request.before {
+        conditions.push(format!("date <= {}", date_to_days(before_date)));
+    }
+
+    if let Some(prefix) = &request.path_prefix {
+        let prefix = prefix.strip_prefix("./").unwrap_or(prefix);
+        conditions.push(format!("path LIKE {}", sql_string(&format!("{}%", like_escape(prefix)))));
+    }
+
+    if let Some(glob) = &request.path_glob {
+        conditions.push(format!("path LIKE {}", sql_string(&glob_like(glob))));
+    }
+
+    if let Some((month, day)) = request.on_this_day {
+        let days: Vec<String> = (ON_THIS_DAY_FIRST_YEAR..=Local::now().year())
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+            .map(|date| date_to_days(date).to_string())
+            .collect();
+        // A day no year has, like 02-30, matches nothing
+        conditions.push(if days.is_empty() { "false".to_string() } else { format!("date IN ({})", days.join(", ")) });
+    }
+
+    for tag in &request.tags {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        conditions.push(format!("array_has(tags, {})", sql_string(&tag)));
+    }
+
+    if let Some(condition) = request.filter.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        conditions.push(format!("({})", condition));
+    }
+
+    (!conditions.is_empty()).then(|| conditions.join(" AND "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(path: &str, chunk_index: i32, score: f32) -> Hit {
+        Hit {
+            path: path.to_string(),
+            chunk_index,
+            date: 0,
+            content: String::new(),
+            section: None,
+            lines: None,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_recency_factor() {
+        assert_eq!(recency_factor(0.0, 30.0), 1.0);
+        assert!((recency_factor(30.0, 30.0) - 0.75).abs() < 1e-6);
+        assert!(recency_factor(3650.0, 30.0) > 0.5);
+    }
+
+    #[test]
+    fn test_group_hits_keeps_best_chunk() {
+        let groups = group_hits(vec![
+            hit("a.md", 2, 0.9),
+            hit("b.md", 0, 0.8),
+            hit("a.md", 0, 0.5),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].best.path, "a.md");
+        assert_eq!(groups[0].best.chunk_index, 2);
+        assert_eq!(groups[0].matching_chunks, 2);
+        assert!((groups[0].aggregate_score - 1.4).abs() < 1e-6);
+        assert_eq!(groups[1].matching_chunks, 1);
+    }
+
+    #[test]
+    fn test_fuse_hits_dedupes_chunks_across_phrasings() {
+        let fused = fuse_hits(vec![
+            vec![hit("a.md", 0, 0.9), hit("b.md", 1, 0.8)],
+            vec![hit("a.md", 0, 0.7), hit("c.md", 0, 0.6)],
+            vec![hit("a.md", 0, 0.5), hit("b.md", 1, 0.4)],
+        ], 10);
+        let order: Vec<(&str, i32)> = fused.iter().map(|hit| (hit.path.as_str(), hit.chunk_index)).collect();
+        assert_eq!(order, vec![("a.md", 0), ("b.md", 1), ("c.md", 0)]);
+        assert!((fused[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_row_filter_combines_conditions() {
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"x","after":"1970-01-02","before":null,"limit":5,"tags":["#Work"],"filter":"mood >= 7"}"#,
+        ).unwrap();
+        assert_eq!(row_filter(&request).unwrap(), "date >= 1 AND array_has(tags, 'work') AND (mood >= 7)");
+
+        let request: SearchQuery = serde_json::from_str(r#"{"query":"x","after":null,"before":null,"limit":5}"#).unwrap();
+        assert_eq!(row_filter(&request), None);
+
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"","after":null,"before":null,"limit":5,"like":"journal/it's.md"}"#,
+        ).unwrap();
+        assert_eq!(row_filter(&request).unwrap(), "path != 'journal/it''s.md'");
+
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"x","after":null,"before":null,"limit":5,"on_this_day":[1,2]}"#,
+        ).unwrap();
+        // 1970-01-02 is epoch day 1
+        assert!(row_filter(&request).unwrap().contains(", 1, 366, "));
+    }
+
+    #[test]
+    fn test_count_needs_search_only_for_queries_and_result_checks() {
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"","after":"2025-01-01","before":null,"limit":5,"tags":["work"],"path_prefix":"journal/"}"#,
+        ).unwrap();
+        assert!(!count_needs_search(&request));
+        assert!(count_needs_search(&SearchQuery { min_score: Some(0.5), ..request.clone() }));
+        assert!(count_needs_search(&SearchQuery { query: "burnout".to_string(), ..request.clone() }));
+
+        let counting = count_query(&SearchQuery { offset: 20, expand: 2, rerank: true, ..request }, 300);
+        assert_eq!((counting.offset, counting.limit, counting.expand, counting.rerank), (0, 300, 0, false));
+    }
+
+    #[test]
+    fn test_content_filter() {
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"x","after":null,"before":null,"limit":5,"must_contain":["Clippy"],"must_match":"E0\\d{3}"}"#,
+        ).unwrap();
+        let filter = ContentFilter::new(&request).unwrap().unwrap();
+        assert!(filter.matches("clippy flagged E0502 again"));
+        assert!(!filter.matches("clippy was happy"));
+        assert!(!filter.matches("E0502 without the linter"));
+
+        let request: SearchQuery = serde_json::from_str(r#"{"query":"x","after":null,"before":null,"limit":5}"#).unwrap();
+        assert!(ContentFilter::new(&request).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sort_by_date_keeps_ranking_within_a_day() {
+        let mut results: Vec<SearchResult> = [("b.md", "2025-07-21"), ("a.md", "2025-03-01"), ("c.md", "2025-07-21")]
+            .into_iter()
+            .map(|(path, date)| {
+                serde_json::from_value(serde_json::json!({ "path": path, "date": date, "score": 0.5, "snippet": "" })).unwrap()
+            })
+            .collect();
+        SortOrder::Date.apply(&mut results);
+        let paths: Vec<String> = results.iter().map(|r| r.path.display().to_string()).collect();
+        assert_eq!(paths, vec!["a.md", "b.md", "c.md"]);
+        assert_eq!("DATE".parse::<SortOrder>(), Ok(SortOrder::Date));
+    }
+
+    #[test]
+    fn test_cursor_is_tied_to_its_search() {
+        let request: SearchQuery = serde_json::from_str(r#"{"query":"x","after":null,"before":null,"limit":5}"#).unwrap();
+        let cursor = page_cursor(&request, 10);
+        assert_eq!(cursor_offset(&SearchQuery { offset: 5, ..request.clone() }, &cursor).unwrap(), 10);
+        assert!(cursor_offset(&SearchQuery { query: "y".to_string(), ..request }, &cursor).is_err());
+    }
+
+    #[test]
+    fn test_path_globs() {
+        assert_eq!(glob_like("./journal/**/topics/*_notes.md"), "journal/%topics/%\\_notes.md");
+        let glob = glob_regex("**/topics/*.md").unwrap();
+        assert!(glob.is_match("journal/topics/rust.md"));
+        assert!(glob.is_match("topics/rust.md"));
+        assert!(!glob.is_match("journal/topics/old/rust.md"));
+    }
+
+    #[test]
+    fn test_merge_collections_ranks_by_position() {
+        let result = |path: &str, score: f32| SearchResult {
+            path: PathBuf::from(path),
+            date: days_to_date(0),
+            score,
+            snippet: String::new(),
+            snippets: None,
+            section: None,
+            start_line: None,
+            end_line: None,
+            metadata: None,
+            matching_chunks: None,
+            aggregate_score: None,
+            context: None,
+            keyword_match: false,
+            collection: None,
+        };
+        let page = |results: Vec<SearchResult>| SearchPage { total: results.len(), results };
+        // Hybrid scores are small RRF values, so ranking by score would bury that collection
+        let merged = merge_collections(vec![
+            ("journal".to_string(), page(vec![result("a.md", 0.9), result("b.md", 0.8)])),
+            ("notes".to_string(), page(vec![result("c.md", 0.03), result("d.md", 0.02)])),
+        ], 1, 3);
+        assert_eq!(merged.total, 4);
+
+        let ranked: Vec<(&str, &str, f32)> = merged.results.iter()
+            .map(|r| (r.path.to_str().unwrap(), r.collection.as_deref().unwrap(), r.score))
+            .collect();
+        assert_eq!(ranked, vec![("c.md", "notes", 0.03), ("b.md", "journal", 0.8), ("d.md", "notes", 0.02)]);
+    }
+}
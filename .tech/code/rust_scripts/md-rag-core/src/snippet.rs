@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Marker put around matched query terms; markdown bold, so JSON and markdown readers see it as is
+pub const HIGHLIGHT: &str = "**";
+
+/// Characters in a snippet unless the caller asks otherwise
+pub const SNIPPET_CHARS: usize = 500;
+
+/// Query words that say nothing about what to show
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "an", "and", "are", "as", "at", "be", "by", "did", "do", "for", "from", "how", "i", "in", "is",
+    "it", "me", "my", "of", "on", "or", "the", "to", "was", "what", "when", "where", "which", "who", "why", "with",
+];
+
+/// A passage of a chunk with the query's terms marked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    /// Character offsets of the passage in the chunk's content, leaving out `...` and marks
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The run of whole sentences of `content` that covers the most query terms within `max_chars`,
+/// with each matched word wrapped in [`HIGHLIGHT`].
+///
+/// Falls back to the opening sentences when no term occurs. `...` marks text left out on either side.
+pub fn extract_snippet(content: &str, query: &str, max_chars: usize) -> String {
+    extract_snippets(content, query, max_chars, 1)
+        .into_iter()
+        .next()
+        .map(|snippet| snippet.text)
+        .unwrap_or_default()
+}
+
+/// Up to `count` non-overlapping snippets in the order they appear, picked best first like
+/// [`extract_snippet`]. Only the first may lack query terms
+pub fn extract_snippets(content: &str, query: &str, max_chars: usize, count: usize) -> Vec<Snippet> {
+    let terms = query_terms(query);
+    let sentences = sentences(content);
+
+    // Distinct terms and total matches per sentence
+    let matched: Vec<(HashSet<usize>, usize)> = sentences
+        .iter()
+        .map(|sentence| {
+            let text = &content[sentence.clone()];
+            let hits: Vec<usize> = words(text)
+                .into_iter()
+                .filter_map(|word| matching_term(&text[word], &terms))
+                .collect();
+            (hits.iter().copied().collect(), hits.len())
+        })
+        .collect();
+
+    let mut taken = vec![false; sentences.len()];
+    let mut spans = Vec::new();
+    while spans.len() < count {
+        let Some(window) = best_window(&sentences, &matched, &taken, max_chars, spans.is_empty()) else {
+            break;
+        };
+        taken[window.clone()].fill(true);
+        let span = sentences[window.start].start..sentences[window.end - 1].end;
+        spans.push(if span.end - span.start > max_chars {
+            shorten(content, span, &terms, max_chars)
+        } else {
+            span
+        });
+    }
+    spans.sort_by_key(|span| span.start);
+
+    spans
+        .into_iter()
+        .map(|span| {
+            let mut text = String::new();
+            if !content[..span.start].trim().is_empty() {
+                text.push_str("...");
+            }
+            text.push_str(&highlight(&content[span.clone()], &terms));
+            if !content[span.end..].trim().is_empty() {
+                text.push_str("...");
+            }
+            let start = content[..span.start].chars().count();
+            Snippet { text, start, end: start + content[span].chars().count() }
+        })
+        .collect()
+}
+
+/// Consecutive free sentences covering the most distinct terms within `max_chars`, then the
+/// most matches, then the earliest
+fn best_window(
+    sentences: &[Range<usize>],
+    matched: &[(HashSet<usize>, usize)],
+    taken: &[bool],
+    max_chars: usize,
+    allow_unmatched: bool,
+) -> Option<Range<usize>> {
+    let mut best: Option<(Range<usize>, (usize, usize))> = None;
+    for first in (0..sentences.len()).filter(|&i| !taken[i]) {
+        let mut covered = HashSet::new();
+        let mut count = 0;
+        let mut last = first;
+        while last < sentences.len()
+            && !taken[last]
+            && (last == first || sentences[last].end - sentences[first].start <= max_chars)
+        {
+            covered.extend(&matched[last].0);
+            count += matched[last].1;
+            last += 1;
+        }
+        let score = (covered.len(), count);
+        if (allow_unmatched || count > 0) && best.as_ref().is_none_or(|(_, best_score)| score > *best_score) {
+            best = Some((first..last, score));
+        }
+    }
+    best.map(|(window, _)| window)
+}
+
+/// Byte ranges of the words in `text` matching a term of `query`, the ones snippets mark
+pub fn matched_words(text: &str, query: &str) -> Vec<Range<usize>> {
+    let terms = query_terms(query);
+    words(text)
+        .into_iter()
+        .filter(|word| matching_term(&text[word.clone()], &terms).is_some())
+        .collect()
+}
+
+/// Lowercased query words worth matching, without duplicates
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut all: Vec<String> = Vec::new();
+    for word in words(query) {
+        let word = query[word].to_lowercase();
+        if !all.contains(&word) {
+            all.push(word);
+        }
+    }
+    let terms: Vec<String> = all.iter().filter(|w| !STOP_WORDS.contains(&w.as_str())).cloned().collect();
+    // A query made only of stop words still wants them found
+    if terms.is_empty() { all } else { terms }
+}
+
+/// Index of the query term `word` matches. Words of four letters or more also match
+/// their longer forms either way round (`meeting`, `meetings`)
+fn matching_term(word: &str, terms: &[String]) -> Option<usize> {
+    let word = word.to_lowercase();
+    terms.iter().position(|term| {
+        let (shorter, longer) = if word.len() <= term.len() { (&word, term) } else { (term, &word) };
+        *shorter == *longer || (shorter.chars().count() >= 4 && longer.starts_with(shorter.as_str()))
+    })
+}
+
+/// Byte ranges of the alphanumeric words in `text`
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(s..text.len());
+    }
+    words
+}
+
+/// Byte ranges of the sentences in `text`, trimmed. A line break always ends one, since
+/// journal bullets and headings rarely end in punctuation
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = match c {
+            '\n' => Some(i),
+            '.' | '!' | '?' if chars.peek().is_none_or(|(_, next)| next.is_whitespace()) => Some(i + 1),
+            _ => None,
+        };
+        if let Some(end) = end {
+            push_trimmed(text, start..end, &mut sentences);
+            start = end;
+        }
+    }
+    push_trimmed(text, start..text.len(), &mut sentences);
+    sentences
+}
+
+fn push_trimmed(text: &str, range: Range<usize>, sentences: &mut Vec<Range<usize>>) {
+    let slice = &text[range.clone()];
+    let trimmed = slice.trim();
+    if !trimmed.is_empty() {
+        let start = range.start + (slice.len() - slice.trim_start().len());
+        sentences.push(start..start + trimmed.len());
+    }
+}
+
+/// Cut a sentence longer than `max_chars` down at word boundaries, keeping its first match in view
+fn shorten(content: &str, span: Range<usize>, terms: &[String], max_chars: usize) -> Range<usize> {
+    let text = &content[span.clone()];
+    let words = words(text);
+    let focus = words
+        .iter()
+        .find(|word| matching_term(&text[(*word).clone()], terms).is_some())
+        .map_or(0, |word| word.start);
+
+    // Some lead-in before the match, then as many whole words as fit
+    let lead = focus.saturating_sub(max_chars / 4);
+    let first = words.iter().find(|word| word.start >= lead).map_or(0, |word| word.start);
+    let last = words
+        .iter()
+        .filter(|word| word.start >= first && word.end - first <= max_chars)
+        .map(|word| word.end)
+        .max()
+        .unwrap_or_else(|| {
+            // A single word longer than the snippet is cut at a character boundary
+            let mut end = (first + max_chars).min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            end
+        });
+    span.start + first..span.start + last
+}
+
+/// `text` with every word matching a query term wrapped in [`HIGHLIGHT`]
+fn highlight(text: &str, terms: &[String]) -> String {
+    let mut marked = String::with_capacity(text.len());
+    let mut copied = 0;
+    for word in words(text) {
+        if matching_term(&text[word.clone()], terms).is_some() {
+            marked.push_str(&text[copied..word.start]);
+            marked.push_str(HIGHLIGHT);
+            marked.push_str(&text[word.clone()]);
+            marked.push_str(HIGHLIGHT);
+            copied = word.end;
+        }
+    }
+    marked.push_str(&text[copied..]);
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_takes_whole_sentences_around_the_terms() {
+        let content = "The weather was grey. Met Ana about the mdrag release! Then lunch.";
+        assert_eq!(
+            extract_snippet(content, "the mdrag releases", 40),
+            "...Met Ana about the **mdrag** **release**!...",
+        );
+    }
+
+    #[test]
+    fn test_snippet_without_match_takes_opening_sentences() {
+        assert_eq!(extract_snippet("First line\nSecond line\nThird line", "zzz", 22), "First line\nSecond line...");
+    }
+
+    #[test]
+    fn test_several_snippets_do_not_overlap() {
+        let content = "Rust at work. Lunch was long. More rust in the evening.";
+        let snippets = extract_snippets(content, "rust", 30, 3);
+        let texts: Vec<&str> = snippets.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["**Rust** at work. Lunch was long....", "...More **rust** in the evening."]);
+        assert_eq!((snippets[1].start, snippets[1].end), (30, 55));
+    }
+
+    #[test]
+    fn test_matched_words() {
+        let text = "Meetings ran late; the meeting notes are in Rust.";
+        let words: Vec<&str> = matched_words(text, "the meeting").into_iter().map(|w| &text[w]).collect();
+        assert_eq!(words, vec!["Meetings", "meeting"]);
+    }
+
+    #[test]
+    fn test_long_sentence_is_cut_between_words() {
+        let content = "one two three four five six seven eight nine ten";
+        let snippet = extract_snippet(content, "six", 20);
+        assert_eq!(snippet, "...five **six** seven eight...");
+    }
+}
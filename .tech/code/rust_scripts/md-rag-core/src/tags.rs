@@ -0,0 +1,65 @@
+use regex::Regex;
+use serde_yaml::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+/// `#tag` at the start of a line or after whitespace or `(`; must start with a letter, so
+/// headings (`# Title`), issue numbers (`#12`) and URL fragments don't count
+static HASHTAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[\s(])#(\p{L}[\p{L}\p{N}_/-]*)").unwrap()
+});
+
+/// Tags of an entry: its frontmatter `tags:` plus every `#hashtag` in the body,
+/// lowercased, without the `#`, deduplicated and sorted
+pub fn extract_tags(body: &str, frontmatter: &HashMap<String, Value>) -> Vec<String> {
+    let mut tags = BTreeSet::new();
+
+    match frontmatter.get("tags") {
+        Some(Value::Sequence(items)) => {
+            for item in items {
+                if let Some(tag) = item.as_str() {
+                    tags.extend(normalize(tag));
+                }
+            }
+        }
+        // `tags: work, rust` or `tags: "#work #rust"`
+        Some(Value::String(list)) => {
+            tags.extend(list.split(|c: char| c == ',' || c.is_whitespace()).filter_map(normalize));
+        }
+        _ => {}
+    }
+
+    let mut in_code_block = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        tags.extend(HASHTAG.captures_iter(line).filter_map(|c| normalize(&c[1])));
+    }
+
+    tags.into_iter().collect()
+}
+
+fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').trim_end_matches(['-', '/']);
+    (!tag.is_empty()).then(|| tag.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_tags() {
+        let frontmatter: HashMap<String, Value> = serde_yaml::from_str("tags: [Work, health]").unwrap();
+        let body = "# Monday\nShipped the #rust port (#work/mdrag).\nSee issue #42 and https://x.dev/#anchor\n```sh\ngrep #notatag\n```\n#Rust again";
+        assert_eq!(extract_tags(body, &frontmatter), vec!["health", "rust", "work", "work/mdrag"]);
+
+        let frontmatter: HashMap<String, Value> = serde_yaml::from_str("tags: \"#travel, family\"").unwrap();
+        assert_eq!(extract_tags("", &frontmatter), vec!["family", "travel"]);
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Serialize;
+
+/// A task item in an entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Task {
+    pub text: String,
+    pub done: bool,
+    /// Text of the nearest heading above it; empty before the first one
+    pub section: String,
+    /// Line in the text it was found in, from 1
+    pub line: usize,
+}
+
+/// A `- [ ] task` or `- [x] task` list item (`*` and `+` bullets too): whether it's checked, and
+/// its text. Other brackets, like `- [-]`, aren't tasks
+pub fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let item = line.trim_start().strip_prefix(['-', '*', '+'])?.strip_prefix([' ', '\t'])?.trim_start();
+    let done = match item.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = &item[3..];
+    if !text.is_empty() && !text.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((done, text.trim()))
+}
+
+/// Every task item in a markdown body, skipping fenced code blocks
+pub fn extract_tasks(body: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut section = "";
+    let mut in_code_block = false;
+    for (i, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#');
+            if heading.starts_with(' ') {
+                section = heading.trim();
+            }
+            continue;
+        }
+        if let Some((done, text)) = checkbox(line) {
+            tasks.push(Task { text: text.to_string(), done, section: section.to_string(), line: i + 1 });
+        }
+    }
+    tasks
+}
+
+/// Checked and unchecked task items in a markdown body
+pub fn checkbox_counts(body: &str) -> (usize, usize) {
+    body.lines().filter_map(checkbox).fold((0, 0), |(done, open), (checked, _)| {
+        if checked { (done + 1, open) } else { (done, open + 1) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkboxes() {
+        assert_eq!(checkbox("- [x] Ship the release"), Some((true, "Ship the release")));
+        assert_eq!(checkbox("  * [ ] Call Ana"), Some((false, "Call Ana")));
+        assert_eq!(checkbox("- [ ]"), Some((false, "")));
+        assert_eq!(checkbox("- [-] Dropped"), None);
+        assert_eq!(checkbox("- [x]ed"), None);
+        assert_eq!(checkbox("[x] Not a list item"), None);
+
+        let body = "## Tasks\n- [x] Run\n- [X] Read\n- [ ] Write\n- Plain item\n";
+        assert_eq!(checkbox_counts(body), (2, 1));
+
+        let body = "- [ ] Before any heading\n# Monday\n## Work\n- [x] Review PR\n```md\n- [ ] Not a task\n```\n#tag line\n  - [ ] Nested\n";
+        let tasks: Vec<_> = extract_tasks(body).into_iter().map(|t| (t.text, t.done, t.section, t.line)).collect();
+        assert_eq!(
+            tasks,
+            vec![
+                ("Before any heading".to_string(), false, String::new(), 1),
+                ("Review PR".to_string(), true, "Work".to_string(), 4),
+                ("Nested".to_string(), false, "Work".to_string(), 9),
+            ],
+        );
+    }
+}
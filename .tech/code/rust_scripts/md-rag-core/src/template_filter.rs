@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::chunk::{locate_lines, split_by_tokens, Chunk, ChunkOptions, FenceState};
+use crate::config::{ScrubConfig, TemplateConfig};
+use crate::frontmatter::split_frontmatter;
+use crate::new_entry::DATE_PLACEHOLDER;
+use crate::scrub::scrub;
+use crate::tasks::checkbox;
+
+/// Sections shorter than this (in characters) are only a header or a stray line and are dropped
+const MIN_SECTION_CHARS: usize = 100;
+
+/// Labels taken for template structure whatever they say, up to this many words
+const SHORT_LABEL_WORDS: usize = 3;
+
+/// Label lines of the journal template this repo was built for; labels this long are only
+/// template when known, so a question of the user's own is kept
+const BUILTIN_TEMPLATE_LABELS: &[&str] = &[
+    "- Main Work Goal(s) for Today:",
+    "- Key Work Tasks:",
+    "- Learning Objective(s) (Review `journal/topics/ai_study_backlog.md` with Cline if needed):",
+    "- Project Task(s) (if any):",
+    "- Key Questions for AI / Discussion Points:",
+    "- Goal for this session:",
+    "- Specific Learning Focus:",
+    "- What went well today (Work, Primary Focus, Personal)?",
+    "- Challenges faced & how they were handled?",
+    "- Key learnings (Technical, Rust, Personal, etc.)?",
+    "- How did the overall balance feel today (Work/Focus/Relaxation/Other Activities)?",
+    "- Adjustments or intentions for tomorrow?",
+];
+
+/// Identifies and filters out template boilerplate from journal entries
+pub struct TemplateFilter {
+    boilerplate_headers: HashSet<String>,
+    empty_section_patterns: Vec<String>,
+    /// Trimmed lines dropped wherever they appear; headings are never dropped this way
+    boilerplate_lines: HashSet<String>,
+    /// Trimmed label lines known to come from a template, beyond those `boilerplate_lines` holds
+    template_labels: HashSet<String>,
+    scrub: ScrubConfig,
+}
+
+impl Default for TemplateFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemplateFilter {
+    pub fn new() -> Self {
+        let mut boilerplate_headers = HashSet::new();
+        
+        // Level 2 headers that are pure template
+        boilerplate_headers.insert("## I. Work Responsibilities & Goals (Mon-Fri)".to_string());
+        boilerplate_headers.insert("## II. Primary Focus Activities: [Declared Primary Focus from above]".to_string());
+        boilerplate_headers.insert("## III. Nice-to-Haves / Other Minor Tasks".to_string());
+        boilerplate_headers.insert("## IV. Progress Toward Broader Goals".to_string());
+        boilerplate_headers.insert("## V. End-of-Day Reflection".to_string());
+        
+        // Level 3 headers that are pure template
+        boilerplate_headers.insert("### A. If AI Study:".to_string());
+        boilerplate_headers.insert("### B. If Rust Study:".to_string());
+        boilerplate_headers.insert("### C. If Other Focused Activity (e.g., NixOS Rice, Specific Project):".to_string());
+        boilerplate_headers.insert("### C. If Other Focused Activity:".to_string());
+        
+        // Unfilled sections are recognised by their structure (see `is_empty_section`)
+        Self {
+            boilerplate_headers,
+            empty_section_patterns: Vec::new(),
+            boilerplate_lines: HashSet::new(),
+            template_labels: BUILTIN_TEMPLATE_LABELS.iter().map(|label| label.to_string()).collect(),
+            scrub: ScrubConfig::default(),
+        }
+    }
+    
+    /// The patterns configured in `.mdrag.toml` and its templates file: the top-level ones and
+    /// those of every named template, plus the built-in ones unless `builtin = false`
+    pub fn with_config(config: &TemplateConfig) -> Self {
+        let mut filter = if config.builtin {
+            Self::new()
+        } else {
+            Self {
+                boilerplate_headers: HashSet::new(),
+                empty_section_patterns: Vec::new(),
+                boilerplate_lines: HashSet::new(),
+                template_labels: HashSet::new(),
+                scrub: ScrubConfig::default(),
+            }
+        };
+        filter.scrub = config.scrub.clone();
+        let top = (&config.boilerplate_headers, &config.empty_sections, &config.boilerplate_lines);
+        let named = config.templates.values().map(|t| (&t.boilerplate_headers, &t.empty_sections, &t.boilerplate_lines));
+        for (headers, empty_sections, lines) in std::iter::once(top).chain(named) {
+            filter.boilerplate_headers.extend(headers.iter().cloned());
+            filter.empty_section_patterns.extend(empty_sections.iter().cloned());
+            filter.template_labels.extend(empty_sections.iter().flat_map(|pattern| normalized_lines(pattern)).map(String::from));
+            filter.boilerplate_lines.extend(lines.iter().map(|line| line.trim().to_string()));
+        }
+        filter
+    }
+    
+    /// Process content and return cleaned version with comments, metadata blocks and template noise removed
+    pub fn clean_content(&self, content: &str) -> String {
+        let content = scrub(content, &self.scrub);
+        let mut cleaned = String::new();
+        let mut current_section = String::new();
+        let mut in_boilerplate_section = false;
+        let mut _section_header = String::new();
+        let mut fence = FenceState::default();
+        
+        for line in content.lines() {
+            // Code is kept as it is: a `## comment` in it is no header
+            let in_code = fence.in_code(line);
+            
+            // Lines left as the template had them, which leaves an untouched section empty
+            if !in_code && self.boilerplate_lines.contains(line.trim()) && parse_heading(line.trim()).is_none() {
+                continue;
+            }
+            
+            // Check if this is a header
+            if !in_code && line.starts_with("##") {
+                // Process the previous section
+                if !in_boilerplate_section && !self.is_empty_section(&current_section) {
+                    cleaned.push_str(&current_section);
+                }
+                
+                // Reset for new section
+                current_section.clear();
+                _section_header = line.to_string();
+                
+                // Check if this header is boilerplate
+                in_boilerplate_section = self.boilerplate_headers.contains(line);
+                
+                // Add the header to current section
+                current_section.push_str(line);
+                current_section.push('\n');
+            } else {
+                // Add line to current section
+                current_section.push_str(line);
+                current_section.push('\n');
+            }
+        }
+        
+        // Don't forget the last section
+        if !in_boilerplate_section && !self.is_empty_section(&current_section) {
+            cleaned.push_str(&current_section);
+        }
+        
+        // Remove consecutive empty lines
+        self.remove_excessive_whitespace(&cleaned)
+    }
+    
+    /// Check if a section contains only template boilerplate: nothing but headings, blank lines,
+    /// bare bullets, empty checkboxes and template labels (see `is_template_line`), or a
+    /// configured empty-section pattern with at most one other line
+    fn is_empty_section(&self, section: &str) -> bool {
+        if section.lines().all(|line| self.is_template_line(line)) {
+            return true;
+        }
+        
+        // Patterns are compared line by line, ignoring indentation, trailing spaces and blank lines
+        let lines = normalized_lines(section);
+        self.empty_section_patterns.iter().any(|pattern| {
+            let pattern = normalized_lines(pattern);
+            if pattern.is_empty() || !lines.windows(pattern.len()).any(|window| window == pattern.as_slice()) {
+                return false;
+            }
+            // Count how many non-template lines exist; labels count, so questions of the user's own keep the section
+            let meaningful_lines = lines
+                .iter()
+                .filter(|line| !line.starts_with('#') && !line.starts_with("- [ ]") && !pattern.contains(line))
+                .filter(|line| !line.chars().all(|c| matches!(c, '-' | '*' | '+' | '_' | ' ')))
+                .count();
+            meaningful_lines < 2
+        })
+    }
+    
+    /// Whether a line is unfilled template structure: blank, a heading, a bare bullet or rule (`-`,
+    /// `---`), an empty `- [ ]`, or a label with nothing written after it. A label ends in `:` or
+    /// `?` and counts when it is a line of a configured or learned template, a built-in one, in
+    /// emphasis (`**Mood:**`, `- **Energy:** [High / Low]`) or at most a few words (`- Gratitude:`);
+    /// a longer plain question or note is the user's
+    fn is_template_line(&self, line: &str) -> bool {
+        let line = line.trim();
+        if line.chars().all(|c| matches!(c, '-' | '*' | '+' | '_' | ' ')) || parse_heading(line).is_some() {
+            return true;
+        }
+        if let Some((done, text)) = checkbox(line) {
+            return !done && text.is_empty();
+        }
+        
+        let mut label = line.trim_start_matches(['-', '*', '+']).trim_start();
+        // A bracketed placeholder after the label, like `[Work / Study / Other]`
+        if label.ends_with(']') && let Some(open) = label.rfind('[') {
+            label = label[..open].trim_end();
+        }
+        let emphasised = label.len() > 2 && label.starts_with(['*', '_']) && label.ends_with(['*', '_']);
+        let label = label.trim_matches(['*', '_']).trim_end();
+        if label.len() <= 1 || !label.ends_with([':', '?']) {
+            return false;
+        }
+        emphasised
+            || label.split_whitespace().count() <= SHORT_LABEL_WORDS
+            || self.boilerplate_lines.contains(line)
+            || self.template_labels.contains(line)
+    }
+    
+    /// Remove excessive whitespace while preserving paragraph structure
+    fn remove_excessive_whitespace(&self, content: &str) -> String {
+        let mut result = String::new();
+        let mut consecutive_empty = 0;
+        let mut fence = FenceState::default();
+        
+        for line in content.lines() {
+            let in_code = fence.in_code(line);
+            if line.trim().is_empty() && !in_code {
+                consecutive_empty += 1;
+                if consecutive_empty <= 2 {
+                    result.push('\n');
+                }
+            } else {
+                consecutive_empty = 0;
+                result.push_str(line);
+                result.push('\n');
+            }
+        }
+        
+        result.trim().to_string()
+    }
+    
+    /// Extract chunks by meaningful sections, skipping template noise.
+    ///
+    /// Every header starts a new chunk, labelled with the path of headings above it;
+    /// sections longer than `options.max_tokens` are split into overlapping chunks.
+    pub fn extract_chunks(&self, content: &str, options: ChunkOptions, count_tokens: &dyn Fn(&str) -> usize) -> Vec<Chunk> {
+        let cleaned = self.clean_content(content);
+        let mut sections = Vec::new();
+        let mut headings: Vec<(usize, String)> = Vec::new();
+        let mut current_section = String::new();
+        let mut fence = FenceState::default();
+        
+        for line in cleaned.lines() {
+            let in_code = fence.in_code(line);
+            if !in_code && let Some((level, title)) = parse_heading(line) {
+                if !current_section.is_empty() {
+                    sections.push((section_path(&headings), std::mem::take(&mut current_section)));
+                }
+                // The level-1 heading is the entry title; the date already identifies the entry
+                if level > 1 {
+                    headings.retain(|(l, _)| *l < level);
+                    headings.push((level, title.to_string()));
+                }
+            }
+            current_section.push_str(line);
+            current_section.push('\n');
+        }
+        sections.push((section_path(&headings), current_section));
+        
+        let mut chunks: Vec<Chunk> = sections
+            .into_iter()
+            .filter(|(_, text)| text.len() > MIN_SECTION_CHARS)
+            .flat_map(|(section, text)| {
+                split_by_tokens(&text, options, count_tokens)
+                    .into_iter()
+                    .map(move |text| Chunk { section: section.clone(), text, lines: None })
+            })
+            .collect();
+        
+        // Chunks appear in source order, and overlap never reaches back past the previous chunk's start
+        let source: Vec<&str> = content.lines().collect();
+        let mut cursor = 0;
+        for chunk in &mut chunks {
+            if let Some((start, end)) = locate_lines(&source, &chunk.text, cursor) {
+                chunk.lines = Some((start + 1, end + 1));
+                cursor = start;
+            }
+        }
+        
+        chunks
+    }
+}
+
+/// Level and title of a markdown ATX heading such as `## Notes`
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if level == 0 || level > 6 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+/// Non-blank lines without surrounding whitespace
+fn normalized_lines(text: &str) -> Vec<&str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).collect()
+}
+
+/// The lines of a journal template that are boilerplate when an entry still has them verbatim:
+/// every non-blank body line except headings and those with the `YYYY-MM-DD` placeholder, trimmed
+pub fn template_lines(template: &str) -> Vec<String> {
+    let (_, body) = split_frontmatter(template);
+    let mut lines: Vec<String> = Vec::new();
+    for line in body.lines().filter_map(line_candidate) {
+        if !line.contains(DATE_PLACEHOLDER) && !lines.iter().any(|l| l == line) {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+/// Lines found verbatim (trimmed) in at least `min_share` of `bodies`, with the number of bodies
+/// each is in, most common first. Blank lines and headings are left out
+pub fn common_lines<'a>(bodies: impl IntoIterator<Item = &'a str>, min_share: f64) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut total = 0;
+    for body in bodies {
+        total += 1;
+        let distinct: HashSet<&str> = body.lines().filter_map(line_candidate).collect();
+        for line in distinct {
+            *counts.entry(line).or_default() += 1;
+        }
+    }
+    let mut lines: Vec<(String, usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| total > 0 && *count as f64 / total as f64 >= min_share)
+        .map(|(line, count)| (line.to_string(), count))
+        .collect();
+    lines.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    lines
+}
+
+/// A line trimmed, unless it's blank or a heading
+fn line_candidate(line: &str) -> Option<&str> {
+    let line = line.trim();
+    (!line.is_empty() && parse_heading(line).is_none()).then_some(line)
+}
+
+fn section_path(headings: &[(usize, String)]) -> String {
+    headings.iter().map(|(_, title)| title.as_str()).collect::<Vec<_>>().join(" > ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_removes_empty_work_section() {
+        let filter = TemplateFilter::new();
+        let content = r#"
+# Daily Reflection
+
+## Morning Check-in
+Had a great morning!
+
+## I. Work Responsibilities & Goals (Mon-Fri)
+- Main Work Goal(s) for Today:
+  -
+- Key Work Tasks:
+  - [ ]
+  - [ ]
+  - [ ]
+
+## Real Content
+This section has actual content worth indexing.
+"#;
+        
+        let cleaned = filter.clean_content(content);
+        assert!(!cleaned.contains("Work Responsibilities"));
+        assert!(cleaned.contains("Had a great morning"));
+        assert!(cleaned.contains("Real Content"));
+    }
+    
+    #[test]
+    fn test_structural_empty_sections() {
+        let filter = TemplateFilter::new();
+        let content = "## Morning Check-in\n\n- **Today's Primary Focus:** [Work / Study / Project / Other]\n-  **Energy Level:**  \n- **Key Priorities:**\n    - [ ]\n  - [ ] \n\n\
+## Evening\n- What went well today (Work, Primary Focus, Personal)?\n  -\n---\n*Notes:*\n\n\
+## Done\n- [x] Shipped it\n- [ ]\n\n\
+## Short\nOne real line.\n\n\
+## Questions\n- Should I take the Berlin offer?\n- Why do I keep putting off the dentist?\n\n\
+## Answered\n- What went well?\n  - The walk: long and quiet.\n";
+
+        let cleaned = filter.clean_content(content);
+        assert!(!cleaned.contains("Morning Check-in"));
+        assert!(!cleaned.contains("Evening"));
+        assert!(cleaned.contains("- [x] Shipped it"));
+        assert!(cleaned.contains("One real line."));
+        assert!(cleaned.contains("- Should I take the Berlin offer?"));
+        assert!(cleaned.contains("The walk: long and quiet."));
+
+        assert!(filter.is_template_line("- Learning Objective(s) (Review `journal/topics/ai_study_backlog.md` with Cline if needed):"));
+        assert!(filter.is_template_line("- _What am I avoiding?_"));
+        assert!(!filter.is_template_line("- Why do I keep putting off the dentist?"));
+        assert!(!filter.is_template_line("Things I want to ask Ana about the trip:"));
+        assert!(!filter.is_template_line("- [ ] Call Ana"));
+        assert!(!filter.is_template_line("- Slept well: 8 hours"));
+        assert!(!filter.is_template_line("[x]"));
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let filter = TemplateFilter::new();
+        let content = "## Setup\nInstalled the toolchain on the new laptop, which took the whole evening to get right.\n```bash\n## update everything\n-\n\n\n\nsudo pacman -Syu\n```\n## Next\nMore to come tomorrow, once the backups finish and the old disk is wiped and reinstalled from scratch.\n";
+        assert!(filter.clean_content(content).contains("```bash\n## update everything\n-\n\n\n\nsudo pacman -Syu\n```"));
+
+        let chunks = filter.extract_chunks(content, ChunkOptions::default(), &|t| t.split_whitespace().count());
+        let sections: Vec<&str> = chunks.iter().map(|c| c.section.as_str()).collect();
+        assert_eq!(sections, vec!["Setup", "Next"]);
+
+        let mut config = TemplateConfig::default();
+        config.scrub.code_blocks = true;
+        let cleaned = TemplateFilter::with_config(&config).clean_content(content);
+        assert!(!cleaned.contains("pacman"));
+        assert!(cleaned.contains("the whole evening"));
+    }
+
+    #[test]
+    fn test_configured_templates() {
+        let config: TemplateConfig = toml::from_str(r#"
+builtin = false
+
+[weekly]
+boilerplate_headers = ["## Week in Review"]
+empty_sections = ["- Wins:\n"]
+"#).unwrap();
+        let filter = TemplateFilter::with_config(&config);
+        let content = "## Week in Review\nFilled in by a script.\nMore of it.\n\n## Wins\n- Wins:\n- [ ]\n\n## I. Work Responsibilities & Goals (Mon-Fri)\nOnly the built-in template knew this header.\nSo it stays.\n";
+
+        let cleaned = filter.clean_content(content);
+        assert!(!cleaned.contains("Week in Review"));
+        assert!(!cleaned.contains("## Wins"));
+        assert!(cleaned.contains("Only the built-in template knew this header"));
+
+        let asked = "## Wins\n- Wins:\n- Did the move to Porto make me happier?\n- Should I call Dad more often this month?\n";
+        assert!(filter.clean_content(asked).contains("call Dad more often"));
+    }
+
+    #[test]
+    fn test_template_lines() {
+        let template = "---\ndate: YYYY-MM-DD\n---\n# Daily - YYYY-MM-DD\n\n## Gratitude\n- \n\n## Checklist\n- [ ] Meditation\n- [ ] Reading\n- **Mood:** \n";
+        let lines = template_lines(template);
+        assert_eq!(lines, vec!["-", "- [ ] Meditation", "- [ ] Reading", "- **Mood:**"]);
+
+        let config = TemplateConfig {
+            builtin: false,
+            boilerplate_lines: lines,
+            ..Default::default()
+        };
+        let filter = TemplateFilter::with_config(&config);
+        let entry = "# Daily - 2025-07-01\n\n## Gratitude\n- \n\n## Checklist\n- [x] Meditation\n- [ ] Reading\n- **Mood:** calm, a long day\n";
+        let cleaned = filter.clean_content(entry);
+        assert!(!cleaned.contains("Gratitude"));
+        assert!(cleaned.contains("- [x] Meditation"));
+        assert!(!cleaned.contains("Reading"));
+        assert!(cleaned.contains("calm, a long day"));
+
+        let bodies = ["## Log\n- [ ] Meditation\nWoke early.\n", "- [ ] Meditation\n- [ ] Meditation\n", "- [x] Meditation\nWoke early.\n"];
+        assert_eq!(common_lines(bodies, 0.6), vec![("- [ ] Meditation".to_string(), 2), ("Woke early.".to_string(), 2)]);
+        assert!(common_lines(bodies, 0.9).is_empty());
+    }
+
+    #[test]
+    fn test_chunks_carry_heading_path() {
+        let filter = TemplateFilter::new();
+        let content = format!(
+            "# Daily Reflection\n\n## Evening Review\n{}\n### Key learnings\n{}\n## Tomorrow\n{}",
+            "- Went for a long walk after work.\n".repeat(4),
+            "- Rust lifetimes finally clicked.\n".repeat(4),
+            "- Read a book before bed.\n".repeat(5),
+        );
+        
+        let chunks = filter.extract_chunks(&content, ChunkOptions::default(), &|t| t.split_whitespace().count());
+        let sections: Vec<&str> = chunks.iter().map(|c| c.section.as_str()).collect();
+        assert_eq!(sections, vec![
+            "Evening Review",
+            "Evening Review > Key learnings",
+            "Tomorrow",
+        ]);
+        assert!(chunks[1].text.starts_with("### Key learnings"));
+        assert_eq!(chunks[1].lines, Some((9, 13)));
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
+
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::llm::{ChatClient, DEFAULT_LLM_MODEL, DEFAULT_LLM_URL, LLM_MODEL_ENV, LLM_URL_ENV};
+use md_rag_core::prompt::{cited, llm_token_counter, pack_sources, render_sources, Source};
+use md_rag_core::schema::db_path;
+use md_rag_core::search::{index_exists, SearchQuery, MISSING_INDEX_HINT};
+
+use crate::search::run_search;
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct AskArgs {
+    /// Question about the journal
+    question: String,
+
+    /// Entries to retrieve [default: 8]
+    #[arg(short, long)]
+    num_results: Option<usize>,
+
+    /// Token budget for the excerpts put in the prompt [default: 3000]
+    #[arg(long)]
+    max_tokens: Option<usize>,
+
+    /// Neighboring chunks on each side of every hit to include as its excerpt
+    #[arg(long, default_value_t = 1)]
+    expand: usize,
+
+    /// Only entries on or after this date (same forms as `mdrag search --after`)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only entries on or before this date
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Only entries with this tag; repeat to require several
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Combine vector search with BM25 full-text search
+    #[arg(long)]
+    hybrid: bool,
+
+    /// Rerank the candidates with a cross-encoder
+    #[arg(long)]
+    rerank: bool,
+
+    /// Chat model [default: llama3.2]
+    #[arg(long, env = LLM_MODEL_ENV)]
+    llm_model: Option<String>,
+
+    /// Base URL of an OpenAI-compatible chat API; the API key is read from $MDRAG_LLM_API_KEY
+    /// [default: http://localhost:11434/v1, a local Ollama]
+    #[arg(long, env = LLM_URL_ENV)]
+    llm_url: Option<String>,
+
+    /// Print the prompt instead of sending it
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Entries retrieved when neither `-n` nor `[ask]` in `.mdrag.toml` sets a number
+const DEFAULT_NUM_RESULTS: usize = 8;
+
+/// Prompt budget for excerpts when neither `--max-tokens` nor `[ask]` sets one
+const DEFAULT_MAX_TOKENS: usize = 3000;
+
+const SYSTEM_PROMPT: &str = "You answer questions about the user's personal journal. \
+Use only the numbered excerpts you are given, and cite every claim with the number of its excerpt in \
+square brackets, like [1] or [2][3]. Address the user as \"you\". If the excerpts don't answer the \
+question, say so instead of guessing.";
+
+#[derive(Serialize)]
+struct JsonAnswer<'a> {
+    answer: &'a str,
+    model: &'a str,
+    /// Every source given to the model; `cited` marks those the answer refers to
+    sources: Vec<JsonSource<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonSource<'a> {
+    #[serde(flatten)]
+    source: &'a Source,
+    cited: bool,
+}
+
+/// Answer a question from the journal: retrieve, pack the best excerpts into a prompt and ask a chat model
+pub async fn run(global: &GlobalArgs, args: AskArgs) -> Result<()> {
+    let defaults = &global.config.ask;
+    let today = Local::now().date_naive();
+    let after = args.after.as_deref().map(|s| parse_date_range(s, today).map(|(start, _)| start)).transpose()?;
+    let before = args.before.as_deref().map(|s| parse_date_range(s, today).map(|(_, end)| end)).transpose()?;
+
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    if !index_exists(&lance_path, &global.table).await? {
+        anyhow::bail!("No table '{}' found at {}. {}", global.table, lance_path, MISSING_INDEX_HINT);
+    }
+
+    // One source per entry, each with its surrounding chunks so the model sees the whole thought
+    let request = SearchQuery {
+        query: args.question.clone(),
+        after,
+        before,
+        limit: args.num_results.or(defaults.num_results).unwrap_or(DEFAULT_NUM_RESULTS),
+        hybrid: args.hybrid || global.config.search.hybrid,
+        rerank: args.rerank || global.config.search.rerank,
+        group_by_doc: true,
+        expand: args.expand,
+        chunk_text: true,
+        tags: args.tags.clone(),
+        ..Default::default()
+    };
+    let results = run_search(global, &global.table, None, false, &request).await?.results;
+    let max_tokens = args.max_tokens.or(defaults.max_tokens).unwrap_or(DEFAULT_MAX_TOKENS);
+    let sources = pack_sources(&results, max_tokens, &*llm_token_counter());
+    if sources.is_empty() {
+        anyhow::bail!("Nothing in the journal matches the question, so there is nothing to answer from");
+    }
+
+    let prompt = format!("Journal excerpts:\n\n{}\n\nQuestion: {}", render_sources(&sources), args.question);
+    if args.dry_run {
+        println!("{}\n\n{}", SYSTEM_PROMPT, prompt);
+        return Ok(());
+    }
+
+    let url = args.llm_url.or_else(|| defaults.url.clone()).unwrap_or_else(|| DEFAULT_LLM_URL.to_string());
+    let model = args.llm_model.or_else(|| defaults.model.clone()).unwrap_or_else(|| DEFAULT_LLM_MODEL.to_string());
+    let client = ChatClient::new(&url, &model);
+    eprintln!("🤖 Asking {} with {} excerpts", client.model(), sources.len());
+    let answer = client.complete(SYSTEM_PROMPT, &prompt)?;
+    let citations = cited(&answer);
+
+    match args.format {
+        OutputFormat::Text => {
+            println!("{}\n", answer);
+            // Only what the answer relies on; all of it when the model cited nothing
+            let listed: Vec<&Source> = if citations.is_empty() {
+                sources.iter().collect()
+            } else {
+                citations.iter().filter_map(|n| sources.iter().find(|s| s.number == *n)).collect()
+            };
+            println!("Sources:");
+            for source in listed {
+                println!("  [{}] {} ({})", source.number, source.path.display(), source.date);
+            }
+        }
+        OutputFormat::Json => {
+            let output = JsonAnswer {
+                answer: &answer,
+                model: client.model(),
+                sources: sources
+                    .iter()
+                    .map(|source| JsonSource { source, cited: citations.contains(&source.number) })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,344 @@
+use anyhow::{bail, Result};
+use chrono::{Datelike, NaiveDate};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+/// Bars of a sparkline, lowest first
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Braille dot bits by row (top to bottom) for the left and right dot columns of a cell
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Width used when stdout isn't a terminal
+const DEFAULT_WIDTH: usize = 80;
+
+/// Heatmap shades from low to high; days without an entry are `·`
+const HEAT: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// GitHub's palette: no entry, then low to high
+const HEAT_COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+
+/// Side of an SVG heatmap square, and the distance between squares
+const HEAT_CELL: u32 = 11;
+const HEAT_STEP: u32 = 13;
+
+/// Size of a chart file: this wide, and this tall per field
+const FILE_WIDTH: u32 = 1000;
+const FILE_HEIGHT_PER_FIELD: u32 = 320;
+
+/// How a field is drawn by `mdrag frontmatter --plot`
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum PlotStyle {
+    /// One line of ▁▂▃▅▇ bars
+    Sparkline,
+    /// A few lines of braille dots joined into a line
+    Braille,
+}
+
+/// One value per day from the first to the last date, averaging days with several entries;
+/// None for days without one
+pub fn daily_series(points: &[(NaiveDate, f64)]) -> Vec<Option<f64>> {
+    let (Some(first), Some(last)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.0).max()) else {
+        return Vec::new();
+    };
+    let days = (last - first).num_days() as usize + 1;
+    let mut sums = vec![(0.0, 0usize); days];
+    for (date, value) in points {
+        let slot = &mut sums[(*date - first).num_days() as usize];
+        slot.0 += value;
+        slot.1 += 1;
+    }
+    sums.into_iter().map(|(sum, count)| (count > 0).then(|| sum / count as f64)).collect()
+}
+
+/// `series` squeezed into at most `width` slots, each the average of the values it covers
+pub fn resample(series: &[Option<f64>], width: usize) -> Vec<Option<f64>> {
+    if series.len() <= width || width == 0 {
+        return series.to_vec();
+    }
+    (0..width)
+        .map(|i| {
+            let slice = &series[i * series.len() / width..(i + 1) * series.len() / width];
+            let values: Vec<f64> = slice.iter().flatten().copied().collect();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        })
+        .collect()
+}
+
+/// Columns available for a chart: the terminal width less `reserved`
+pub fn chart_width(reserved: usize) -> usize {
+    let columns = terminal_size::terminal_size().map_or(DEFAULT_WIDTH, |(terminal_size::Width(w), _)| w as usize);
+    columns.saturating_sub(reserved).max(10)
+}
+
+/// A bar per value between the series' min and max; gaps are spaces
+pub fn sparkline(values: &[Option<f64>]) -> String {
+    let Some((min, max)) = bounds(values) else {
+        return " ".repeat(values.len());
+    };
+    values
+        .iter()
+        .map(|value| match value {
+            Some(v) => SPARKS[scale(*v, min, max, SPARKS.len())],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// A line chart `height` characters tall, two values per character; each value is a dot and
+/// consecutive values are joined by a vertical run of dots. Gaps break the line
+pub fn braille(values: &[Option<f64>], height: usize) -> Vec<String> {
+    let dots_high = height * 4;
+    let mut cells = vec![vec![0u32; values.len().div_ceil(2)]; height];
+    let Some((min, max)) = bounds(values) else {
+        return cells.iter().map(|row| " ".repeat(row.len())).collect();
+    };
+
+    let mut previous: Option<usize> = None;
+    for (x, value) in values.iter().enumerate() {
+        let Some(value) = value else {
+            previous = None;
+            continue;
+        };
+        // Dot rows count from the top
+        let y = dots_high - 1 - scale(*value, min, max, dots_high);
+        let (from, to) = match previous {
+            Some(p) => (p.min(y), p.max(y)),
+            None => (y, y),
+        };
+        for dot in from..=to {
+            cells[dot / 4][x / 2] |= BRAILLE_DOTS[dot % 4][x % 2];
+        }
+        previous = Some(y);
+    }
+
+    cells
+        .iter()
+        .map(|row| row.iter().map(|&bits| char::from_u32(0x2800 + bits).unwrap_or(' ')).collect())
+        .collect()
+}
+
+/// Each date's average over the `days` days ending on it, for the dates in `points`
+pub fn rolling_mean(points: &[(NaiveDate, f64)], days: u32) -> Vec<(NaiveDate, f64)> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by_key(|p| p.0);
+    let mut dates: Vec<NaiveDate> = sorted.iter().map(|p| p.0).collect();
+    dates.dedup();
+    dates
+        .into_iter()
+        .map(|date| {
+            let start = date - chrono::Duration::days(days as i64 - 1);
+            let window: Vec<f64> = sorted.iter().filter(|p| p.0 >= start && p.0 <= date).map(|p| p.1).collect();
+            (date, window.iter().sum::<f64>() / window.len() as f64)
+        })
+        .collect()
+}
+
+/// Write a chart of each field's values over time, stacked, with its `window`-day rolling
+/// average drawn over them. The extension picks the format: `.svg` or `.png`
+pub fn write_chart(path: &Path, fields: &[(String, Vec<(NaiveDate, f64)>)], window: u32) -> Result<()> {
+    let size = (FILE_WIDTH, FILE_HEIGHT_PER_FIELD * fields.len().max(1) as u32);
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("svg") => draw_chart(SVGBackend::new(path, size).into_drawing_area(), fields, window),
+        Some("png") => draw_chart(BitMapBackend::new(path, size).into_drawing_area(), fields, window),
+        _ => bail!("Can't tell the chart format of {}; use a .svg or .png file", path.display()),
+    }
+}
+
+fn draw_chart<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, fields: &[(String, Vec<(NaiveDate, f64)>)], window: u32) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    for (area, (name, points)) in root.split_evenly((fields.len().max(1), 1)).iter().zip(fields) {
+        let (Some(first), Some(last)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.0).max()) else {
+            continue;
+        };
+        let values: Vec<Option<f64>> = points.iter().map(|p| Some(p.1)).collect();
+        let Some((min, max)) = bounds(&values) else {
+            continue;
+        };
+        // Room around the line, and a visible range for a flat series
+        let pad = if max > min { (max - min) * 0.1 } else { 1.0 };
+        let last = if last > first { last } else { first + chrono::Duration::days(1) };
+
+        let mut chart = ChartBuilder::on(area)
+            .caption(name, ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(50)
+            .build_cartesian_2d(first..last, (min - pad)..(max + pad))?;
+        chart
+            .configure_mesh()
+            .x_labels(8)
+            .x_label_formatter(&|date| date.format("%Y-%m-%d").to_string())
+            .draw()?;
+
+        chart
+            .draw_series(LineSeries::new(points.iter().copied(), BLUE.mix(0.35)))?
+            .label(name.as_str())
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+        chart.draw_series(points.iter().map(|&point| Circle::new(point, 2, BLUE.filled())))?;
+        chart
+            .draw_series(LineSeries::new(rolling_mean(points, window), RED.stroke_width(2)))?
+            .label(format!("{}-day average", window))
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED.stroke_width(2)));
+        chart.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw()?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+/// A calendar heatmap: one block per year with a column per week and a row per weekday, each
+/// day shaded by its value between the overall min and max
+pub fn heatmap_text(name: &str, points: &[(NaiveDate, f64)]) -> String {
+    let days = daily_values(points);
+    let Some((min, max)) = bounds(&days.iter().map(|d| Some(d.1)).collect::<Vec<_>>()) else {
+        return format!("{}: no values to draw\n", name);
+    };
+    let mut text = String::new();
+    for (start, end) in year_spans(&days) {
+        let monday = start - chrono::Duration::days(start.weekday().num_days_from_monday() as i64);
+        let weeks = (end - monday).num_days() as usize / 7 + 1;
+
+        // Month names above the week they start in, when there's room
+        let mut months = " ".repeat(4 + weeks + 3);
+        let mut free_from = 0;
+        for date in start.iter_days().take_while(|date| *date <= end).filter(|date| date.day() == 1 || *date == start) {
+            let column = 4 + (date - monday).num_days() as usize / 7;
+            if column >= free_from {
+                months.replace_range(column..column + 3, &date.format("%b").to_string());
+                free_from = column + 4;
+            }
+        }
+        text.push_str(&format!("{}\n{}\n", start.year(), months.trim_end()));
+
+        for (row, label) in ["Mon", "", "Wed", "", "Fri", "", ""].iter().enumerate() {
+            let mut line = format!("{:<4}", label);
+            for week in 0..weeks {
+                let date = monday + chrono::Duration::days((week * 7 + row) as i64);
+                line.push(match days.binary_search_by_key(&date, |d| d.0) {
+                    _ if date < start || date > end => ' ',
+                    Ok(i) => HEAT[scale(days[i].1, min, max, HEAT.len())],
+                    Err(_) => '·',
+                });
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text.push('\n');
+    }
+    text.push_str(&format!("{}: less {} more (min {}, max {}), · no entry\n", name, HEAT.iter().collect::<String>(), min, max));
+    text
+}
+
+/// The same calendar as an SVG of GitHub-style squares, with each day's value as a tooltip
+pub fn heatmap_svg(name: &str, points: &[(NaiveDate, f64)]) -> String {
+    let days = daily_values(points);
+    let (min, max) = bounds(&days.iter().map(|d| Some(d.1)).collect::<Vec<_>>()).unwrap_or((0.0, 0.0));
+    let spans = year_spans(&days);
+    let block_height = 30 + 7 * HEAT_STEP;
+    let (left, width) = (32, 32 + 54 * HEAT_STEP);
+
+    let mut body = String::new();
+    for (i, (start, end)) in spans.iter().enumerate() {
+        let top = i as u32 * block_height;
+        let monday = *start - chrono::Duration::days(start.weekday().num_days_from_monday() as i64);
+        body.push_str(&format!("<text x=\"0\" y=\"{}\" font-weight=\"bold\">{}</text>\n", top + 12, start.year()));
+        for (row, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+            body.push_str(&format!("<text x=\"0\" y=\"{}\">{}</text>\n", top + 30 + row * HEAT_STEP + 9, label));
+        }
+        for date in start.iter_days().take_while(|date| date <= end) {
+            let week = (date - monday).num_days() as u32 / 7;
+            let (x, y) = (left + week * HEAT_STEP, top + 30 + date.weekday().num_days_from_monday() * HEAT_STEP);
+            if date.day() == 1 {
+                body.push_str(&format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x, top + 26, date.format("%b")));
+            }
+            let (color, title) = match days.binary_search_by_key(&date, |d| d.0) {
+                Ok(i) => (HEAT_COLORS[1 + scale(days[i].1, min, max, HEAT.len())], format!("{}: {} {}", date, days[i].1, name)),
+                Err(_) => (HEAT_COLORS[0], format!("{}: no entry", date)),
+            };
+            body.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"2\" fill=\"{}\"><title>{}</title></rect>\n",
+                x, y, HEAT_CELL, HEAT_CELL, color, title,
+            ));
+        }
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"10\">\n{}</svg>\n",
+        width,
+        (spans.len() as u32 * block_height).max(block_height),
+        body,
+    )
+}
+
+/// One value per date with any, averaging several in a day, sorted by date
+fn daily_values(points: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, f64)> {
+    let Some(first) = points.iter().map(|p| p.0).min() else {
+        return Vec::new();
+    };
+    daily_series(points)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, value)| Some((first + chrono::Duration::days(i as i64), value?)))
+        .collect()
+}
+
+/// The first and last day drawn for each calendar year the values span
+fn year_spans(days: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, NaiveDate)> {
+    let (Some(first), Some(last)) = (days.first().map(|d| d.0), days.last().map(|d| d.0)) else {
+        return Vec::new();
+    };
+    (first.year()..=last.year())
+        .map(|year| {
+            let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap().max(first);
+            let end = NaiveDate::from_ymd_opt(year, 12, 31).unwrap().min(last);
+            (start, end)
+        })
+        .collect()
+}
+
+fn bounds(values: &[Option<f64>]) -> Option<(f64, f64)> {
+    let mut present = values.iter().flatten();
+    let first = *present.next()?;
+    Some(present.fold((first, first), |(min, max), v| (min.min(*v), max.max(*v))))
+}
+
+/// `value`'s step out of `steps` between `min` and `max`; the middle one for a flat series
+fn scale(value: f64, min: f64, max: f64, steps: usize) -> usize {
+    if max == min {
+        return steps / 2;
+    }
+    (((value - min) / (max - min)) * (steps - 1) as f64).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_and_braille() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+        let series = daily_series(&[(day(1), 1.0), (day(2), 4.0), (day(2), 6.0), (day(4), 8.0)]);
+        assert_eq!(series, vec![Some(1.0), Some(5.0), None, Some(8.0)]);
+        assert_eq!(resample(&series, 2), vec![Some(3.0), Some(8.0)]);
+
+        assert_eq!(sparkline(&series), "▁▅ █");
+        assert_eq!(sparkline(&[Some(3.0), Some(3.0)]), "▅▅");
+
+        // 0 is the bottom-left dot; the rise to 3 fills the right column bottom to top
+        let lines = braille(&[Some(0.0), Some(3.0)], 1);
+        assert_eq!(lines, vec![char::from_u32(0x2800 + 0x40 + 0x08 + 0x10 + 0x20 + 0x80).unwrap().to_string()]);
+        assert_eq!(braille(&[None, None, None], 2), vec!["  ", "  "]);
+
+        let heatmap = heatmap_text("mood", &[(day(1), 1.0), (day(2), 10.0), (day(4), 4.0), (day(4), 6.0)]);
+        // 2025-07-01 is a Tuesday: one week column, Monday before the range left blank
+        assert_eq!(heatmap, "2025\n    Jul\nMon\n    ░\nWed █\n    ·\nFri ▒\n\n\n\nmood: less ░▒▓█ more (min 1, max 10), · no entry\n");
+        assert!(heatmap_svg("mood", &[(day(1), 1.0)]).contains("<title>2025-07-01: 1 mood</title>"));
+
+        let averaged = rolling_mean(&[(day(3), 6.0), (day(1), 2.0), (day(2), 4.0), (day(2), 6.0), (day(5), 9.0)], 2);
+        assert_eq!(averaged, vec![(day(1), 2.0), (day(2), 4.0), (day(3), 16.0 / 3.0), (day(5), 9.0)]);
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+
+use md_rag_core::crypto::{generate_key, store_key, KEYRING_SERVICE, KEYRING_USER, KEY_ENV};
+
+#[derive(clap::Args, Debug)]
+pub struct ContentKeyArgs {
+    /// Save the key in the OS keyring instead of printing it (builds with `--features keyring`)
+    #[arg(long)]
+    store: bool,
+}
+
+pub fn run(args: ContentKeyArgs) -> Result<()> {
+    let key = generate_key();
+    if args.store {
+        store_key(&key)?;
+        eprintln!("🔑 Saved a new content key in the OS keyring ({} / {})", KEYRING_SERVICE, KEYRING_USER);
+        return Ok(());
+    }
+    eprintln!("🔑 New content key; set {} to it, and keep a copy: the index can't be read without it", KEY_ENV);
+    println!("{}", key);
+    Ok(())
+}
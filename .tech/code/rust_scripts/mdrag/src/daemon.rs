@@ -0,0 +1,197 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use md_rag_core::embeddings::EmbeddingGenerator;
+use md_rag_core::rerank::Reranker;
+use md_rag_core::schema::{db_path, DEFAULT_TABLE_NAME};
+use md_rag_core::search::{load_search_model, search_table_page, verify_index_model, SearchPage, SearchQuery, SearchResult};
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::GlobalArgs;
+
+/// Socket file the daemon listens on, next to the database
+const SOCKET_NAME: &str = "mdrag.sock";
+
+/// The daemon's reply: `{"page": {"results": [...], "total": N}}` or `{"error": "..."}`.
+/// Daemons from before paging replied `{"results": [...]}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonResponse {
+    Page(SearchPage),
+    Results(Vec<SearchResult>),
+    Error(String),
+}
+
+/// Each table gets its own daemon, so a search never reaches one serving another table
+pub fn socket_path(lance_dir: &Path, table_name: &str) -> PathBuf {
+    if table_name == DEFAULT_TABLE_NAME {
+        lance_dir.join(SOCKET_NAME)
+    } else {
+        lance_dir.join(format!("mdrag-{}.sock", table_name))
+    }
+}
+
+/// Keep the embedding model and database connection open and answer searches on a Unix socket
+pub async fn serve(global: &GlobalArgs) -> Result<()> {
+    let socket = socket_path(&global.lance_dir, &global.table);
+
+    // A leftover socket from a crashed daemon refuses connections and can be replaced
+    if socket.exists() {
+        if UnixStream::connect(&socket).await.is_ok() {
+            bail!("A daemon is already listening on {}", socket.display());
+        }
+        std::fs::remove_file(&socket)?;
+    }
+
+    let lance_path = db_path(&global.lance_dir);
+    let db = lancedb::connect(lance_path.to_str().unwrap())
+        .execute()
+        .await?;
+    // Always the model the index was built with
+    let embedding_generator = load_search_model(lance_path.to_str().unwrap(), &global.table, global.backend, None, false).await?;
+
+    let listener = UnixListener::bind(&socket)?;
+    eprintln!("🔌 Search daemon listening on {} (Ctrl+C to stop)", socket.display());
+
+    // Loaded by the first request that asks for reranking
+    let mut reranker = None;
+
+    let template = TemplateFilter::with_config(&global.config.template);
+
+    let result = tokio::select! {
+        result = accept_loop(&listener, &db, &global.table, &embedding_generator, &mut reranker, &template) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+
+    let _ = std::fs::remove_file(&socket);
+    result
+}
+
+async fn accept_loop(
+    listener: &UnixListener,
+    db: &lancedb::Connection,
+    table_name: &str,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: &mut Option<Reranker>,
+    template: &TemplateFilter,
+) -> Result<()> {
+    // Searches take milliseconds once the model is loaded, so clients are served one at a time
+    loop {
+        let (stream, _) = listener.accept().await?;
+        if let Err(e) = handle_connection(stream, db, table_name, embedding_generator, reranker, template).await {
+            eprintln!("  ⚠️  Client error: {}", e);
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    db: &lancedb::Connection,
+    table_name: &str,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: &mut Option<Reranker>,
+    template: &TemplateFilter,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    // Requests are a `SearchQuery` as one JSON line per connection
+    let response = match serde_json::from_str::<SearchQuery>(&line) {
+        Ok(request) => match run_search(db, table_name, embedding_generator, reranker, template, &request).await {
+            Ok(page) => DaemonResponse::Page(page),
+            Err(e) => DaemonResponse::Error(e.to_string()),
+        },
+        Err(e) => DaemonResponse::Error(format!("Invalid request: {}", e)),
+    };
+
+    let mut out = serde_json::to_string(&response)?;
+    out.push('\n');
+    writer.write_all(out.as_bytes()).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+async fn run_search(
+    db: &lancedb::Connection,
+    table_name: &str,
+    embedding_generator: &EmbeddingGenerator,
+    reranker: &mut Option<Reranker>,
+    template: &TemplateFilter,
+    request: &SearchQuery,
+) -> Result<SearchPage> {
+    if request.rerank && reranker.is_none() {
+        *reranker = Some(Reranker::new()?);
+    }
+
+    // Reopened per request so the latest index version is searched after a re-index
+    let table = db.open_table(table_name).execute().await?;
+    // A rebuild with another model needs a daemon restart
+    verify_index_model(&table.schema().await?, embedding_generator)?;
+    search_table_page(&table, embedding_generator, reranker.as_ref(), template, request).await
+}
+
+/// Send a search to a running daemon.
+///
+/// Returns `Ok(None)` when no daemon is listening, so the caller can search locally.
+pub async fn query(lance_dir: &Path, table_name: &str, request: &SearchQuery) -> Result<Option<SearchPage>> {
+    let stream = match UnixStream::connect(socket_path(lance_dir, table_name)).await {
+        Ok(stream) => stream,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let (reader, mut writer) = stream.into_split();
+    let mut out = serde_json::to_string(request)?;
+    out.push('\n');
+    writer.write_all(out.as_bytes()).await?;
+    writer.flush().await?;
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    match serde_json::from_str(&line)? {
+        DaemonResponse::Page(page) => Ok(Some(page)),
+        DaemonResponse::Results(results) => Ok(Some(SearchPage { total: results.len(), results })),
+        DaemonResponse::Error(e) => bail!("Daemon search failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_format() {
+        let request: SearchQuery = serde_json::from_str(
+            r#"{"query":"rust","after":"2025-01-01","before":null,"limit":5}"#,
+        ).unwrap();
+        assert_eq!(request.after, chrono::NaiveDate::from_ymd_opt(2025, 1, 1));
+        assert_eq!(request.limit, 5);
+        assert!(!request.hybrid);
+        assert!(request.prefilter);
+
+        let response = serde_json::to_string(&DaemonResponse::Error("boom".to_string())).unwrap();
+        assert_eq!(response, r#"{"error":"boom"}"#);
+        assert!(matches!(
+            serde_json::from_str(r#"{"results":[]}"#).unwrap(),
+            DaemonResponse::Results(results) if results.is_empty()
+        ));
+        assert!(matches!(
+            serde_json::from_str(r#"{"page":{"results":[],"total":12}}"#).unwrap(),
+            DaemonResponse::Page(SearchPage { total: 12, .. })
+        ));
+    }
+
+    #[test]
+    fn test_socket_per_table() {
+        let dir = Path::new("/tmp/lance");
+        assert_eq!(socket_path(dir, DEFAULT_TABLE_NAME), dir.join("mdrag.sock"));
+        assert_eq!(socket_path(dir, "notes"), dir.join("mdrag-notes.sock"));
+    }
+}
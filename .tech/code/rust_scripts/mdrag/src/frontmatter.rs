@@ -0,0 +1,1412 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use md_rag_core::frontmatter::{
+    extract_frontmatter, lookup, numeric_value, parse_date_from_frontmatter, set_fields, split_frontmatter, yaml_to_json_value,
+};
+use md_rag_core::frontmatter_filter::Filter;
+use md_rag_core::frontmatter_migrate::{FieldType, Migration};
+use md_rag_core::frontmatter_schema::SchemaScan;
+use md_rag_core::path_date::PathDatePattern;
+use md_rag_core::scan::{markdown_files, markdown_files_sorted, EntryDates};
+use md_rag_core::tasks::checkbox_counts;
+
+use crate::chart::{self, PlotStyle};
+use crate::new::{entry_date, find_entry, parse_field, replace_file};
+use crate::{index, GlobalArgs};
+
+/// `--heatmap-field` name for the entries' word counts
+const WORDS_FIELD: &str = "words";
+
+/// Days in the rolling average of `--plot-file` charts without `--rolling`
+const DEFAULT_CHART_WINDOW: u32 = 7;
+
+#[derive(clap::Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct FrontmatterArgs {
+    #[command(subcommand)]
+    command: Option<FrontmatterCommand>,
+
+    /// Fields to extract from frontmatter; dot paths reach into nested values, e.g. sleep.hours
+    /// or exercise[0].type. NAME=KIND adds a field counted from the entry text, e.g.
+    /// tasks_done=checkbox_done; KIND is checkbox_done, checkbox_open, checkbox_total or words
+    #[arg(short, long, num_args = 1.., default_values_t = vec!["mood".to_string(), "anxiety".to_string(), "weight_kg".to_string()])]
+    fields: Vec<String>,
+
+    /// Start date filter (YYYY-MM-DD)
+    #[arg(short = 's', long)]
+    start_date: Option<String>,
+
+    /// End date filter (YYYY-MM-DD)
+    #[arg(short = 'e', long)]
+    end_date: Option<String>,
+
+    /// Date entries without a frontmatter date from their path, as `mdrag index --path-date` does
+    /// [default: `path_date` in .mdrag.toml, else YYYY/MM/DD or YYYY-MM-DD anywhere in the path]
+    #[arg(long, value_name = "PATTERN")]
+    path_date: Option<PathDatePattern>,
+
+    /// Only entries matching an expression over any frontmatter keys, e.g.
+    /// "mood >= 7 AND anxiety < 3 AND 'rust' in tags"; applied before the output and stats
+    #[arg(short = 'w', long = "where", value_name = "EXPR")]
+    filter: Option<Filter>,
+
+    /// Calculate statistics for numeric fields
+    #[arg(long)]
+    stats: bool,
+
+    /// One row per ISO week, month or year with each numeric field's count/min/max/avg,
+    /// instead of one row per entry
+    #[arg(short = 'g', long, value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Print how often each value of these fields occurs (list items counted one by one),
+    /// instead of one row per entry; spots typos like rust vs Rust
+    #[arg(long, num_args = 1.., value_name = "FIELD", conflicts_with = "group_by")]
+    distinct: Vec<String>,
+
+    /// Add a `<field>_rolling<N>` column per field: the average over the N days ending on each row
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    rolling: Option<u32>,
+
+    /// Order rows by this field (or date, or a rolling column) instead of by date; numbers by
+    /// value, entries without it last. A field not in --fields is added to them
+    #[arg(long, value_name = "FIELD")]
+    sort_by: Option<String>,
+
+    /// Sort in descending order (newest first without --sort-by)
+    #[arg(long)]
+    desc: bool,
+
+    /// Print at most this many rows, after sorting; --stats cover the rows printed
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Skip this many rows first
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+
+    /// Add a row with empty values for every day without an entry, from --start-date (or the
+    /// first entry) to --end-date (or the last one)
+    #[arg(long)]
+    fill_missing: bool,
+
+    /// Output format
+    #[arg(short = 'o', long, value_enum, default_value = "json")]
+    format: OutputFormat,
+
+    /// Include file paths in output
+    #[arg(long)]
+    include_files: bool,
+
+    /// Draw each numeric field (and rolling column) over the date range in the terminal instead
+    /// of printing rows: a sparkline, or a braille line chart with --plot braille
+    #[arg(long, value_enum, value_name = "STYLE", num_args = 0..=1, default_missing_value = "sparkline")]
+    plot: Option<PlotStyle>,
+
+    /// Also save a chart of each numeric field over time, with its rolling average (--rolling
+    /// days, default 7) overlaid, as .svg or .png
+    #[arg(long, value_name = "FILE")]
+    plot_file: Option<PathBuf>,
+
+    /// Field shaded by --format heatmap [default: the first of --fields]; `words` shades the
+    /// entries' word counts
+    #[arg(long, value_name = "FIELD")]
+    heatmap_field: Option<String>,
+
+    /// Write the output to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum FrontmatterCommand {
+    /// Set values in one entry's frontmatter, keeping the other keys, their order and comments
+    Set(SetArgs),
+    /// Rename keys and convert value types across the whole journal
+    Migrate(MigrateArgs),
+    /// List every key in the journal's frontmatter with its types, fill rate and example values
+    Schema(SchemaArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct SetArgs {
+    /// Values to set, e.g. mood=7 weight_kg=82.4 "tags=[work, rust]" (written as YAML)
+    #[arg(required = true, value_name = "KEY=VALUE", value_parser = parse_field)]
+    fields: Vec<(String, String)>,
+
+    /// Day of the entry: YYYY-MM-DD or an expression such as yesterday [default: today]
+    #[arg(short, long)]
+    date: Option<String>,
+
+    /// The entry file, instead of looking it up by date
+    #[arg(long, conflicts_with = "date")]
+    file: Option<PathBuf>,
+
+    /// Index the entry right away (an incremental pass)
+    #[arg(long)]
+    index: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct MigrateArgs {
+    /// Rename a key, e.g. --rename anxiety_level=anxiety; repeatable. Entries that already have
+    /// the new key keep the old one and are reported
+    #[arg(long, value_name = "OLD=NEW", value_parser = parse_field, required_unless_present = "coerce")]
+    rename: Vec<(String, String)>,
+
+    /// Rewrite a key's values as int, float, bool or string, e.g. --coerce weight=float; repeatable.
+    /// Applied after the renames. Values that don't convert (e.g. 3-4 as an int) are reported
+    #[arg(long, value_name = "KEY=TYPE", value_parser = parse_coercion)]
+    coerce: Vec<(String, FieldType)>,
+
+    /// Print the changed lines of every entry without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct SchemaArgs {
+    /// Output format
+    #[arg(short = 'o', long, value_enum, default_value = "table")]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Table,
+    /// A pipe table with padded columns, to paste into notes
+    Markdown,
+    /// A `<table>` to open in a browser
+    Html,
+    /// One JSON object per line; streamed straight from the files when nothing needs every row
+    Ndjson,
+    /// A GitHub-style calendar of one field (--heatmap-field); SVG when --output ends in .svg
+    Heatmap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub(crate) enum GroupBy {
+    Week,
+    Month,
+    Year,
+}
+
+/// A value counted from an entry's text, for `--fields NAME=KIND`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Derived {
+    /// Checked `- [x]` items
+    CheckboxDone,
+    /// Unchecked `- [ ]` items
+    CheckboxOpen,
+    /// Both
+    CheckboxTotal,
+    /// Words after the frontmatter
+    Words,
+}
+
+impl FromStr for Derived {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "checkbox_done" => Ok(Derived::CheckboxDone),
+            "checkbox_open" => Ok(Derived::CheckboxOpen),
+            "checkbox_total" => Ok(Derived::CheckboxTotal),
+            "words" => Ok(Derived::Words),
+            _ => Err(format!("Unknown derived field '{}'. Use checkbox_done, checkbox_open, checkbox_total or words", s)),
+        }
+    }
+}
+
+impl Derived {
+    fn count(self, body: &str) -> usize {
+        match self {
+            Derived::CheckboxDone => checkbox_counts(body).0,
+            Derived::CheckboxOpen => checkbox_counts(body).1,
+            Derived::CheckboxTotal => {
+                let (done, open) = checkbox_counts(body);
+                done + open
+            }
+            Derived::Words => body.split_whitespace().count(),
+        }
+    }
+}
+
+/// Turn each `NAME=KIND` in `fields` into plain `NAME`, returning the names with their kinds
+fn derived_fields(fields: &mut [String]) -> Result<Vec<(String, Derived)>> {
+    let mut derived = Vec::new();
+    for field in fields {
+        if let Some((name, kind)) = field.split_once('=') {
+            let kind = kind.parse().map_err(anyhow::Error::msg)?;
+            *field = name.trim().to_string();
+            derived.push((field.clone(), kind));
+        }
+    }
+    Ok(derived)
+}
+
+impl GroupBy {
+    /// The bucket `date` falls in: its label (`2025-W30`, `2025-07`, `2025`) and first day
+    pub(crate) fn bucket(self, date: NaiveDate) -> (String, NaiveDate) {
+        match self {
+            GroupBy::Week => {
+                let week = date.iso_week();
+                let monday = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                (format!("{}-W{:02}", week.year(), week.week()), monday)
+            }
+            GroupBy::Month => (date.format("%Y-%m").to_string(), date.with_day(1).unwrap()),
+            GroupBy::Year => (date.format("%Y").to_string(), date.with_ordinal(1).unwrap()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct JournalEntry {
+    file_path: PathBuf,
+    date: NaiveDate,
+    frontmatter: HashMap<String, serde_yaml::Value>,
+    /// Words in the text after the frontmatter
+    words: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct QueryResult {
+    date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(flatten)]
+    fields: HashMap<String, Option<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FieldStats {
+    count: usize,
+    /// Share of the rows with any value for the field; for a yes/no field `avg` is the share
+    /// of those that are true
+    fill_rate: f64,
+    min: f64,
+    max: f64,
+    avg: f64,
+    median: f64,
+    /// Sample standard deviation; 0 for a single value
+    stddev: f64,
+    p25: f64,
+    p75: f64,
+    /// Least-squares change per day over the entries' dates; None when they're all one day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slope_per_day: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped_count: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct GroupResult {
+    period: String,
+    start: String,
+    entries: usize,
+    stats: HashMap<String, FieldStats>,
+}
+
+fn find_journal_files(
+    base_dir: &Path,
+    dates: &EntryDates,
+    derived: &[(String, Derived)],
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+) -> Result<Vec<JournalEntry>> {
+    let mut entries: Vec<JournalEntry> = journal_entries(base_dir, dates, derived, start_date, end_date).collect();
+    
+    // Sort by date
+    entries.sort_by_key(|e| e.date);
+    
+    Ok(entries)
+}
+
+/// Dated entries under `base_dir` within the date filters, read one at a time in path order.
+/// An entry without a frontmatter date (or without frontmatter) is dated by its path, the
+/// way `mdrag index` does; one dated by neither is left out. `derived` fields are counted from
+/// the text and added to the frontmatter, so they work like any other key
+fn journal_entries(
+    base_dir: &Path,
+    dates: &EntryDates,
+    derived: &[(String, Derived)],
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+) -> impl Iterator<Item = JournalEntry> {
+    markdown_files_sorted(base_dir).filter_map(move |path| {
+        // Skip files we can't read or date
+        let content = fs::read_to_string(&path).ok()?;
+        let mut frontmatter = extract_frontmatter(&content).unwrap_or_default();
+        let date = parse_date_from_frontmatter(&frontmatter, &dates.formats)
+            .ok()
+            .or_else(|| dates.path.date(path.strip_prefix(base_dir).unwrap_or(&path)))?;
+        
+        // Apply date filters
+        if start_date.is_some_and(|start| date < start) || end_date.is_some_and(|end| date > end) {
+            return None;
+        }
+        
+        let body = split_frontmatter(&content).1;
+        for (name, kind) in derived {
+            frontmatter.insert(name.clone(), serde_yaml::Value::from(kind.count(body) as u64));
+        }
+        Some(JournalEntry {
+            file_path: path,
+            date,
+            frontmatter,
+            words: Derived::Words.count(body),
+        })
+    })
+}
+
+fn query_fields(entries: &[JournalEntry], fields: &[String], include_files: bool) -> Vec<QueryResult> {
+    entries.iter().map(|entry| query_result(entry, fields, include_files)).collect()
+}
+
+fn query_result(entry: &JournalEntry, fields: &[String], include_files: bool) -> QueryResult {
+    let mut field_values = HashMap::new();
+    
+    for field in fields {
+        let value = lookup(&entry.frontmatter, field)
+            .map(yaml_to_json_value)
+            .filter(|v| !matches!(v, serde_json::Value::Null));
+        
+        field_values.insert(field.clone(), value);
+    }
+    
+    QueryResult {
+        date: entry.date.format("%Y-%m-%d").to_string(),
+        file: if include_files {
+            Some(entry.file_path.display().to_string())
+        } else {
+            None
+        },
+        fields: field_values,
+    }
+}
+
+/// `results` (sorted by date) with an empty row for each day between `start` and `end` that has none
+fn fill_missing(
+    results: Vec<QueryResult>,
+    fields: &[String],
+    include_files: bool,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Vec<QueryResult> {
+    let day = |result: &QueryResult| NaiveDate::parse_from_str(&result.date, "%Y-%m-%d").ok();
+    let first = start.or_else(|| results.first().and_then(day));
+    let last = end.or_else(|| results.last().and_then(day));
+    let (Some(first), Some(last)) = (first, last) else {
+        return results;
+    };
+
+    let mut filled = Vec::new();
+    let mut results = results.into_iter().peekable();
+    for date in first.iter_days().take_while(|date| *date <= last) {
+        let mut found = false;
+        while let Some(result) = results.next_if(|result| day(result).is_some_and(|d| d <= date)) {
+            found |= day(&result) == Some(date);
+            filled.push(result);
+        }
+        if !found {
+            filled.push(QueryResult {
+                date: date.format("%Y-%m-%d").to_string(),
+                // Keeps the CSV columns aligned
+                file: include_files.then(String::new),
+                fields: fields.iter().map(|field| (field.clone(), None)).collect(),
+            });
+        }
+    }
+    filled.extend(results);
+    filled
+}
+
+/// Add each field's average over the `days` days ending on the row's date, ignoring empty and
+/// non-numeric values, as a `<field>_rolling<days>` value. Returns the new column names
+fn add_rolling(results: &mut [QueryResult], fields: &[String], days: u32) -> Vec<String> {
+    let dates: Vec<Option<NaiveDate>> = results.iter().map(|r| NaiveDate::parse_from_str(&r.date, "%Y-%m-%d").ok()).collect();
+    let mut columns = Vec::new();
+    for field in fields {
+        let column = format!("{}_rolling{}", field, days);
+        let averages: Vec<Option<f64>> = (0..results.len())
+            .map(|i| {
+                let end = dates[i]?;
+                let window_start = end - chrono::Duration::days(days as i64 - 1);
+                let values: Vec<f64> = (0..=i)
+                    .rev()
+                    .take_while(|&j| dates[j].is_some_and(|date| date >= window_start))
+                    .filter_map(|j| results[j].fields.get(field)?.as_ref().and_then(numeric_value))
+                    .collect();
+                (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+            })
+            .collect();
+        for (result, average) in results.iter_mut().zip(averages) {
+            let average = average.map(|avg| json!((avg * 100.0).round() / 100.0));
+            result.fields.insert(column.clone(), average);
+        }
+        columns.push(column);
+    }
+    columns
+}
+
+/// Order rows by `field` (`date` when None), keeping date order among equal values. Values
+/// compare as numbers when both are numeric, else as text; rows without one go last either way
+fn sort_results(results: &mut [QueryResult], field: Option<&str>, desc: bool) {
+    let field = field.unwrap_or("date");
+    let key = |result: &QueryResult| -> Option<serde_json::Value> {
+        if field == "date" {
+            return Some(serde_json::Value::String(result.date.clone()));
+        }
+        result.fields.get(field).cloned().flatten().filter(|value| !value.is_null())
+    };
+    let text = |value: &serde_json::Value| value.as_str().map_or_else(|| value.to_string(), str::to_string);
+    results.sort_by(|a, b| match (key(a), key(b)) {
+        (Some(a), Some(b)) => {
+            let ordering = match (numeric_value(&a), numeric_value(&b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                _ => text(&a).cmp(&text(&b)),
+            };
+            if desc { ordering.reverse() } else { ordering }
+        }
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+fn calculate_stats(results: &[QueryResult], field: &str) -> Option<FieldStats> {
+    let mut values = Vec::new();
+    let mut days = Vec::new();
+    let mut skipped = 0;
+    let mut filled = 0;
+    
+    for result in results {
+        if let Some(Some(value)) = result.fields.get(field) {
+            filled += 1;
+            if let Some(num) = numeric_value(value) {
+                values.push(num);
+                if let Ok(date) = NaiveDate::parse_from_str(&result.date, "%Y-%m-%d") {
+                    days.push((date.num_days_from_ce() as f64, num));
+                }
+            } else {
+                skipped += 1;
+            }
+        }
+    }
+    
+    if values.is_empty() {
+        return None;
+    }
+    
+    let count = values.len();
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let sum: f64 = values.iter().sum();
+    let avg = sum / count as f64;
+    let variance = if count > 1 {
+        values.iter().map(|v| (v - avg).powi(2)).sum::<f64>() / (count - 1) as f64
+    } else {
+        0.0
+    };
+    values.sort_by(f64::total_cmp);
+    
+    Some(FieldStats {
+        count,
+        fill_rate: filled as f64 / results.len() as f64,
+        min,
+        max,
+        avg,
+        median: percentile(&values, 0.5),
+        stddev: variance.sqrt(),
+        p25: percentile(&values, 0.25),
+        p75: percentile(&values, 0.75),
+        slope_per_day: slope(&days),
+        skipped_count: if skipped > 0 { Some(skipped) } else { None },
+    })
+}
+
+/// The `p` quantile of sorted values, interpolating between the two nearest
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+/// Slope of the least-squares line through (day, value) points
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let spread: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if spread == 0.0 {
+        return None;
+    }
+    Some(points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>() / spread)
+}
+
+/// Entries (sorted by date) split into buckets, with the stats of each numeric field per bucket
+fn group_entries(entries: &[JournalEntry], fields: &[String], group_by: GroupBy) -> Vec<GroupResult> {
+    entries
+        .chunk_by(|a, b| group_by.bucket(a.date).0 == group_by.bucket(b.date).0)
+        .map(|bucket| {
+            let (period, start) = group_by.bucket(bucket[0].date);
+            let results = query_fields(bucket, fields, false);
+            let stats = fields
+                .iter()
+                .filter_map(|field| Some((field.clone(), calculate_stats(&results, field)?)))
+                .collect();
+            GroupResult { period, start: start.format("%Y-%m-%d").to_string(), entries: bucket.len(), stats }
+        })
+        .collect()
+}
+
+/// Each value of `field` with the number of entries using it, most used first. List items count
+/// separately; text is compared exactly, so case variants stay apart
+fn distinct_values(entries: &[JournalEntry], field: &str) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        let values = match lookup(&entry.frontmatter, field).map(yaml_to_json_value) {
+            Some(serde_json::Value::Array(items)) => items,
+            Some(value) => vec![value],
+            None => continue,
+        };
+        for value in values {
+            let text = match value {
+                serde_json::Value::Null => continue,
+                serde_json::Value::String(s) if s.trim().is_empty() => continue,
+                serde_json::Value::String(s) => s.trim().to_string(),
+                value => value.to_string(),
+            };
+            *counts.entry(text).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+fn output_distinct(out: &mut dyn Write, entries: &[JournalEntry], fields: &[String], format: &OutputFormat) -> Result<()> {
+    let tables: Vec<(&String, Vec<(String, usize)>)> = fields.iter().map(|field| (field, distinct_values(entries, field))).collect();
+
+    for (field, counts) in &tables {
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for (value, _) in counts {
+            if let Some(other) = seen.insert(value.to_lowercase(), value) {
+                eprintln!("⚠️  {}: '{}' and '{}' differ only in case", field, other, value);
+            }
+        }
+    }
+
+    if let OutputFormat::Json = format {
+        let output: serde_json::Map<String, serde_json::Value> = tables
+            .iter()
+            .map(|(field, counts)| {
+                let counts = counts.iter().map(|(value, count)| json!({ "value": value, "count": count })).collect();
+                (field.to_string(), serde_json::Value::Array(counts))
+            })
+            .collect();
+        return write_json(out, &output);
+    }
+    if let OutputFormat::Ndjson = format {
+        let lines = tables.iter().flat_map(|(field, counts)| {
+            counts.iter().map(move |(value, count)| json!({ "field": field, "value": value, "count": count }))
+        });
+        return write_ndjson(out, lines);
+    }
+    let header = ["field", "value", "count"].map(String::from);
+    let rows: Vec<Vec<String>> = tables
+        .iter()
+        .flat_map(|(field, counts)| counts.iter().map(move |(value, count)| vec![field.to_string(), value.clone(), count.to_string()]))
+        .collect();
+    write_rows(out, format, &header, &rows, &[])
+}
+
+fn output_groups(out: &mut dyn Write, groups: &[GroupResult], fields: &[String], format: &OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => return write_json(out, &groups),
+        OutputFormat::Ndjson => return write_ndjson(out, groups),
+        _ => {}
+    }
+
+    let mut header = vec!["period".to_string(), "start".to_string(), "entries".to_string()];
+    for field in fields {
+        header.extend(["count", "min", "max", "avg"].map(|stat| format!("{}_{}", field, stat)));
+    }
+
+    let mut rows = Vec::new();
+    for group in groups {
+        let mut row = vec![group.period.clone(), group.start.clone(), group.entries.to_string()];
+        for field in fields {
+            match group.stats.get(field) {
+                Some(stats) => row.extend([
+                    stats.count.to_string(),
+                    stats.min.to_string(),
+                    stats.max.to_string(),
+                    format!("{:.2}", stats.avg),
+                ]),
+                None => row.extend(["0".to_string(), String::new(), String::new(), String::new()]),
+            }
+        }
+        rows.push(row);
+    }
+    write_rows(out, format, &header, &rows, &[])
+}
+
+fn output_json(out: &mut dyn Write, results: &[QueryResult], stats: Option<HashMap<String, FieldStats>>) -> Result<()> {
+    if let Some(stats) = stats {
+        let output = json!({
+            "results": results,
+            "stats": stats,
+        });
+        write_json(out, &output)
+    } else {
+        write_json(out, &results)
+    }
+}
+
+/// Per-entry rows for the row formats: date, the file with --include-files, then `fields`.
+/// Markdown and HTML end with count/avg/min/max rows when there are stats
+fn output_rows(
+    out: &mut dyn Write,
+    results: &[QueryResult],
+    fields: &[String],
+    include_files: bool,
+    format: &OutputFormat,
+    stats: Option<&HashMap<String, FieldStats>>,
+) -> Result<()> {
+    let header = row_header(fields, include_files);
+    let rows: Vec<Vec<String>> = results.iter().map(|result| row_cells(result, fields, include_files)).collect();
+
+    let mut footer = Vec::new();
+    if let (Some(stats), OutputFormat::Markdown | OutputFormat::Html) = (stats, format) {
+        let stat_rows: [(&str, fn(&FieldStats) -> String); 4] = [
+            ("count", |s| s.count.to_string()),
+            ("avg", |s| format!("{:.2}", s.avg)),
+            ("min", |s| s.min.to_string()),
+            ("max", |s| s.max.to_string()),
+        ];
+        for (label, value) in stat_rows {
+            let mut row = vec![label.to_string()];
+            if include_files {
+                row.push(String::new());
+            }
+            row.extend(fields.iter().map(|field| stats.get(field).map(value).unwrap_or_default()));
+            footer.push(row);
+        }
+    }
+    write_rows(out, format, &header, &rows, &footer)
+}
+
+/// The dated numeric values of `column`, in date order
+fn numeric_points(results: &[QueryResult], column: &str) -> Vec<(NaiveDate, f64)> {
+    let mut points: Vec<(NaiveDate, f64)> = results
+        .iter()
+        .filter_map(|result| {
+            let date = NaiveDate::parse_from_str(&result.date, "%Y-%m-%d").ok()?;
+            Some((date, numeric_value(result.fields.get(column)?.as_ref()?)?))
+        })
+        .collect();
+    points.sort_by_key(|point| point.0);
+    points
+}
+
+/// A chart per column with numeric values, one slot per day between its first and last value
+fn output_plots(out: &mut dyn Write, results: &[QueryResult], columns: &[String], style: PlotStyle) -> Result<()> {
+    for column in columns {
+        let points = numeric_points(results, column);
+        let (Some(first), Some(last)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.0).max()) else {
+            continue;
+        };
+        let values: Vec<f64> = points.iter().map(|p| p.1).collect();
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let avg = values.iter().sum::<f64>() / values.len() as f64;
+        writeln!(out, "{}  {} → {}  min {} max {} avg {:.2}", column, first, last, min, max, avg)?;
+
+        let series = chart::daily_series(&points);
+        let width = chart::chart_width(0);
+        match style {
+            PlotStyle::Sparkline => writeln!(out, "{}", chart::sparkline(&chart::resample(&series, width)))?,
+            PlotStyle::Braille => {
+                for line in chart::braille(&chart::resample(&series, width * 2), 4) {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn row_header(fields: &[String], include_files: bool) -> Vec<String> {
+    let mut header = vec!["date".to_string()];
+    if include_files {
+        header.push("file".to_string());
+    }
+    header.extend(fields.iter().cloned());
+    header
+}
+
+fn row_cells(result: &QueryResult, fields: &[String], include_files: bool) -> Vec<String> {
+    let mut row = vec![result.date.clone()];
+    if include_files {
+        row.push(result.file.clone().unwrap_or_default());
+    }
+    row.extend(fields.iter().map(|field| cell_text(result.fields.get(field))));
+    row
+}
+
+/// Writes per-entry rows one at a time as NDJSON, CSV or tab-separated text
+enum RowSink<'a> {
+    Ndjson(&'a mut dyn Write),
+    Csv(csv::Writer<&'a mut dyn Write>),
+    Table(&'a mut dyn Write),
+}
+
+impl<'a> RowSink<'a> {
+    /// None for formats that need every row before writing the first
+    fn new(out: &'a mut dyn Write, format: &OutputFormat, header: &[String]) -> Result<Option<Self>> {
+        Ok(Some(match format {
+            OutputFormat::Ndjson => RowSink::Ndjson(out),
+            OutputFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(out);
+                writer.write_record(header)?;
+                RowSink::Csv(writer)
+            }
+            OutputFormat::Table => {
+                writeln!(out, "{}", header.join("\t"))?;
+                RowSink::Table(out)
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    fn write(&mut self, result: &QueryResult, fields: &[String], include_files: bool) -> Result<()> {
+        match self {
+            RowSink::Ndjson(out) => {
+                serde_json::to_writer(&mut **out, result)?;
+                writeln!(out)?;
+            }
+            RowSink::Csv(writer) => writer.write_record(row_cells(result, fields, include_files))?,
+            RowSink::Table(out) => writeln!(out, "{}", row_cells(result, fields, include_files).join("\t"))?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        if let RowSink::Csv(mut writer) = self {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Query entries straight from the files to the output without holding them, for output that
+/// needs no sorting, paging, filling, rolling averages, stats or charts. Entries come in path
+/// order (date order for `YYYY/MM/DD.md`), one journal directory after another
+fn stream_rows(
+    out: &mut dyn Write,
+    global: &GlobalArgs,
+    args: &FrontmatterArgs,
+    dates: &EntryDates,
+    derived: &[(String, Derived)],
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+) -> Result<()> {
+    let Some(mut sink) = RowSink::new(out, &args.format, &row_header(&args.fields, args.include_files))? else {
+        bail!("--format {:?} can't be streamed", args.format);
+    };
+    for dir in &global.journal_dirs {
+        for entry in journal_entries(dir, dates, derived, start_date, end_date) {
+            if let Some(filter) = &args.filter
+                && !filter.matches(&|key| lookup(&entry.frontmatter, key).map(yaml_to_json_value))
+            {
+                continue;
+            }
+            sink.write(&query_result(&entry, &args.fields, args.include_files), &args.fields, args.include_files)?;
+        }
+    }
+    sink.finish()
+}
+
+/// One JSON document per line
+fn write_ndjson<T: Serialize>(out: &mut dyn Write, items: impl IntoIterator<Item = T>) -> Result<()> {
+    for item in items {
+        serde_json::to_writer(&mut *out, &item)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// A field's value as printed in a cell: strings without quotes, empty when missing
+fn cell_text(value: Option<&Option<serde_json::Value>>) -> String {
+    match value {
+        Some(Some(serde_json::Value::String(s))) => s.clone(),
+        Some(Some(value)) => value.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// A header and rows as CSV (quoted where a value needs it), a markdown or HTML table, or
+/// tab-separated text. Only markdown and HTML show `footer` rows
+fn write_rows(out: &mut dyn Write, format: &OutputFormat, header: &[String], rows: &[Vec<String>], footer: &[Vec<String>]) -> Result<()> {
+    match format {
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(header)?;
+            for row in rows {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Markdown => write!(out, "{}", markdown_table(header, rows, footer))?,
+        OutputFormat::Html => write!(out, "{}", html_table(header, rows, footer))?,
+        // A heatmap needs per-entry dates; groups, distinct values and the schema print as a table
+        OutputFormat::Table | OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Heatmap => {
+            writeln!(out, "{}", header.join("\t"))?;
+            for row in rows {
+                writeln!(out, "{}", row.join("\t"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A pipe table with every column padded to its widest cell; footer rows have bold labels
+fn markdown_table(header: &[String], rows: &[Vec<String>], footer: &[Vec<String>]) -> String {
+    let escape = |cell: &String| cell.replace('|', "\\|").replace('\n', "<br>");
+    let mut cells: Vec<Vec<String>> = vec![header.iter().map(escape).collect()];
+    cells.extend(rows.iter().map(|row| row.iter().map(escape).collect()));
+    cells.extend(footer.iter().map(|row| {
+        row.iter().enumerate().map(|(i, cell)| if i == 0 && !cell.is_empty() { format!("**{}**", escape(cell)) } else { escape(cell) }).collect()
+    }));
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| cells.iter().filter_map(|row| row.get(i)).map(|cell| cell.chars().count()).max().unwrap_or(0).max(3))
+        .collect();
+    let line = |row: &[String]| {
+        let padded: Vec<String> = widths.iter().enumerate().map(|(i, width)| {
+            let cell = row.get(i).map(String::as_str).unwrap_or("");
+            format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+        }).collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+
+    let mut table = line(&cells[0]);
+    table.push_str(&format!("|{}|\n", widths.iter().map(|width| "-".repeat(width + 2)).collect::<Vec<_>>().join("|")));
+    for row in &cells[1..] {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+/// A `<table>` with the header in `<thead>` and footer rows in `<tfoot>`
+fn html_table(header: &[String], rows: &[Vec<String>], footer: &[Vec<String>]) -> String {
+    let html_row = |row: &[String], tag: &str| {
+        let cells: String = row.iter().map(|cell| format!("<{tag}>{}</{tag}>", escape_html(cell))).collect();
+        format!("    <tr>{}</tr>\n", cells)
+    };
+    let mut table = format!("<table>\n  <thead>\n{}  </thead>\n  <tbody>\n", html_row(header, "th"));
+    for row in rows {
+        table.push_str(&html_row(row, "td"));
+    }
+    table.push_str("  </tbody>\n");
+    if !footer.is_empty() {
+        table.push_str("  <tfoot>\n");
+        for row in footer {
+            table.push_str(&html_row(row, "td"));
+        }
+        table.push_str("  </tfoot>\n");
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn write_json(out: &mut dyn Write, value: &impl Serialize) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Buffered stdout, or the `--output` file
+fn open_output(path: Option<&Path>) -> Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => {
+            let file = fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+pub async fn run(global: &GlobalArgs, mut args: FrontmatterArgs) -> Result<()> {
+    match args.command {
+        Some(FrontmatterCommand::Set(args)) => return set(global, args).await,
+        Some(FrontmatterCommand::Migrate(args)) => return migrate(global, args),
+        Some(FrontmatterCommand::Schema(args)) => return schema(global, args),
+        None => {}
+    }
+
+    // Parse dates if provided
+    let start_date = args.start_date
+        .as_ref()
+        .map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d"))
+        .transpose()
+        .context("Invalid start date format")?;
+    
+    let end_date = args.end_date
+        .as_ref()
+        .map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d"))
+        .transpose()
+        .context("Invalid end date format")?;
+    let mut dates = global.config.entry_dates()?;
+    if let Some(pattern) = &args.path_date {
+        dates.path = pattern.clone();
+    }
+    let derived = derived_fields(&mut args.fields)?;
+    
+    // Paging follows the date order the other formats sort into, which path order may not be
+    let needs_all_rows = args.sort_by.is_some()
+        || args.desc
+        || args.limit.is_some()
+        || args.offset > 0
+        || args.fill_missing
+        || args.rolling.is_some()
+        || args.stats
+        || args.plot.is_some()
+        || args.plot_file.is_some()
+        || args.group_by.is_some()
+        || !args.distinct.is_empty();
+    if !needs_all_rows && matches!(args.format, OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::Table) {
+        let mut out = open_output(args.output.as_deref())?;
+        stream_rows(&mut out, global, &args, &dates, &derived, start_date, end_date)?;
+        return finish_output(out, args.output.as_deref());
+    }
+    
+    // Find and process journal files
+    let mut entries = Vec::new();
+    for dir in &global.journal_dirs {
+        entries.extend(find_journal_files(dir, &dates, &derived, start_date, end_date)?);
+    }
+    entries.sort_by_key(|e| e.date);
+    if let Some(filter) = &args.filter {
+        entries.retain(|entry| filter.matches(&|key| lookup(&entry.frontmatter, key).map(yaml_to_json_value)));
+    }
+    let mut out = open_output(args.output.as_deref())?;
+    if !args.distinct.is_empty() {
+        output_distinct(&mut out, &entries, &args.distinct, &args.format)?;
+        return finish_output(out, args.output.as_deref());
+    }
+    if let Some(group_by) = args.group_by {
+        output_groups(&mut out, &group_entries(&entries, &args.fields, group_by), &args.fields, &args.format)?;
+        return finish_output(out, args.output.as_deref());
+    }
+    let mut fields = args.fields.clone();
+    if let Some(sort_by) = &args.sort_by {
+        let rolling = args.rolling.is_some_and(|days| sort_by.ends_with(&format!("_rolling{}", days)));
+        if sort_by != "date" && !rolling && !fields.contains(sort_by) {
+            fields.push(sort_by.clone());
+        }
+    }
+    let heatmap_field = args.heatmap_field.clone().unwrap_or_else(|| fields[0].clone());
+    if let OutputFormat::Heatmap = args.format
+        && heatmap_field != WORDS_FIELD
+        && !fields.contains(&heatmap_field)
+    {
+        fields.push(heatmap_field.clone());
+    }
+    let mut results = query_fields(&entries, &fields, args.include_files);
+    if args.fill_missing {
+        results = fill_missing(results, &fields, args.include_files, start_date, end_date);
+    }
+    let mut columns = fields.clone();
+    if let Some(days) = args.rolling {
+        columns.extend(add_rolling(&mut results, &fields, days));
+    }
+    // Charts cover every filtered entry, whatever --sort-by, --offset and --limit pick for rows
+    if let Some(path) = &args.plot_file {
+        let series: Vec<(String, Vec<(NaiveDate, f64)>)> = fields
+            .iter()
+            .map(|field| (field.clone(), numeric_points(&results, field)))
+            .filter(|(_, points)| !points.is_empty())
+            .collect();
+        if series.is_empty() {
+            bail!("None of {} has numeric values to chart", fields.join(", "));
+        }
+        chart::write_chart(path, &series, args.rolling.unwrap_or(DEFAULT_CHART_WINDOW))?;
+        eprintln!("📈 Wrote chart {}", path.display());
+    }
+    if let OutputFormat::Heatmap = args.format {
+        let points = if heatmap_field == WORDS_FIELD {
+            entries.iter().map(|entry| (entry.date, entry.words as f64)).collect()
+        } else {
+            numeric_points(&results, &heatmap_field)
+        };
+        let svg = args.output.as_ref().is_some_and(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")));
+        let heatmap = if svg { chart::heatmap_svg(&heatmap_field, &points) } else { chart::heatmap_text(&heatmap_field, &points) };
+        write!(out, "{}", heatmap)?;
+        return finish_output(out, args.output.as_deref());
+    }
+    if let Some(style) = args.plot {
+        output_plots(&mut out, &results, &columns, style)?;
+        return finish_output(out, args.output.as_deref());
+    }
+
+    sort_results(&mut results, args.sort_by.as_deref(), args.desc);
+    results.drain(..args.offset.min(results.len()));
+    if let Some(limit) = args.limit {
+        results.truncate(limit);
+    }
+    
+    // Calculate statistics if requested
+    let stats = if args.stats {
+        let mut field_stats = HashMap::new();
+        for field in &fields {
+            if let Some(stats) = calculate_stats(&results, field) {
+                field_stats.insert(field.clone(), stats);
+            }
+        }
+        Some(field_stats)
+    } else {
+        None
+    };
+    
+    // Output results
+    match args.format {
+        OutputFormat::Json => output_json(&mut out, &results, stats)?,
+        OutputFormat::Ndjson => {
+            write_ndjson(&mut out, &results)?;
+            if let Some(stats) = stats {
+                write_ndjson(&mut out, [json!({ "stats": stats })])?;
+            }
+        }
+        _ => output_rows(&mut out, &results, &columns, args.include_files, &args.format, stats.as_ref())?,
+    }
+    finish_output(out, args.output.as_deref())
+}
+
+fn finish_output(mut out: Box<dyn Write>, path: Option<&Path>) -> Result<()> {
+    out.flush()?;
+    if let Some(path) = path {
+        eprintln!("💾 Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+async fn set(global: &GlobalArgs, args: SetArgs) -> Result<()> {
+    let path = match args.file {
+        Some(path) => path,
+        None => locate_entry(global, entry_date(args.date.as_deref())?)?,
+    };
+    set_entry_fields(&path, &args.fields)?;
+    let values: Vec<String> = args.fields.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+    eprintln!("✏️  Updated {}: {}", path.display(), values.join(", "));
+
+    if args.index {
+        let indexed = index::update(global, &mut None).await?;
+        eprintln!("✅ Indexed {} new or modified entries", indexed);
+    }
+    Ok(())
+}
+
+fn migrate(global: &GlobalArgs, args: MigrateArgs) -> Result<()> {
+    let migration = Migration { renames: args.rename, coercions: args.coerce };
+    let (mut scanned, mut changed, mut problems) = (0, 0, 0);
+
+    for dir in &global.journal_dirs {
+        for path in markdown_files(dir) {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            scanned += 1;
+            let migrated = migration.apply(&content);
+            for problem in &migrated.problems {
+                eprintln!("  ⚠️  {}: {}", path.display(), problem);
+            }
+            problems += migrated.problems.len();
+            let Some(updated) = migrated.content else {
+                continue;
+            };
+            if let Err(e) = extract_frontmatter(&updated) {
+                eprintln!("  ⚠️  {}: left as is, the migrated frontmatter wouldn't parse ({:#})", path.display(), e);
+                problems += 1;
+                continue;
+            }
+            changed += 1;
+            if args.dry_run {
+                print_diff(&path, &content, &updated);
+            } else {
+                replace_file(&path, &updated)?;
+            }
+        }
+    }
+
+    if args.dry_run {
+        eprintln!("\n🔍 {} of {} entries would change, {} problem(s) (dry run, nothing written)", changed, scanned, problems);
+    } else {
+        eprintln!("\n✅ Migrated {} of {} entries, {} problem(s)", changed, scanned, problems);
+        if changed > 0 {
+            eprintln!("   Run `mdrag index` so searches and --where filters see the new keys");
+        }
+    }
+    Ok(())
+}
+
+fn schema(global: &GlobalArgs, args: SchemaArgs) -> Result<()> {
+    let mut scan = SchemaScan::default();
+    for dir in &global.journal_dirs {
+        for path in markdown_files(dir) {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(frontmatter) = extract_frontmatter(&content) {
+                scan.add(&frontmatter);
+            }
+        }
+    }
+    let schema = scan.finish();
+
+    let mut out = open_output(None)?;
+    if let OutputFormat::Json = args.format {
+        write_json(&mut out, &schema)?;
+    } else if let OutputFormat::Ndjson = args.format {
+        write_ndjson(&mut out, &schema.keys)?;
+    } else {
+        let header = ["key", "types", "filled", "fill_rate", "examples"].map(String::from);
+        let rows: Vec<Vec<String>> = schema
+            .keys
+            .iter()
+            .map(|key| {
+                let types: Vec<String> = key.types.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+                vec![
+                    key.key.clone(),
+                    types.join(", "),
+                    key.filled.to_string(),
+                    format!("{:.0}%", key.fill_rate * 100.0),
+                    key.examples.join(" | "),
+                ]
+            })
+            .collect();
+        write_rows(&mut out, &args.format, &header, &rows, &[])?;
+    }
+    out.flush()?;
+    eprintln!("\n📋 {} keys across {} entries with frontmatter", schema.keys.len(), schema.entries);
+    Ok(())
+}
+
+/// The lines a migration changes; it rewrites lines in place, so they pair up
+fn print_diff(path: &Path, before: &str, after: &str) {
+    println!("--- {}", path.display());
+    for (old, new) in before.lines().zip(after.lines()).filter(|(old, new)| old != new) {
+        println!("-{}\n+{}", old, new);
+    }
+}
+
+/// `KEY=TYPE` from `--coerce`
+fn parse_coercion(text: &str) -> Result<(String, FieldType), String> {
+    let (key, field_type) = parse_field(text)?;
+    Ok((key, field_type.parse()?))
+}
+
+/// The entry for `date`: `YYYY/MM/DD.md` in a journal directory, else the first file whose
+/// frontmatter date is that day
+pub(crate) fn locate_entry(global: &GlobalArgs, date: NaiveDate) -> Result<PathBuf> {
+    if let Some(path) = find_entry(global, date) {
+        return Ok(path);
+    }
+    let dates = global.config.entry_dates()?;
+    for dir in &global.journal_dirs {
+        if let Some(entry) = find_journal_files(dir, &dates, &[], Some(date), Some(date))?.into_iter().next() {
+            return Ok(entry.file_path);
+        }
+    }
+    bail!("No journal entry for {}; start one with `mdrag new {}`", date, date)
+}
+
+/// Set `fields` in the frontmatter of the entry at `path`. Nothing is written if the result
+/// wouldn't parse, e.g. because a value isn't valid YAML
+pub(crate) fn set_entry_fields(path: &Path, fields: &[(String, String)]) -> Result<()> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let updated = set_fields(&content, fields);
+    extract_frontmatter(&updated).with_context(|| format!("Not updating {}: the new frontmatter is invalid", path.display()))?;
+    replace_file(path, &updated)
+}
+
+/// A markdown table of `fields` for every entry from `start` to `end`, followed by the
+/// statistics of the numeric ones; None when no entry in the period has frontmatter
+pub(crate) fn summary_table(global: &GlobalArgs, fields: &[String], start: NaiveDate, end: NaiveDate) -> Result<Option<String>> {
+    let dates = global.config.entry_dates()?;
+    let mut entries = Vec::new();
+    for dir in &global.journal_dirs {
+        entries.extend(find_journal_files(dir, &dates, &[], Some(start), Some(end))?);
+    }
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    entries.sort_by_key(|e| e.date);
+    let results = query_fields(&entries, fields, false);
+
+    let mut table = format!("| date | {} |\n|---|{}\n", fields.join(" | "), "---|".repeat(fields.len()));
+    for result in &results {
+        let values: Vec<String> = fields
+            .iter()
+            .map(|field| cell_text(result.fields.get(field)))
+            .collect();
+        table.push_str(&format!("| {} | {} |\n", result.date, values.join(" | ")));
+    }
+    for field in fields {
+        if let Some(stats) = calculate_stats(&results, field) {
+            table.push_str(&format!(
+                "\n{}: average {:.1} over {} entries (min {}, max {})",
+                field, stats.avg, stats.count, stats.min, stats.max,
+            ));
+        }
+    }
+    Ok(Some(table))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("2025/07")).unwrap();
+        fs::write(dir.join("2025/07/20.md"), "---\nmood: 6\n---\nA short day\n").unwrap();
+        fs::write(dir.join("2025/07/21.md"), "# No frontmatter at all\n").unwrap();
+        fs::write(dir.join("2025/07/notes.md"), "---\ndate: 22/07/2025\n---\n").unwrap();
+        fs::write(dir.join("ideas.md"), "Undated\n").unwrap();
+
+        let mut dates = EntryDates { formats: vec!["%d/%m/%Y".to_string()], ..Default::default() };
+        let found: Vec<_> = journal_entries(dir, &dates, &[], None, None).map(|e| (e.date.to_string(), e.words)).collect();
+        assert_eq!(found, vec![("2025-07-20".to_string(), 3), ("2025-07-21".to_string(), 5), ("2025-07-22".to_string(), 0)]);
+
+        dates.path = "%Y/%m/%d".parse().unwrap();
+        dates.formats.clear();
+        assert_eq!(journal_entries(dir, &dates, &[], "2025-07-21".parse().ok(), None).count(), 1);
+    }
+
+    #[test]
+    fn test_group_entries() {
+        let entry = |date: &str, mood: &str| JournalEntry {
+            file_path: PathBuf::from(format!("{}.md", date)),
+            date: date.parse().unwrap(),
+            frontmatter: HashMap::from([("mood".to_string(), serde_yaml::from_str(mood).unwrap())]),
+            words: 0,
+        };
+        let entries = [entry("2024-12-30", "4"), entry("2025-01-05", "6"), entry("2025-01-06", "3-4"), entry("2025-01-07", "good")];
+        let fields = vec!["mood".to_string()];
+
+        let weeks = group_entries(&entries, &fields, GroupBy::Week);
+        let periods: Vec<_> = weeks.iter().map(|g| (g.period.as_str(), g.start.as_str(), g.entries)).collect();
+        assert_eq!(periods, vec![("2025-W01", "2024-12-30", 2), ("2025-W02", "2025-01-06", 2)]);
+        assert_eq!(weeks[0].stats["mood"].avg, 5.0);
+        assert_eq!((weeks[1].stats["mood"].count, weeks[1].stats["mood"].skipped_count), (1, Some(1)));
+
+        let months = group_entries(&entries, &fields, GroupBy::Month);
+        assert_eq!(months.iter().map(|g| g.period.as_str()).collect::<Vec<_>>(), vec!["2024-12", "2025-01"]);
+        assert_eq!(group_entries(&entries, &fields, GroupBy::Year)[1].start, "2025-01-01");
+
+        let tagged = [entry("2025-01-01", "[rust, work]"), entry("2025-01-02", "[Rust, rust]"), entry("2025-01-03", "work"), entry("2025-01-04", "")];
+        assert_eq!(
+            distinct_values(&tagged, "mood"),
+            vec![("rust".to_string(), 2), ("work".to_string(), 2), ("Rust".to_string(), 1)],
+        );
+    }
+
+    #[test]
+    fn test_calculate_stats() {
+        let result = |day: u32, mood: serde_json::Value| QueryResult {
+            date: format!("2025-07-{:02}", day),
+            file: None,
+            fields: HashMap::from([("mood".to_string(), Some(mood))]),
+        };
+        let results = [result(1, json!(2)), result(2, json!("4")), result(3, json!(6)), result(5, json!(10)), result(6, json!("n/a"))];
+        let stats = calculate_stats(&results, "mood").unwrap();
+
+        assert_eq!((stats.count, stats.min, stats.max, stats.avg), (4, 2.0, 10.0, 5.5));
+        assert_eq!((stats.median, stats.p25, stats.p75), (5.0, 3.5, 7.0));
+        assert!((stats.stddev - 35f64.sqrt() / 3f64.sqrt()).abs() < 1e-9);
+        assert!((stats.slope_per_day.unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(stats.skipped_count, Some(1));
+
+        assert_eq!(stats.fill_rate, 1.0);
+
+        let unset = QueryResult { date: "2025-07-03".to_string(), file: None, fields: HashMap::new() };
+        let gym = [result(1, json!(true)), result(2, json!(false)), unset, result(4, json!(true))];
+        let stats = calculate_stats(&gym, "mood").unwrap();
+        assert_eq!((stats.count, stats.avg, stats.fill_rate), (3, 2.0 / 3.0, 0.75));
+
+        let single = calculate_stats(&results[..1], "mood").unwrap();
+        assert_eq!((single.stddev, single.slope_per_day), (0.0, None));
+
+        let mut sorted = vec![result(1, json!(2)), result(2, json!("9")), result(3, json!(null)), result(4, json!(10))];
+        sort_results(&mut sorted, Some("mood"), true);
+        assert_eq!(sorted.iter().map(|r| r.date.as_str()).collect::<Vec<_>>(), vec!["2025-07-04", "2025-07-02", "2025-07-01", "2025-07-03"]);
+        sort_results(&mut sorted, None, false);
+        assert_eq!(sorted[0].date, "2025-07-01");
+
+        let fields = vec!["mood".to_string()];
+        let mut filled = fill_missing(results.into(), &fields, false, None, Some("2025-07-07".parse().unwrap()));
+        let dates: Vec<_> = filled.iter().map(|r| r.date.get(8..).unwrap()).collect();
+        assert_eq!(dates, vec!["01", "02", "03", "04", "05", "06", "07"]);
+        assert_eq!(filled[3].fields["mood"], None);
+
+        assert_eq!(add_rolling(&mut filled, &fields, 3), vec!["mood_rolling3"]);
+        let rolling: Vec<_> = filled.iter().map(|r| r.fields["mood_rolling3"].clone()).collect();
+        assert_eq!(rolling, vec![Some(json!(2.0)), Some(json!(3.0)), Some(json!(4.0)), Some(json!(5.0)), Some(json!(8.0)), Some(json!(10.0)), Some(json!(10.0))]);
+    }
+
+    #[test]
+    fn test_markdown_and_html_tables() {
+        let header = ["date", "mood", "note"].map(String::from);
+        let rows = vec![["2025-07-21", "7", "a|b"].map(String::from).to_vec(), ["2025-07-22", "", "<ok>"].map(String::from).to_vec()];
+        let footer = vec![["avg", "7.00", ""].map(String::from).to_vec()];
+
+        assert_eq!(
+            markdown_table(&header, &rows, &footer),
+            "| date       | mood | note |\n|------------|------|------|\n| 2025-07-21 | 7    | a\\|b |\n| 2025-07-22 |      | <ok> |\n| **avg**    | 7.00 |      |\n",
+        );
+        let html = html_table(&header, &rows, &footer);
+        assert!(html.contains("<tr><td>2025-07-22</td><td></td><td>&lt;ok&gt;</td></tr>"));
+        assert!(html.contains("<tfoot>\n    <tr><td>avg</td><td>7.00</td><td></td></tr>"));
+    }
+    #[test]
+    fn test_row_sink() {
+        let fields = vec!["note".to_string(), "mood".to_string()];
+        let result = QueryResult {
+            date: "2025-07-21".to_string(),
+            file: None,
+            fields: HashMap::from([("note".to_string(), Some(json!("tired, \"meh\""))), ("mood".to_string(), Some(json!(4)))]),
+        };
+        let written = |format: OutputFormat| {
+            let mut buffer = Vec::new();
+            let mut sink = RowSink::new(&mut buffer, &format, &row_header(&fields, false)).unwrap().unwrap();
+            sink.write(&result, &fields, false).unwrap();
+            sink.finish().unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+
+        assert_eq!(written(OutputFormat::Csv), "date,note,mood\n2025-07-21,\"tired, \"\"meh\"\"\",4\n");
+        assert_eq!(written(OutputFormat::Table), "date\tnote\tmood\n2025-07-21\ttired, \"meh\"\t4\n");
+        let line: serde_json::Value = serde_json::from_str(written(OutputFormat::Ndjson).trim_end()).unwrap();
+        assert_eq!(line, json!({ "date": "2025-07-21", "note": "tired, \"meh\"", "mood": 4 }));
+        assert!(RowSink::new(&mut Vec::new(), &OutputFormat::Markdown, &[]).unwrap().is_none());
+    }
+}
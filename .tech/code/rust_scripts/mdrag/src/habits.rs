@@ -0,0 +1,214 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use md_rag_core::frontmatter::{lookup, numeric_value, yaml_to_json_value};
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+use md_rag_core::schema::days_to_date;
+use md_rag_core::tasks::extract_tasks;
+
+use crate::chart;
+use crate::frontmatter::GroupBy;
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct HabitsArgs {
+    /// Habit to track: a frontmatter key (true/false, yes/no or a number, done when above 0) or
+    /// the text of a `- [x]` checkbox; repeatable
+    #[arg(long = "field", value_name = "NAME", required = true)]
+    fields: Vec<String>,
+
+    /// Only entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Also show the completion rate per week, month or year
+    #[arg(long, value_enum, value_name = "PERIOD")]
+    by: Option<GroupBy>,
+
+    /// Output format
+    #[arg(short, long, default_value = "table", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    /// A calendar per habit, full blocks on days it was done
+    Heatmap,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct HabitSummary {
+    habit: String,
+    /// Days with an entry
+    days: usize,
+    /// Days the habit was done
+    done: usize,
+    /// Done over days with an entry
+    rate: f64,
+    /// Consecutive days done up to today, or up to yesterday when today isn't done yet
+    current_streak: usize,
+    longest_streak: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    periods: Vec<PeriodRate>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct PeriodRate {
+    period: String,
+    start: NaiveDate,
+    days: usize,
+    done: usize,
+    rate: f64,
+}
+
+pub fn run(global: &GlobalArgs, args: HabitsArgs) -> Result<()> {
+    // Read straight from the journal, so this works without an index
+    let dates = global.config.entry_dates()?;
+    let mut days: Vec<BTreeMap<NaiveDate, bool>> = vec![BTreeMap::new(); args.fields.len()];
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        for doc in scan_journal_directory(dir, &paths, args.since.as_deref(), &dates, default_jobs(), false, &|_| {})? {
+            let date = days_to_date(doc.date);
+            for (habit, days) in args.fields.iter().zip(&mut days) {
+                let done = habit_done(&doc.frontmatter, &doc.content, habit).unwrap_or(false);
+                *days.entry(date).or_default() |= done;
+            }
+        }
+    }
+
+    let today = Local::now().date_naive();
+    if matches!(args.format, OutputFormat::Heatmap) {
+        for (habit, days) in args.fields.iter().zip(&days) {
+            let points: Vec<(NaiveDate, f64)> = days.iter().map(|(date, done)| (*date, if *done { 1.0 } else { 0.0 })).collect();
+            print!("{}", chart::heatmap_text(habit, &points));
+        }
+        return Ok(());
+    }
+
+    let summaries: Vec<HabitSummary> = args.fields.iter().zip(&days).map(|(habit, days)| summarize(habit, days, args.by, today)).collect();
+    match args.format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "habits": summaries }))?);
+        }
+        _ => {
+            let width = args.fields.iter().map(String::len).max().unwrap_or(0).max(5);
+            println!("{:<width$}  {:>5}  {:>5}  {:>5}  {:>7}  {:>7}", "habit", "done", "days", "rate", "streak", "longest");
+            for s in &summaries {
+                println!(
+                    "{:<width$}  {:>5}  {:>5}  {:>4.0}%  {:>7}  {:>7}",
+                    s.habit, s.done, s.days, s.rate * 100.0, s.current_streak, s.longest_streak,
+                );
+            }
+            for s in summaries.iter().filter(|s| !s.periods.is_empty()) {
+                println!("\n{}", s.habit);
+                for p in &s.periods {
+                    println!("  {:<10}  {:>3}/{:<3}  {:>4.0}%", p.period, p.done, p.days, p.rate * 100.0);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether an entry records `habit` as done: its frontmatter value if it has one, else whether a
+/// checkbox named after it (`- [x] Meditation`, `- [x] meditation: 10 min`) is checked. None
+/// when the entry doesn't mention it
+fn habit_done(frontmatter: &HashMap<String, serde_yaml::Value>, body: &str, habit: &str) -> Option<bool> {
+    let recorded = lookup(frontmatter, habit).map(yaml_to_json_value).and_then(|value| match value {
+        serde_json::Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "" => None,
+            "yes" | "y" | "true" | "done" => Some(true),
+            "no" | "n" | "false" => Some(false),
+            _ => numeric_value(&serde_json::Value::String(s)).map(|n| n > 0.0),
+        },
+        value => numeric_value(&value).map(|n| n > 0.0),
+    });
+    if recorded.is_some() {
+        return recorded;
+    }
+
+    let habit = habit.to_lowercase();
+    extract_tasks(body)
+        .into_iter()
+        .filter(|task| {
+            let text = task.text.to_lowercase();
+            text.strip_prefix(&habit).is_some_and(|rest| !rest.starts_with(char::is_alphanumeric))
+        })
+        .map(|task| task.done)
+        .reduce(|a, b| a || b)
+}
+
+fn summarize(habit: &str, days: &BTreeMap<NaiveDate, bool>, by: Option<GroupBy>, today: NaiveDate) -> HabitSummary {
+    let done_days: Vec<NaiveDate> = days.iter().filter(|(_, done)| **done).map(|(date, _)| *date).collect();
+    let (current_streak, longest_streak) = streaks(&done_days, today);
+
+    let mut periods: BTreeMap<NaiveDate, PeriodRate> = BTreeMap::new();
+    if let Some(by) = by {
+        for (date, done) in days {
+            let (period, start) = by.bucket(*date);
+            let rate = periods.entry(start).or_insert_with(|| PeriodRate { period, start, days: 0, done: 0, rate: 0.0 });
+            rate.days += 1;
+            rate.done += *done as usize;
+            rate.rate = rate.done as f64 / rate.days as f64;
+        }
+    }
+
+    HabitSummary {
+        habit: habit.to_string(),
+        days: days.len(),
+        done: done_days.len(),
+        rate: done_days.len() as f64 / days.len().max(1) as f64,
+        current_streak,
+        longest_streak,
+        periods: periods.into_values().collect(),
+    }
+}
+
+/// The run of consecutive days ending today (or yesterday) and the longest run, from sorted days
+pub(crate) fn streaks(done_days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let (mut longest, mut run) = (0, 0);
+    let mut previous: Option<NaiveDate> = None;
+    for &date in done_days {
+        run = if previous.is_some_and(|p| date - p == Duration::days(1)) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(date);
+    }
+    let current = match previous {
+        Some(last) if last == today || last == today - Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use md_rag_core::frontmatter::extract_frontmatter;
+
+    #[test]
+    fn test_habits() {
+        let frontmatter = extract_frontmatter("---\nmeditation: true\nexercise: no\nsteps: 8000\n---\n").unwrap();
+        let body = "## Habits\n- [x] Reading: 20 pages\n- [ ] Journaling\n- [x] Exercise\n- [x] Readings list\n";
+        assert_eq!(habit_done(&frontmatter, body, "meditation"), Some(true));
+        assert_eq!(habit_done(&frontmatter, body, "exercise"), Some(false));
+        assert_eq!(habit_done(&frontmatter, body, "steps"), Some(true));
+        assert_eq!(habit_done(&frontmatter, body, "reading"), Some(true));
+        assert_eq!(habit_done(&frontmatter, body, "journaling"), Some(false));
+        assert_eq!(habit_done(&frontmatter, body, "stretching"), None);
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+        let days: BTreeMap<NaiveDate, bool> =
+            [(1, true), (2, true), (3, true), (4, false), (6, true), (7, true), (8, false), (9, true)].map(|(d, done)| (day(d), done)).into();
+        assert_eq!(streaks(&[day(1), day(2), day(3), day(6), day(7), day(9)], day(10)), (1, 3));
+        assert_eq!(streaks(&[day(6), day(7)], day(9)), (0, 2));
+
+        let summary = summarize("gym", &days, Some(GroupBy::Week), day(9));
+        assert_eq!((summary.days, summary.done, summary.current_streak, summary.longest_streak), (8, 6, 1, 3));
+        let weeks: Vec<_> = summary.periods.iter().map(|p| (p.period.as_str(), p.done, p.days)).collect();
+        assert_eq!(weeks, vec![("2025-W27", 4, 5), ("2025-W28", 2, 3)]);
+    }
+}
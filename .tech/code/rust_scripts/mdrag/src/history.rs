@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use md_rag_core::history::{self, most_frequent};
+
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct HistoryArgs {
+    /// Searches (or queries, with --top) to show
+    #[arg(short, long, default_value_t = 20)]
+    num_results: usize,
+
+    /// Most frequent queries with how often they were searched, instead of the latest searches
+    #[arg(long)]
+    top: bool,
+
+    /// Delete the history
+    #[arg(long, conflicts_with = "top")]
+    clear: bool,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Show or clear the log of past `mdrag search` queries
+pub fn run(global: &GlobalArgs, args: HistoryArgs) -> Result<()> {
+    let path = history::default_path()
+        .context("No home or config directory to keep the search history in; set MDRAG_CONFIG_DIR")?;
+
+    if args.clear {
+        if history::clear(&path)? {
+            eprintln!("🗑️  Cleared search history {}", path.display());
+        } else {
+            eprintln!("No search history at {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let entries = history::read(&path)?;
+    if entries.is_empty() && global.config.search.history == Some(false) {
+        eprintln!("Search history is off (`history = false` under [search] in .mdrag.toml)");
+    }
+
+    if args.top {
+        let counts: Vec<_> = most_frequent(&entries).into_iter().take(args.num_results).collect();
+        match args.format {
+            OutputFormat::Text => {
+                for count in &counts {
+                    println!("{:>5}  {}  (last {})", count.count, count.query, count.last.with_timezone(&Local).format("%Y-%m-%d"));
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "queries": counts }))?),
+        }
+        return Ok(());
+    }
+
+    // Latest first
+    let recent: Vec<_> = entries.iter().rev().take(args.num_results).collect();
+    match args.format {
+        OutputFormat::Text => {
+            for entry in &recent {
+                let collection = if entry.collection == global.table { String::new() } else { format!("  [{}]", entry.collection) };
+                println!(
+                    "{}  {:>3} results  {}{}",
+                    entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                    entry.results,
+                    entry.query,
+                    collection,
+                );
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "searches": recent }))?),
+    }
+    Ok(())
+}
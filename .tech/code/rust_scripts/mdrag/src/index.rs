@@ -0,0 +1,1327 @@
+use anyhow::{Context, Result};
+use arrow::array::{Array, ArrayRef, Int32Array, Int64Array, StringArray};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use arrow::record_batch::{RecordBatchIterator, RecordBatchReader};
+use futures::{StreamExt, TryStreamExt};
+use lancedb::index::scalar::{BTreeIndexBuilder, FtsIndexBuilder, LabelListIndexBuilder};
+use lancedb::index::vector::{IvfHnswSqIndexBuilder, IvfPqIndexBuilder};
+use lancedb::database::CreateTableMode;
+use lancedb::index::{Index, IndexType};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::{Connection, Table};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::fs;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use md_rag_core::cache::{cache_key, EmbeddingCache};
+use md_rag_core::crypto::ContentCipher;
+use md_rag_core::embeddings::{estimate_tokens, Device, EmbeddingGenerator, TokenCounter, DEFAULT_MODEL};
+use md_rag_core::hash::content_hash;
+use md_rag_core::lock::IndexLock;
+use md_rag_core::metadata::{infer_fields, validate_field_name, MetadataField};
+use md_rag_core::metric::{normalize, Metric};
+use md_rag_core::path_date::PathDatePattern;
+use md_rag_core::path_filter::PathFilter;
+use md_rag_core::scan::{default_jobs, scan_journal_directory, ScanDocument, SkipReason};
+use md_rag_core::schema::{
+    days_to_date, db_path, documents_schema, embedding_array, rebuild_table_name, retired_table_name,
+    schema_encrypted, schema_metadata_fields, schema_metric, schema_model, schema_skips_code_blocks, sql_string, table_dir,
+    tags_array,
+};
+use md_rag_core::chunk::{Chunk, ChunkOptions, DEFAULT_CHUNK_OVERLAP, DEFAULT_CHUNK_TOKENS};
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::GlobalArgs;
+use crate::progress::{rate, Progress};
+use crate::watch::JournalWatcher;
+
+/// Status line for people: stdout, or stderr under `--json` so stdout holds only the report
+macro_rules! status {
+    ($json:expr, $($arg:tt)*) => {
+        if $json { eprintln!($($arg)*) } else { println!($($arg)*) }
+    };
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct IndexArgs {
+    /// Re-embed every entry instead of only new or modified ones
+    #[arg(short, long)]
+    rebuild: bool,
+
+    /// Only index files modified since this date (YYYY-MM-DD)
+    #[arg(short, long)]
+    since: Option<String>,
+
+    /// Verbose output
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Date entries without a frontmatter date from their path: a strftime format matched
+    /// against the end of the path (e.g. %Y/%m/%d) or a regex with year, month and day groups.
+    /// [default: YYYY/MM/DD or YYYY-MM-DD anywhere in the path]
+    #[arg(long, value_name = "PATTERN")]
+    path_date: Option<PathDatePattern>,
+
+    /// Skip files matching this .gitignore-style pattern (e.g. drafts/ or *-private.md); repeatable.
+    /// Applied on top of the journal's .ragignore
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Only index files matching this glob (e.g. "2025/**"); repeatable
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Leave fenced code blocks out of the embeddings (`--skip-code-blocks false` keeps them). It
+    /// changes entry hashes, so it is recorded with the index, which `verify` and later passes
+    /// follow; defaults to the index's setting (or `[template.scrub] code_blocks`), and changing
+    /// it requires --rebuild
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    skip_code_blocks: Option<bool>,
+
+    /// Threads reading, cleaning and chunking entries while earlier batches are embedded
+    /// [default: one per CPU]
+    #[arg(long)]
+    jobs: Option<NonZeroUsize>,
+
+    /// Run the local embedding model on cpu, cuda or coreml; GPU providers need a build with the
+    /// matching cargo feature
+    #[arg(long, env = "MDRAG_DEVICE", default_value = "cpu")]
+    device: Device,
+
+    /// ONNX Runtime threads for the local embedding model, kept on that many CPUs (Linux only)
+    /// [default: one per CPU]
+    #[arg(long, value_name = "N")]
+    threads: Option<NonZeroUsize>,
+
+    /// Scan, clean and chunk, then list the entries that would be added, updated or removed,
+    /// without loading the model or writing to the index. Chunk counts estimate 4 characters per token
+    #[arg(long, conflicts_with = "watch")]
+    dry_run: bool,
+
+    /// If another run is writing to the index, wait for it to finish instead of failing
+    #[arg(long)]
+    wait: bool,
+
+    /// Print a JSON report of the pass on stdout (files scanned and skipped, chunks, embeddings,
+    /// table rows, seconds per phase); status goes to stderr
+    #[arg(long, conflicts_with = "verbose")]
+    json: bool,
+
+    /// Keep running and re-index entries as they are saved
+    #[arg(short, long)]
+    watch: bool,
+
+    /// Embedding model (fastembed model code, or the API model name with --backend openai);
+    /// defaults to the model the index was built with.
+    /// Switching models requires --rebuild.
+    #[arg(short, long)]
+    model: Option<String>,
+
+    /// Distance metric searches use: cosine, l2 or dot. Cosine and dot store unit-length vectors.
+    /// Defaults to the index's metric, or cosine for a new index. Switching requires --rebuild.
+    #[arg(long)]
+    metric: Option<Metric>,
+
+    /// Build an approximate nearest neighbor index on the embeddings after indexing
+    /// (ivf-pq or hnsw). Worth it from a few thousand chunks; rebuild it now and then as entries are added
+    #[arg(long, value_name = "KIND", num_args = 0..=1, default_missing_value = "ivf-pq", value_enum)]
+    build_ann: Option<AnnIndex>,
+
+    /// Maximum tokens per chunk, counted with the model's tokenizer [default: 350].
+    /// Changing chunking requires --rebuild
+    #[arg(long)]
+    chunk_tokens: Option<usize>,
+
+    /// Tokens repeated from the end of one chunk at the start of the next [default: 50]
+    #[arg(long)]
+    chunk_overlap: Option<usize>,
+
+    /// Frontmatter keys to store as columns for `mdrag search --where`, e.g. mood,project,anxiety.
+    /// Defaults to the fields the index already has. Changing them requires --rebuild
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    metadata_fields: Option<Vec<String>>,
+
+    /// Store chunk text encrypted with AES-256-GCM, keyed by $MDRAG_CONTENT_KEY or the OS keyring;
+    /// searches decrypt it with the same key. Hybrid search needs plain text. `--encrypt-content
+    /// false` turns it off. Defaults to the index's setting; changing it requires --rebuild
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    encrypt_content: Option<bool>,
+}
+
+/// Kind of vector index built by `--build-ann`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnnIndex {
+    /// Inverted file with product quantization: small and fast, slightly lossy
+    IvfPq,
+    /// Inverted file with HNSW graphs over scalar-quantized vectors: higher recall, more memory
+    Hnsw,
+}
+
+/// IVF training needs at least this many vectors; smaller tables are searched exhaustively anyway
+const MIN_ANN_ROWS: usize = 256;
+
+pub async fn run(global: &GlobalArgs, mut args: IndexArgs) -> Result<()> {
+    apply_config(global, &mut args)?;
+    
+    status!(args.json, "🔍 RAG Indexer");
+    status!(args.json, "📁 Scanning: {}", display_dirs(&global.journal_dirs));
+    status!(args.json, "🗂️  Collection: {}", global.table);
+    status!(args.json, "💾 Index location: {}", global.lance_dir.display());
+    
+    // Loaded on first use so an up-to-date index costs nothing, then kept warm while watching
+    let mut embedding_generator = None;
+    let report = index_journal(global, &args, &mut embedding_generator).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+    
+    if args.watch {
+        // Only the first pass honours --rebuild and --build-ann; later passes are incremental
+        let args = IndexArgs { rebuild: false, build_ann: None, ..args };
+        let mut watcher = JournalWatcher::new(&global.journal_dirs)?;
+        status!(args.json, "\n👀 Watching {} for changes (Ctrl+C to stop)", display_dirs(&global.journal_dirs));
+        
+        while let Some(changed) = watcher.next_change().await {
+            status!(args.json, "\n📝 {} file(s) changed", changed.len());
+            if args.verbose {
+                for path in &changed {
+                    status!(args.json, "  {}", path.display());
+                }
+            }
+            
+            // A failed pass (e.g. a half-written file) shouldn't end the watch
+            match index_journal(global, &args, &mut embedding_generator).await {
+                Ok(report) if args.json => println!("{}", serde_json::to_string_pretty(&report)?),
+                Ok(_) => {}
+                Err(e) => eprintln!("  ⚠️  Re-index failed: {}", e),
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// An incremental pass for callers whose stdout isn't free (the MCP server, `verify --fix
+/// --format json`): status goes to stderr and no report is printed. Returns how many entries
+/// were embedded. A caller that keeps a model loaded passes it in to be reused, or replaced when
+/// the index needs another one
+pub async fn update(global: &GlobalArgs, embedding_generator: &mut Option<EmbeddingGenerator>) -> Result<usize> {
+    let mut args = IndexArgs { json: true, ..IndexArgs::default() };
+    apply_config(global, &mut args)?;
+    let report = index_journal(global, &args, embedding_generator).await?;
+    Ok(report.documents_indexed)
+}
+
+/// Whether the live table was indexed with fenced code left out; None when there is no table yet
+pub async fn indexed_skip_code_blocks(global: &GlobalArgs) -> Result<Option<bool>> {
+    let lance_path = db_path(&global.lance_dir);
+    if !lance_path.exists() {
+        return Ok(None);
+    }
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    if !db.table_names().execute().await?.contains(&global.table) {
+        return Ok(None);
+    }
+    let schema = db.open_table(&global.table).execute().await?.schema().await?;
+    Ok(Some(schema_skips_code_blocks(&schema)))
+}
+
+/// The configured template filter, with fenced code kept or left out as the index records, so
+/// every reader hashes entries alike
+pub fn hashing_filter(global: &GlobalArgs, skip_code_blocks: bool) -> TemplateFilter {
+    let mut template = global.config.template.clone();
+    template.scrub.code_blocks = skip_code_blocks;
+    TemplateFilter::with_config(&template)
+}
+
+/// Settings in `.mdrag.toml` count as if they had been passed on the command line
+fn apply_config(global: &GlobalArgs, args: &mut IndexArgs) -> Result<()> {
+    args.model = args.model.take().or_else(|| global.config.model.clone());
+    args.chunk_tokens = args.chunk_tokens.or(global.config.chunk_tokens);
+    args.chunk_overlap = args.chunk_overlap.or(global.config.chunk_overlap);
+    args.metadata_fields = args.metadata_fields.take().or_else(|| global.config.metadata_fields.clone());
+    args.encrypt_content = args.encrypt_content.or(global.config.encrypt_content);
+    if global.config.template.scrub.code_blocks {
+        args.skip_code_blocks.get_or_insert(true);
+    }
+    
+    for name in args.metadata_fields.iter().flatten() {
+        validate_field_name(name)?;
+    }
+    
+    let chunking = chunk_options(args);
+    if chunking.max_tokens == 0 || chunking.overlap_tokens >= chunking.max_tokens {
+        anyhow::bail!("--chunk-overlap must be smaller than --chunk-tokens, which must be positive");
+    }
+    Ok(())
+}
+
+/// Bring the index in line with the journal directory
+async fn index_journal(
+    global: &GlobalArgs,
+    args: &IndexArgs,
+    embedding_generator: &mut Option<EmbeddingGenerator>,
+) -> Result<IndexReport> {
+    let started = Instant::now();
+    let mut report = IndexReport::default();
+    
+    // Scan documents
+    let mut dates = global.config.entry_dates()?;
+    if let Some(pattern) = &args.path_date {
+        dates.path = pattern.clone();
+    }
+    // Rebuilt every pass so edits to .ragignore apply while watching
+    let jobs = args.jobs.map_or_else(default_jobs, NonZeroUsize::get);
+    let progress = Progress::new(args.json);
+    let scanning = progress.phase("Scanning", "files", None);
+    let (unreadable, before_since) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let on_file = |skipped: Option<SkipReason>| {
+        scanning.inc(1);
+        match skipped {
+            Some(SkipReason::Unreadable) => unreadable.fetch_add(1, Ordering::Relaxed),
+            Some(SkipReason::BeforeSince) => before_since.fetch_add(1, Ordering::Relaxed),
+            None => 0,
+        };
+    };
+    let mut documents = Vec::new();
+    let mut filters = Vec::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &args.exclude, &args.include)?;
+        documents.extend(scan_journal_directory(dir, &paths, args.since.as_deref(), &dates, jobs, args.verbose, &on_file)?);
+        filters.push(paths);
+    }
+    report.seconds.scanning = scanning.finish();
+    report.skipped.unreadable = unreadable.into_inner();
+    report.skipped.before_since = before_since.into_inner();
+    report.files_scanned = documents.len() + report.skipped.unreadable + report.skipped.before_since;
+    documents.sort_by_key(|doc| doc.date);
+    status!(args.json, "\n📊 Found {} documents to index", documents.len());
+    
+    if documents.is_empty() {
+        status!(args.json, "No documents to index!");
+        report.seconds.total = started.elapsed().as_secs_f64();
+        return Ok(report);
+    }
+    
+    // Entries are hashed with the code block setting the index records unless it is being changed
+    let indexed_skip_code_blocks = indexed_skip_code_blocks(global).await?;
+    let skip_code_blocks = args.skip_code_blocks.or(indexed_skip_code_blocks).unwrap_or(false);
+    
+    // Create template filter, shared with the chunking threads
+    let filter = Arc::new(hashing_filter(global, skip_code_blocks));
+    
+    let cleaning = progress.phase("Cleaning", "documents", Some(documents.len()));
+    let mut pending: Vec<(&ScanDocument, String)> = documents
+        .iter()
+        .map(|doc| {
+            let hash = entry_hash(&filter, doc);
+            cleaning.inc(1);
+            (doc, hash)
+        })
+        .collect();
+    report.seconds.cleaning = cleaning.finish();
+    
+    if args.dry_run {
+        let changes = plan_changes(global, args, &pending, &filters, jobs, &filter).await?;
+        let planned = changes.new.iter().chain(&changes.updated);
+        report.chunks = planned.clone().map(|entry| entry.chunks).sum();
+        report.skipped.unchanged = pending.len() - planned.count();
+        report.documents_removed = changes.deleted.len();
+        report.changes = Some(changes);
+        report.seconds.total = started.elapsed().as_secs_f64();
+        return Ok(report);
+    }
+    
+    // Create or open LanceDB connection
+    let lance_path = db_path(&global.lance_dir);
+    fs::create_dir_all(&global.lance_dir)?;
+    let _lock = lock_index(&global.lance_dir, args.wait, args.json).await?;
+    
+    let db = lancedb::connect(lance_path.to_str().unwrap())
+        .execute()
+        .await?;
+    status!(args.json, "📂 Connected to LanceDB at: {}", lance_path.display());
+    recover_interrupted_swap(&db, &lance_path, &global.table, args.json).await?;
+    
+    // Check if table exists
+    let tables = db.table_names().execute().await?;
+    let mut existing = None;
+    // On --rebuild, the table whose vectors are copied forward for chunk text that hasn't changed
+    let mut reusable = None;
+    // A rebuild that can't upsert in place is written beside the live table and swapped in at the end
+    let mut build_table = global.table.clone();
+    let mut model_id = args.model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let mut metric = args.metric.unwrap_or_default();
+    let mut field_names = args.metadata_fields.clone().unwrap_or_default();
+    let mut metadata_fields = None;
+    let mut encrypted = args.encrypt_content.unwrap_or(false);
+    
+    if tables.contains(&global.table) {
+        let table = db.open_table(&global.table).execute().await?;
+        
+        // Tables from before models were configurable were always embedded with the default
+        let schema = table.schema().await?;
+        let indexed_model = schema_model(&schema).unwrap_or(DEFAULT_MODEL).to_string();
+        let model_changed = args.model.as_deref().is_some_and(|m| !m.eq_ignore_ascii_case(&indexed_model));
+        if model_changed && !args.rebuild {
+            anyhow::bail!(
+                "Index was built with {}. Run `mdrag index --rebuild --model {}` to re-embed it with the new model.",
+                indexed_model,
+                model_id,
+            );
+        }
+        
+        // ...and searched with L2 before the metric was configurable
+        let indexed_metric = schema_metric(&schema).unwrap_or(Metric::L2);
+        let metric_changed = args.metric.is_some_and(|m| m != indexed_metric);
+        if metric_changed && !args.rebuild {
+            anyhow::bail!(
+                "Index was built for the {} metric. Run `mdrag index --rebuild --metric {}` to switch.",
+                indexed_metric,
+                metric,
+            );
+        }
+        
+        let indexed_fields = schema_metadata_fields(&schema);
+        let fields_changed = args.metadata_fields.as_ref().is_some_and(|names| {
+            !indexed_fields.iter().map(|f| &f.name).eq(names.iter())
+        });
+        if fields_changed && !args.rebuild {
+            anyhow::bail!(
+                "Index stores the frontmatter fields [{}]. Run `mdrag index --rebuild --metadata-fields ...` to change them.",
+                indexed_fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>().join(", "),
+            );
+        }
+        if args.metadata_fields.is_none() {
+            field_names = indexed_fields.iter().map(|f| f.name.clone()).collect();
+        }
+        
+        let indexed_encrypted = schema_encrypted(&schema);
+        let encryption_changed = args.encrypt_content.is_some_and(|e| e != indexed_encrypted);
+        if encryption_changed && !args.rebuild {
+            anyhow::bail!(
+                "Index stores chunk text {}. Run `mdrag index --rebuild --encrypt-content {}` to change it.",
+                if indexed_encrypted { "encrypted" } else { "in plain text" },
+                encrypted,
+            );
+        }
+        let code_blocks_changed = args.skip_code_blocks.is_some_and(|s| s != schema_skips_code_blocks(&schema));
+        if code_blocks_changed && !args.rebuild {
+            anyhow::bail!(
+                "Index was cleaned {} fenced code blocks. Run `mdrag index --rebuild --skip-code-blocks {}` to change it.",
+                if schema_skips_code_blocks(&schema) { "without" } else { "with" },
+                skip_code_blocks,
+            );
+        }
+        if args.rebuild && !model_changed {
+            reusable = Some(table.clone());
+        }
+        
+        if !is_current_format(&schema) {
+            if !args.rebuild {
+                anyhow::bail!("Index was built with an older format. Run `mdrag index --rebuild` once to upgrade it.");
+            }
+            status!(args.json, "🏗️  Rebuilding a table from an older index format; the old one serves searches until it is done...");
+            build_table = rebuild_table_name(&global.table);
+        } else if model_changed || metric_changed || fields_changed || encryption_changed || code_blocks_changed {
+            status!(
+                args.json,
+                "🏗️  Rebuilding the table built with {} for {}; the old one serves searches until it is done...",
+                indexed_model,
+                indexed_metric,
+            );
+            build_table = rebuild_table_name(&global.table);
+        } else {
+            model_id = indexed_model;
+            metric = indexed_metric;
+            encrypted = indexed_encrypted;
+            // Keep the existing column types so upserts match the table
+            metadata_fields = Some(indexed_fields);
+            let indexed = indexed_hashes(&table).await?;
+            
+            // Drop chunks of entries that were deleted, renamed or excluded since the last run
+            let scanned: HashSet<&str> = documents.iter().map(|doc| doc.path.as_str()).collect();
+            // Entries of a directory that is no longer passed count as excluded
+            let orphans = orphaned_paths(&indexed, &scanned, |path| !filters.iter().any(|f| f.is_included(path)));
+            if !orphans.is_empty() {
+                if args.verbose {
+                    for path in &orphans {
+                        status!(args.json, "  🗑️  Removing {} (no longer on disk or excluded)", path);
+                    }
+                }
+                table.delete(&path_filter(orphans.iter().map(String::as_str))).await?;
+                status!(args.json, "  Removed {} deleted, renamed or excluded documents from the index", orphans.len());
+                report.documents_removed = orphans.len();
+            }
+            
+            if args.rebuild {
+                status!(args.json, "  Re-embedding all {} documents", pending.len());
+            } else {
+                let total = pending.len();
+                pending.retain(|(doc, hash)| indexed.get(&doc.path) != Some(hash));
+                report.skipped.unchanged = total - pending.len();
+                status!(args.json, "  {} unchanged, {} new or modified", total - pending.len(), pending.len());
+                
+                if pending.is_empty() {
+                    if let Some(kind) = args.build_ann {
+                        let indexing = Instant::now();
+                        build_ann_index(&table, kind, metric, args.json).await?;
+                        report.seconds.indexing = indexing.elapsed().as_secs_f64();
+                    }
+                    status!(args.json, "\n✨ Index is up to date!");
+                    report.table_rows = Some(table.count_rows(None).await?);
+                    report.seconds.total = started.elapsed().as_secs_f64();
+                    return Ok(report);
+                }
+            }
+            existing = Some(table);
+        }
+    }
+    
+    // Fails before anything is embedded when there is no key
+    let cipher = encrypted.then(ContentCipher::load).transpose()?;
+    if cipher.is_some() {
+        status!(args.json, "🔐 Chunk text is stored encrypted");
+    }
+    
+    // Create embedding generator
+    if embedding_generator.as_ref().is_none_or(|g| g.model_id() != model_id) {
+        *embedding_generator = Some(EmbeddingGenerator::load_on(global.backend, &model_id, args.device, args.threads)?);
+    }
+    let embedding_generator = embedding_generator.as_ref().unwrap();
+    let embedding_dim = embedding_generator.embedding_dimension();
+    
+    // A new table types its metadata columns from every scanned entry
+    let metadata_fields: Vec<MetadataField> = match metadata_fields {
+        Some(fields) => fields,
+        None => {
+            let frontmatter: Vec<_> = documents.iter().map(|doc| &doc.frontmatter).collect();
+            infer_fields(&field_names, &frontmatter)?
+        }
+    };
+    
+    // Create schema for our documents with chunk support
+    let schema = documents_schema(
+        embedding_generator.model_id(),
+        embedding_dim,
+        embedding_generator.is_normalized(),
+        metric,
+        &metadata_fields,
+        encrypted,
+        skip_code_blocks,
+    );
+    
+    status!(args.json, "\n🧽 Cleaning template noise and chunking documents...");
+    status!(args.json, "🤖 Generating real embeddings with {}...", embedding_generator.model_id());
+    
+    // Reuse vectors for chunk text that was embedded before, even by an earlier rebuild
+    let cache = EmbeddingCache::open(&db, embedding_dim).await?;
+    if let Some(source) = reusable {
+        // Chunking, metric or field changes leave most chunk text as it was
+        report.embeddings_reused = cache.seed_from(&source, embedding_generator.model_id()).await?;
+        if report.embeddings_reused > 0 {
+            status!(args.json, "♻️  Carried {} existing embeddings forward for unchanged chunk text", report.embeddings_reused);
+        }
+    }
+    
+    // Chunks are embedded and written a batch of entries at a time, so memory stays flat however large the journal
+    let upsert = existing.is_some();
+    let mut table = existing;
+    let mut rows = ChunkRows::default();
+    let mut written = WriteStats::default();
+    
+    // Entries are cleaned and chunked on other threads while the batch before them is embedded
+    let contents = pending.iter().map(|(doc, _)| doc.content.clone()).collect();
+    let mut chunked = spawn_chunking(contents, filter.clone(), chunk_options(args), embedding_generator.token_counter(), jobs);
+    let mut done = 0;
+    let embedding = progress.phase("Embedding", "documents", Some(pending.len()));
+    let writing = progress.phase("Writing", "documents", Some(pending.len()));
+    let (mut embed_time, mut write_time) = (Duration::ZERO, Duration::ZERO);
+    
+    while let Some((i, chunks)) = chunked.recv().await {
+        let (doc, hash) = &pending[i];
+        rows.push_document(doc, hash, chunks);
+        done += 1;
+        
+        // An entry's chunks always land in the same batch, so each upsert can replace whole entries
+        if rows.len() < WRITE_BATCH_ROWS && done < pending.len() {
+            continue;
+        }
+        let batch_rows = std::mem::take(&mut rows);
+        let paths = batch_rows.documents.clone();
+        
+        let started = Instant::now();
+        let embeddings = embed_rows(&batch_rows, embedding_generator, &cache, metric, &mut written).await?;
+        embed_time += started.elapsed();
+        embedding.batch(paths.len(), format!(
+            "{} chunks, {:.0} chunks/s",
+            written.embedded + written.cached,
+            rate(written.embedded, embed_time.as_secs_f64()),
+        ));
+        
+        let batch = batch_rows.into_batch(&schema, &metadata_fields, embeddings, embedding_dim, cipher.as_ref())?;
+        written.chunks += batch.num_rows();
+        
+        let writing_started = Instant::now();
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema.clone());
+        match &table {
+            Some(table) if upsert => {
+                // Upsert in place so the table and any vector indexes on it survive.
+                // Chunks past the new end of a shortened entry match nothing in the
+                // new data and are deleted.
+                let mut merge = table.merge_insert(&["path", "chunk_index"]);
+                merge
+                    .when_matched_update_all(None)
+                    .when_not_matched_insert_all()
+                    .when_not_matched_by_source_delete(Some(path_filter(paths.iter().copied())));
+                merge.execute(Box::new(batches)).await?;
+            }
+            Some(table) => {
+                table.add(batches).execute().await?;
+            }
+            None => {
+                table = Some(db.create_table(&build_table, batches).execute().await?);
+            }
+        }
+        
+        write_time += writing_started.elapsed();
+        writing.batch(paths.len(), format!("{} chunks", written.chunks));
+    }
+    embedding.finish();
+    writing.finish();
+    if done < pending.len() {
+        anyhow::bail!("Chunking stopped after {} of {} documents", done, pending.len());
+    }
+    
+    status!(
+        args.json,
+        "✅ Embedded {} new chunks of dimension {} ({} served from the cache)",
+        written.embedded,
+        embedding_dim,
+        written.cached,
+    );
+    
+    // There is always at least one pending entry here, so at least one batch was written
+    let mut table = table.ok_or_else(|| anyhow::anyhow!("No batch was written"))?;
+    let count = table.count_rows(None).await?;
+    report.documents_indexed = pending.len();
+    report.chunks = written.chunks;
+    report.embeddings_generated = written.embedded;
+    report.embeddings_cached = written.cached;
+    report.table_rows = Some(count);
+    report.seconds.embedding = embed_time.as_secs_f64();
+    report.seconds.writing = write_time.as_secs_f64();
+    if upsert {
+        status!(args.json, "✅ Upserted {} documents, table now holds {} chunks", pending.len(), count);
+    } else {
+        status!(args.json, "✅ Created table with {} chunks from {} documents", count, pending.len());
+    }
+    
+    let indexing = Instant::now();
+    if build_table != global.table {
+        // A finished rebuild becomes the next version of the live table, indexed as part of the swap
+        table = swap_in_rebuild(&db, &global.table, args.build_ann, args.json).await?;
+        status!(args.json, "🔁 Swapped the rebuilt table in");
+    } else {
+        // Rebuilt on every run so searches see the new chunks
+        build_scalar_indexes(&table, args.json).await?;
+        
+        if let Some(kind) = args.build_ann {
+            build_ann_index(&table, kind, metric, args.json).await?;
+        }
+    }
+    report.seconds.indexing = indexing.elapsed().as_secs_f64();
+    
+    status!(args.json, "🧽 Removed template boilerplate from all entries");
+    status!(args.json, "\n✨ Indexing complete!");
+    
+    report.seconds.total = started.elapsed().as_secs_f64();
+    Ok(report)
+}
+
+/// What an index pass would do, read from the existing table without changing it
+async fn plan_changes(
+    global: &GlobalArgs,
+    args: &IndexArgs,
+    pending: &[(&ScanDocument, String)],
+    filters: &[PathFilter],
+    jobs: usize,
+    filter: &Arc<TemplateFilter>,
+) -> Result<PlannedChanges> {
+    // Without an index directory everything is new; connecting would create it
+    let lance_path = db_path(&global.lance_dir);
+    let mut indexed = HashMap::new();
+    if lance_path.exists() {
+        let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+        if db.table_names().execute().await?.contains(&global.table) {
+            indexed = indexed_hashes(&db.open_table(&global.table).execute().await?).await?;
+        }
+    }
+    
+    let scanned: HashSet<&str> = pending.iter().map(|(doc, _)| doc.path.as_str()).collect();
+    let deleted = orphaned_paths(&indexed, &scanned, |path| !filters.iter().any(|f| f.is_included(path)));
+    let changed: Vec<&(&ScanDocument, String)> = pending
+        .iter()
+        .filter(|(doc, hash)| args.rebuild || indexed.get(&doc.path) != Some(hash))
+        .collect();
+    
+    let contents = changed.iter().map(|(doc, _)| doc.content.clone()).collect();
+    let mut chunked = spawn_chunking(contents, filter.clone(), chunk_options(args), Arc::new(estimate_tokens), jobs);
+    let mut chunk_counts = vec![0; changed.len()];
+    while let Some((i, chunks)) = chunked.recv().await {
+        chunk_counts[i] = chunks.len();
+    }
+    
+    let mut changes = PlannedChanges { deleted, ..Default::default() };
+    for ((doc, _), chunks) in changed.into_iter().zip(chunk_counts) {
+        let entry = PlannedEntry { path: doc.path.clone(), chunks };
+        if indexed.contains_key(&doc.path) {
+            changes.updated.push(entry);
+        } else {
+            changes.new.push(entry);
+        }
+    }
+    
+    for (marker, entries) in [("+", &changes.new), ("~", &changes.updated)] {
+        for entry in entries {
+            status!(args.json, "  {} {} ({} chunks)", marker, entry.path, entry.chunks);
+        }
+    }
+    for path in &changes.deleted {
+        status!(args.json, "  - {}", path);
+    }
+    status!(
+        args.json,
+        "\n🧪 Dry run: {} new, {} updated, {} deleted. Nothing was written.",
+        changes.new.len(),
+        changes.updated.len(),
+        changes.deleted.len(),
+    );
+    
+    Ok(changes)
+}
+
+/// Take the index lock for the rest of the pass; with `wait`, poll until the other writer is done
+pub async fn lock_index(lance_dir: &Path, wait: bool, json: bool) -> Result<IndexLock> {
+    let mut waiting = false;
+    loop {
+        match IndexLock::try_acquire(lance_dir)? {
+            Ok(lock) => return Ok(lock),
+            Err(holder) if wait => {
+                if !waiting {
+                    status!(json, "⏳ Waiting for another mdrag run ({}) to finish...", holder);
+                    waiting = true;
+                }
+                tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+            }
+            Err(holder) => anyhow::bail!(
+                "Another mdrag run ({}) is writing to this index. Pass --wait to queue behind it.",
+                holder,
+            ),
+        }
+    }
+}
+
+/// How often `--wait` checks whether the index lock was released
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Replace the rows of the live table with its finished rebuild, index them and drop the rebuild.
+///
+/// The rebuild is written over the live table as a new version in one LanceDB commit, so
+/// readers see the old rows or the new ones, never a missing or partly written table, and the
+/// versions before it stay available to `mdrag rollback`. The rows are streamed from a scan of
+/// the rebuild, so memory stays flat however large the journal. An overwrite leaves the new
+/// version without indexes, so the full-text, date and tag indexes are built right after it,
+/// along with the vector index: `ann`, or else the kind the live table had.
+pub async fn swap_in_rebuild(db: &Connection, table: &str, ann: Option<AnnIndex>, json: bool) -> Result<Table> {
+    let rebuild = db.open_table(rebuild_table_name(table)).execute().await?;
+    let schema = rebuild.schema().await?;
+    let ann = match ann {
+        Some(kind) => Some(kind),
+        None => ann_index_kind(&db.open_table(table).execute().await?).await?,
+    };
+    
+    let rows = RebuildRows::scan(&rebuild, schema.clone()).await?;
+    let live = db
+        .create_table(table, rows)
+        .mode(CreateTableMode::Overwrite)
+        .execute()
+        .await
+        .with_context(|| format!("Failed to write the rebuilt table over '{}'", table))?;
+    
+    // An empty rebuild has nothing to index
+    if live.count_rows(None).await? > 0 {
+        build_scalar_indexes(&live, json).await?;
+        if let Some(kind) = ann {
+            let metric = schema_metric(&schema).unwrap_or(Metric::L2);
+            build_ann_index(&live, kind, metric, json).await?;
+        }
+    }
+    db.drop_table(&rebuild_table_name(table)).await?;
+    Ok(live)
+}
+
+/// The kind of vector index on `embedding`, if `--build-ann` built one
+async fn ann_index_kind(table: &Table) -> Result<Option<AnnIndex>> {
+    let indices = table.list_indices().await?;
+    Ok(indices
+        .into_iter()
+        .filter(|index| index.columns.iter().any(|column| column == "embedding"))
+        .find_map(|index| match index.index_type {
+            IndexType::IvfPq => Some(AnnIndex::IvfPq),
+            IndexType::IvfHnswSq => Some(AnnIndex::Hnsw),
+            _ => None,
+        }))
+}
+
+/// Batches of a scan of the rebuild table, as the `RecordBatchReader` an overwrite is written from.
+///
+/// A task drives the scan and hands over a couple of batches at a time, so only those are ever
+/// held in memory. Query results don't carry the schema metadata the model and metric are
+/// recorded in, so every batch is given the rebuild's schema.
+struct RebuildRows {
+    schema: SchemaRef,
+    batches: mpsc::Receiver<Result<RecordBatch, ArrowError>>,
+}
+
+impl RebuildRows {
+    async fn scan(rebuild: &Table, schema: SchemaRef) -> Result<Self> {
+        let mut stream = rebuild.query().execute().await?;
+        let (tx, batches) = mpsc::channel(2);
+        let batch_schema = schema.clone();
+        tokio::spawn(async move {
+            while let Some(batch) = stream.next().await {
+                let batch = batch
+                    .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+                    .and_then(|batch| RecordBatch::try_new(batch_schema.clone(), batch.columns().to_vec()));
+                if tx.send(batch).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { schema, batches })
+    }
+}
+
+impl Iterator for RebuildRows {
+    type Item = Result<RecordBatch, ArrowError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        // Waits for the scan task, which runs on another worker
+        futures::executor::block_on(self.batches.recv())
+    }
+}
+
+impl RecordBatchReader for RebuildRows {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Clean up after a run that stopped during a rebuild: drop the unfinished rebuild, or finish a
+/// swap by directory renames that an older version of mdrag had started
+pub async fn recover_interrupted_swap(db: &Connection, lance_path: &Path, table: &str, json: bool) -> Result<()> {
+    let tables = db.table_names().execute().await?;
+    let (rebuild, retired) = (rebuild_table_name(table), retired_table_name(table));
+    
+    // Older versions only moved the live table aside once its rebuild was complete
+    if !tables.iter().any(|name| name == table) && tables.contains(&retired) && tables.contains(&rebuild) {
+        fs::rename(table_dir(lance_path, &rebuild), table_dir(lance_path, table))?;
+        status!(json, "🔁 Finished swapping in a rebuild that was interrupted");
+    } else if tables.contains(&rebuild) {
+        db.drop_table(&rebuild).await?;
+        status!(json, "🗑️  Dropped an unfinished rebuild from an interrupted run");
+    }
+    if tables.contains(&retired) {
+        db.drop_table(&retired).await?;
+    }
+    Ok(())
+}
+
+/// Summary of one index pass, printed by `--json`
+#[derive(Debug, Default, Serialize)]
+struct IndexReport {
+    /// Markdown files that passed the path filters
+    files_scanned: usize,
+    skipped: SkippedFiles,
+    /// Entries dropped from the index because they were deleted, renamed or excluded
+    documents_removed: usize,
+    /// New or modified entries chunked, embedded and written
+    documents_indexed: usize,
+    chunks: usize,
+    /// Chunks run through the model; the rest came from the embedding cache
+    embeddings_generated: usize,
+    embeddings_cached: usize,
+    /// Vectors copied from the table being rebuilt into the cache, so unchanged chunk text isn't embedded again
+    embeddings_reused: usize,
+    /// Chunks in the table after the pass
+    table_rows: Option<usize>,
+    seconds: PhaseTimes,
+    /// What `--dry-run` found would change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<PlannedChanges>,
+}
+
+/// Entries a real run would write or remove
+#[derive(Debug, Default, Serialize)]
+struct PlannedChanges {
+    new: Vec<PlannedEntry>,
+    updated: Vec<PlannedEntry>,
+    deleted: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedEntry {
+    path: String,
+    /// Estimated, since the model's tokenizer isn't loaded
+    chunks: usize,
+}
+
+/// Scanned files that weren't indexed, by reason
+#[derive(Debug, Default, Serialize)]
+struct SkippedFiles {
+    unreadable: usize,
+    before_since: usize,
+    /// Already indexed with the same content
+    unchanged: usize,
+}
+
+/// Wall-clock seconds per phase; chunking overlaps embedding, so it has no entry of its own
+#[derive(Debug, Default, Serialize)]
+struct PhaseTimes {
+    scanning: f64,
+    cleaning: f64,
+    embedding: f64,
+    writing: f64,
+    /// Full-text, date, tag and ANN indexes
+    indexing: f64,
+    total: f64,
+}
+
+/// Rows written per batch; all chunks of an entry go in the same batch, so a batch can run a little over
+const WRITE_BATCH_ROWS: usize = 1000;
+
+/// Chunks per embedding call, to avoid timeouts
+const EMBED_BATCH_SIZE: usize = 100;
+
+/// Chunked entries waiting to be embedded; bounds how far chunking runs ahead
+const CHUNK_QUEUE_SIZE: usize = 256;
+
+/// Running totals across the batches of one index pass
+#[derive(Default)]
+struct WriteStats {
+    chunks: usize,
+    embedded: usize,
+    cached: usize,
+}
+
+/// Column values for one batch of chunks, in schema order
+#[derive(Default)]
+struct ChunkRows<'a> {
+    /// Every entry in the batch, including those that produced no chunks
+    documents: Vec<&'a str>,
+    texts: Vec<String>,
+    embedding_inputs: Vec<String>,
+    sections: Vec<String>,
+    paths: Vec<&'a str>,
+    dates: Vec<i32>,
+    chunk_indices: Vec<i32>,
+    total_chunks: Vec<i32>,
+    hashes: Vec<&'a str>,
+    mtimes: Vec<i64>,
+    start_lines: Vec<Option<i32>>,
+    end_lines: Vec<Option<i32>>,
+    tags: Vec<&'a [String]>,
+    frontmatter: Vec<&'a HashMap<String, serde_yaml::Value>>,
+}
+
+impl<'a> ChunkRows<'a> {
+    fn push_document(&mut self, doc: &'a ScanDocument, hash: &'a str, chunks: Vec<Chunk>) {
+        self.documents.push(&doc.path);
+        let num_chunks = chunks.len() as i32;
+        // Chunk lines count from the start of the body, after any frontmatter
+        let offset = doc.first_line - 1;
+        
+        for (idx, chunk) in chunks.into_iter().enumerate() {
+            self.embedding_inputs.push(chunk.embedding_text(days_to_date(doc.date)));
+            self.texts.push(chunk.text);
+            self.sections.push(chunk.section);
+            self.paths.push(&doc.path);
+            self.dates.push(doc.date);
+            self.chunk_indices.push(idx as i32);
+            self.total_chunks.push(num_chunks);
+            self.hashes.push(hash);
+            self.mtimes.push(doc.mtime);
+            self.start_lines.push(chunk.lines.map(|(start, _)| (offset + start) as i32));
+            self.end_lines.push(chunk.lines.map(|(_, end)| (offset + end) as i32));
+            self.tags.push(&doc.tags);
+            self.frontmatter.push(&doc.frontmatter);
+        }
+    }
+    
+    fn len(&self) -> usize {
+        self.texts.len()
+    }
+    
+    fn into_batch(
+        self,
+        schema: &Arc<Schema>,
+        metadata_fields: &[MetadataField],
+        embeddings: Vec<Vec<f32>>,
+        embedding_dim: usize,
+        cipher: Option<&ContentCipher>,
+    ) -> Result<RecordBatch> {
+        // The vectors are already made from the plain text
+        let texts = match cipher {
+            Some(cipher) => self.texts
+                .iter()
+                .zip(self.paths.iter().zip(&self.chunk_indices))
+                .map(|(text, (path, &chunk_index))| cipher.encrypt(text, path, chunk_index))
+                .collect::<Result<Vec<_>>>()?,
+            None => self.texts,
+        };
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.paths)),
+            Arc::new(Int32Array::from(self.dates)),
+            Arc::new(StringArray::from(texts)),
+            Arc::new(StringArray::from(self.sections)),
+            Arc::new(Int32Array::from(self.chunk_indices)),
+            Arc::new(Int32Array::from(self.total_chunks)),
+            Arc::new(StringArray::from(self.hashes)),
+            Arc::new(Int64Array::from(self.mtimes)),
+            Arc::new(Int32Array::from(self.start_lines)),
+            Arc::new(Int32Array::from(self.end_lines)),
+            Arc::new(tags_array(&self.tags)),
+            Arc::new(embedding_array(embeddings, embedding_dim)),
+        ];
+        columns.extend(metadata_fields.iter().map(|field| field.column(&self.frontmatter)));
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+}
+
+/// Clean and chunk `contents` on `jobs` blocking threads, yielding each entry's position and
+/// chunks as soon as it is ready, so not in order
+fn spawn_chunking(
+    contents: Vec<Arc<str>>,
+    filter: Arc<TemplateFilter>,
+    options: ChunkOptions,
+    count_tokens: TokenCounter,
+    jobs: usize,
+) -> mpsc::Receiver<(usize, Vec<Chunk>)> {
+    let contents = Arc::new(contents);
+    let next = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel(CHUNK_QUEUE_SIZE);
+    
+    for _ in 0..jobs {
+        let (contents, next, sender) = (contents.clone(), next.clone(), sender.clone());
+        let (filter, count_tokens) = (filter.clone(), count_tokens.clone());
+        tokio::task::spawn_blocking(move || {
+            loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(content) = contents.get(i) else { break };
+                let chunks = filter.extract_chunks(content, options, &*count_tokens);
+                // The receiver is gone if writing a batch failed
+                if sender.blocking_send((i, chunks)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    
+    receiver
+}
+
+/// Vectors for every row of `rows`, from the cache where possible; new ones are added to it
+async fn embed_rows(
+    rows: &ChunkRows<'_>,
+    embedding_generator: &EmbeddingGenerator,
+    cache: &EmbeddingCache,
+    metric: Metric,
+    stats: &mut WriteStats,
+) -> Result<Vec<Vec<f32>>> {
+    let keys: Vec<String> = rows.embedding_inputs
+        .iter()
+        .map(|input| cache_key(embedding_generator.model_id(), input))
+        .collect();
+    let mut cached = cache.get(&keys).await?;
+    let missing: Vec<usize> = (0..keys.len())
+        .filter(|&i| !cached.contains_key(&keys[i]))
+        .collect();
+    stats.cached += keys.len() - missing.len();
+    stats.embedded += missing.len();
+    
+    let mut new_entries = Vec::new();
+    for index_batch in missing.chunks(EMBED_BATCH_SIZE) {
+        let texts = index_batch.iter().map(|&c| rows.embedding_inputs[c].clone()).collect();
+        let batch_embeddings = embedding_generator.generate_embeddings(texts)?;
+        new_entries.extend(index_batch.iter().map(|&c| keys[c].clone()).zip(batch_embeddings));
+    }
+    
+    cache.insert(new_entries.clone()).await?;
+    cached.extend(new_entries);
+    let mut embeddings: Vec<Vec<f32>> = keys
+        .iter()
+        .map(|key| cached.get(key).cloned().ok_or_else(|| anyhow::anyhow!("No embedding for chunk")))
+        .collect::<Result<_>>()?;
+    // The cache keeps raw model output so it serves every metric
+    if metric.normalizes() {
+        embeddings.iter_mut().for_each(|v| normalize(v));
+    }
+    Ok(embeddings)
+}
+
+fn display_dirs(dirs: &[PathBuf]) -> String {
+    dirs.iter().map(|dir| dir.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Replace the full-text, date and tag indexes with ones covering every row
+pub async fn build_scalar_indexes(table: &Table, json: bool) -> Result<()> {
+    // Serves `mdrag search --hybrid`; ciphertext has no words to index
+    if !schema_encrypted(&table.schema().await?) {
+        table
+            .create_index(&["content"], Index::FTS(FtsIndexBuilder::default()))
+            .replace(true)
+            .execute()
+            .await?;
+        status!(json, "🔤 Rebuilt full-text index");
+    }
+    
+    // Lets --after/--before narrow the candidates without scanning every row
+    table
+        .create_index(&["date"], Index::BTree(BTreeIndexBuilder::default()))
+        .replace(true)
+        .execute()
+        .await?;
+    status!(json, "📅 Rebuilt date index");
+    
+    // Serves `--tag` filters
+    table
+        .create_index(&["tags"], Index::LabelList(LabelListIndexBuilder::default()))
+        .replace(true)
+        .execute()
+        .await?;
+    status!(json, "🏷️  Rebuilt tag index");
+    
+    Ok(())
+}
+
+/// Replace the vector index on `embedding`, built for the metric searches will use
+async fn build_ann_index(table: &Table, kind: AnnIndex, metric: Metric, json: bool) -> Result<()> {
+    let rows = table.count_rows(None).await?;
+    if rows < MIN_ANN_ROWS {
+        status!(json, "⏭️  Skipping ANN index: {} chunks is below the {} needed to train it", rows, MIN_ANN_ROWS);
+        return Ok(());
+    }
+    
+    status!(json, "🧭 Building {:?} index over {} chunks ({})...", kind, rows, metric);
+    let index = match kind {
+        AnnIndex::IvfPq => Index::IvfPq(IvfPqIndexBuilder::default().distance_type(metric.distance_type())),
+        AnnIndex::Hnsw => Index::IvfHnswSq(IvfHnswSqIndexBuilder::default().distance_type(metric.distance_type())),
+    };
+    table
+        .create_index(&["embedding"], index)
+        .replace(true)
+        .execute()
+        .await?;
+    status!(json, "✅ ANN index ready");
+    
+    Ok(())
+}
+
+fn chunk_options(args: &IndexArgs) -> ChunkOptions {
+    ChunkOptions {
+        max_tokens: args.chunk_tokens.unwrap_or(DEFAULT_CHUNK_TOKENS),
+        overlap_tokens: args.chunk_overlap.unwrap_or(DEFAULT_CHUNK_OVERLAP),
+    }
+}
+
+/// Filter expression matching every chunk of the given entries
+fn path_filter<'a>(paths: impl Iterator<Item = &'a str>) -> String {
+    let quoted: Vec<String> = paths.map(sql_string).collect();
+    format!("path IN ({})", quoted.join(", "))
+}
+
+/// Indexed entries that were not found by this scan and either no longer exist on disk or are now excluded.
+///
+/// The existence check keeps entries that were only skipped by `--since`.
+pub fn orphaned_paths(
+    indexed: &HashMap<String, String>,
+    scanned: &HashSet<&str>,
+    is_excluded: impl Fn(&Path) -> bool,
+) -> Vec<String> {
+    let mut orphans: Vec<String> = indexed
+        .keys()
+        .filter(|path| !scanned.contains(path.as_str()))
+        .filter(|path| !Path::new(path).exists() || is_excluded(Path::new(path)))
+        .cloned()
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+/// Hash of everything an entry's rows are built from: its frontmatter and cleaned body.
+///
+/// Entries that only differ in template noise count as unchanged, while frontmatter edits
+/// (tags, metadata fields) are picked up even though they aren't embedded.
+pub fn entry_hash(filter: &TemplateFilter, doc: &ScanDocument) -> String {
+    // Sorted so the hash doesn't depend on HashMap order
+    let frontmatter: BTreeMap<_, _> = doc.frontmatter.iter().collect();
+    let frontmatter = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    content_hash(&format!("{}\n{}", frontmatter, filter.clean_content(&doc.content)))
+}
+
+/// Whether a chunk table has every column this version writes; older ones need a rebuild
+pub fn is_current_format(schema: &Schema) -> bool {
+    ["content_hash", "section", "start_line", "tags"].iter().all(|column| schema.field_with_name(column).is_ok())
+}
+
+/// Content hash of every entry already in the index, keyed by path
+pub async fn indexed_hashes(table: &Table) -> Result<HashMap<String, String>> {
+    // Every chunk of an entry carries the same hash, so the first chunk is enough
+    let batches: Vec<RecordBatch> = table
+        .query()
+        .select(Select::columns(&["path", "content_hash"]))
+        .only_if("chunk_index = 0")
+        .execute()
+        .await?
+        .try_collect()
+        .await?;
+    
+    let mut hashes = HashMap::new();
+    for batch in batches {
+        let paths = batch.column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow::anyhow!("Missing path column"))?;
+        let content_hashes = batch.column_by_name("content_hash")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow::anyhow!("Missing content_hash column"))?;
+        
+        for i in 0..batch.num_rows() {
+            hashes.insert(paths.value(i).to_string(), content_hashes.value(i).to_string());
+        }
+    }
+    
+    Ok(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, NaiveDate};
+    use lancedb::index::scalar::FullTextSearchQuery;
+
+    #[test]
+    fn test_date_parsing() {
+        let date = NaiveDate::parse_from_str("2025-07-21", "%Y-%m-%d").unwrap();
+        assert_eq!(date.year(), 2025);
+        assert_eq!(date.month(), 7);
+        assert_eq!(date.day(), 21);
+    }
+
+    #[test]
+    fn test_orphaned_paths() {
+        let indexed: HashMap<String, String> = [
+            ("journal/kept.md", "a"),
+            ("journal/renamed-away.md", "b"),
+            ("Cargo.toml", "c"), // exists on disk but outside this scan
+            ("src/main.rs", "d"), // exists on disk but now excluded
+        ]
+        .into_iter()
+        .map(|(p, h)| (p.to_string(), h.to_string()))
+        .collect();
+        let scanned: HashSet<&str> = ["journal/kept.md"].into_iter().collect();
+
+        let excluded = |path: &Path| path.starts_with("src");
+        assert_eq!(orphaned_paths(&indexed, &scanned, excluded), vec!["journal/renamed-away.md", "src/main.rs"]);
+        assert_eq!(path_filter(["a.md", "it's.md"].into_iter()), "path IN ('a.md', 'it''s.md')");
+    }
+
+    #[test]
+    fn test_index_report_json() {
+        let report = IndexReport { files_scanned: 3, table_rows: Some(12), ..Default::default() };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["files_scanned"], 3);
+        assert_eq!(json["skipped"]["before_since"], 0);
+        assert_eq!(json["table_rows"], 12);
+        assert!(json["seconds"]["embedding"].is_number());
+    }
+
+    #[test]
+    fn test_chunk_rows_build_a_batch() {
+        let doc = |path: &str| ScanDocument {
+            path: path.to_string(),
+            date: 0,
+            content: "".into(),
+            first_line: 3,
+            mtime: 0,
+            frontmatter: HashMap::new(),
+            tags: vec!["work".to_string()],
+        };
+        let (full, empty) = (doc("a.md"), doc("b.md"));
+        let chunk = |text: &str| Chunk { section: String::new(), text: text.to_string(), lines: Some((1, 2)) };
+
+        let mut rows = ChunkRows::default();
+        rows.push_document(&full, "hash", vec![chunk("one"), chunk("two")]);
+        rows.push_document(&empty, "hash", Vec::new());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows.documents, vec!["a.md", "b.md"]);
+        assert_eq!(rows.start_lines, vec![Some(3), Some(3)]);
+
+        let schema = documents_schema("m", 2, true, Metric::Cosine, &[], true, false);
+        let cipher = ContentCipher::from_base64(&md_rag_core::crypto::generate_key()).unwrap();
+        let batch = rows.into_batch(&schema, &[], vec![vec![1.0, 0.0]; 2], 2, Some(&cipher)).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let contents = batch.column_by_name("content").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_ne!(contents.value(0), "one");
+        assert_eq!(cipher.decrypt(contents.value(0), "a.md", 0).unwrap(), "one");
+        assert!(cipher.decrypt(contents.value(0), "a.md", 1).is_err());
+    }
+    #[tokio::test]
+    async fn test_rebuild_swap_is_ready_for_hybrid_search() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = lancedb::connect(tmp.path().to_str().unwrap()).execute().await.unwrap();
+        let schema = documents_schema("m", 2, true, Metric::Cosine, &[], false, false);
+        let table = |text: &str| {
+            let doc = ScanDocument {
+                path: "a.md".to_string(),
+                date: 0,
+                content: "".into(),
+                first_line: 1,
+                mtime: 0,
+                frontmatter: HashMap::new(),
+                tags: Vec::new(),
+            };
+            let mut rows = ChunkRows::default();
+            rows.push_document(&doc, "hash", vec![Chunk { section: String::new(), text: text.to_string(), lines: None }]);
+            let batch = rows.into_batch(&schema, &[], vec![vec![1.0, 0.0]], 2, None).unwrap();
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone())
+        };
+        db.create_table("documents", table("the old entry")).execute().await.unwrap();
+        db.create_table(rebuild_table_name("documents"), table("a lighthouse keeper")).execute().await.unwrap();
+
+        let live = swap_in_rebuild(&db, "documents", None, true).await.unwrap();
+        assert_eq!(db.table_names().execute().await.unwrap(), vec!["documents"]);
+
+        // Both halves of `--hybrid` answer straight after the swap
+        let vector: Vec<RecordBatch> = live.vector_search(vec![1.0, 0.0]).unwrap().execute().await.unwrap().try_collect().await.unwrap();
+        assert_eq!(vector.iter().map(|batch| batch.num_rows()).sum::<usize>(), 1);
+        let text: Vec<RecordBatch> = live
+            .query()
+            .full_text_search(FullTextSearchQuery::new("lighthouse".to_string()))
+            .execute()
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(text.iter().map(|batch| batch.num_rows()).sum::<usize>(), 1);
+    }
+}
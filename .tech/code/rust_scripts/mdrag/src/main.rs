@@ -0,0 +1,202 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use md_rag_core::config::{self, Config};
+use md_rag_core::embeddings::Backend;
+use md_rag_core::schema::DEFAULT_TABLE_NAME;
+
+mod ask;
+mod chart;
+mod content_key;
+mod daemon;
+mod frontmatter;
+mod habits;
+mod history;
+mod index;
+mod mcp;
+mod new;
+mod progress;
+mod search;
+mod snapshots;
+mod status;
+mod tags;
+mod tasks;
+mod template;
+mod transfer;
+mod tui;
+mod verify;
+mod watch;
+mod web;
+mod writing;
+
+#[derive(Parser, Debug)]
+#[command(name = "mdrag", author, version, about = "Semantic search and metadata analysis for a markdown journal", long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalOpts,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Journal directory when neither the command line nor `.mdrag.toml` names one
+const DEFAULT_JOURNAL_DIR: &str = "journal";
+
+/// LanceDB directory when neither the command line nor `.mdrag.toml` names one
+const DEFAULT_LANCE_DIR: &str = ".tech/data/lancedb";
+
+/// Options shared by every subcommand, as given on the command line or in the environment
+#[derive(clap::Args, Debug)]
+struct GlobalOpts {
+    /// Journal directory; repeat to index several directories into one collection [default: journal]
+    #[arg(short, long, global = true, env = "MDRAG_JOURNAL_DIR")]
+    journal_dir: Vec<PathBuf>,
+
+    /// LanceDB directory [default: .tech/data/lancedb]
+    #[arg(short, long, global = true, env = "MDRAG_LANCE_DIR")]
+    lance_dir: Option<PathBuf>,
+
+    /// Collection (LanceDB table) holding the indexed chunks, e.g. journal, notes or meetings [default: documents]
+    #[arg(long, visible_alias = "collection", global = true, env = "MDRAG_TABLE")]
+    table: Option<String>,
+
+    /// Embedding backend: local (fastembed) or openai (OpenAI-compatible API at $MDRAG_EMBED_URL) [default: local]
+    #[arg(long, global = true, env = "MDRAG_BACKEND")]
+    backend: Option<Backend>,
+
+    /// Config file [default: the nearest .mdrag.toml in this or a parent directory]
+    #[arg(long, global = true, env = "MDRAG_CONFIG")]
+    config: Option<PathBuf>,
+}
+
+/// Global options with `.mdrag.toml` and the built-in defaults filled in
+#[derive(Debug, Clone)]
+pub struct GlobalArgs {
+    /// Never empty
+    pub journal_dirs: Vec<PathBuf>,
+    pub lance_dir: PathBuf,
+    pub table: String,
+    pub backend: Backend,
+    /// The rest of `.mdrag.toml`, for subcommand defaults
+    pub config: Config,
+}
+
+impl GlobalOpts {
+    fn resolve(self) -> Result<GlobalArgs> {
+        let config_path = match self.config {
+            Some(path) => Some(path),
+            None => config::discover(&std::env::current_dir()?),
+        };
+        let config = config_path.as_deref().map(Config::load).transpose()?.unwrap_or_default();
+
+        let backend = match self.backend {
+            Some(backend) => backend,
+            None => config.backend.as_deref()
+                .map(str::parse::<Backend>)
+                .transpose()
+                .map_err(anyhow::Error::msg)?
+                .unwrap_or_default(),
+        };
+
+        let journal_dirs = if !self.journal_dir.is_empty() {
+            self.journal_dir
+        } else {
+            vec![config.journal_dir.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_JOURNAL_DIR))]
+        };
+
+        Ok(GlobalArgs {
+            journal_dirs,
+            lance_dir: self.lance_dir
+                .or_else(|| config.lance_dir.clone())
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_LANCE_DIR)),
+            table: self.table
+                .or_else(|| config.table.clone())
+                .unwrap_or_else(|| DEFAULT_TABLE_NAME.to_string()),
+            backend,
+            config,
+        })
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Index journal files for RAG search
+    Index(index::IndexArgs),
+    /// Search indexed journal files
+    Search(search::SearchArgs),
+    /// Start a journal entry from the template for today or another day
+    New(new::NewArgs),
+    /// Show past searches, or the most frequent queries
+    History(history::HistoryArgs),
+    /// Browse search results in the terminal with a live query, filters and an entry preview
+    Tui(tui::TuiArgs),
+    /// Serve a search page for the browser on localhost
+    Web(web::WebArgs),
+    /// Answer a question from the journal with a chat model, citing the entries it used
+    Ask(ask::AskArgs),
+    /// Query and analyze frontmatter data from journal files, or set values in one entry
+    Frontmatter(frontmatter::FrontmatterArgs),
+    /// Show what a collection holds: entries, chunks, dates, model, size and indexes
+    Status(status::StatusArgs),
+    /// Inspect the saved versions of a collection
+    Snapshots(snapshots::SnapshotsArgs),
+    /// Restore a collection to an earlier version from `mdrag snapshots list`
+    Rollback(snapshots::RollbackArgs),
+    /// Delete old versions of a collection to reclaim disk space
+    Vacuum(snapshots::VacuumArgs),
+    /// Write a collection, embeddings included, to a Parquet file
+    Export(transfer::ExportArgs),
+    /// Load a collection from `mdrag export` without re-embedding it
+    Import(transfer::ImportArgs),
+    /// Check the index against the journal files and optionally repair it
+    Verify(verify::VerifyArgs),
+    /// Inspect tags (frontmatter `tags:` and #hashtags) across the journal
+    Tags(tags::TagsArgs),
+    /// List open `- [ ]` tasks across the journal, completion rates, or tasks to carry forward
+    Tasks(tasks::TasksArgs),
+    /// Streaks and completion rates of habits kept in frontmatter or as checkboxes
+    Habits(habits::HabitsArgs),
+    /// Words written per day, writing streaks, entry length by weekday and the most written sections
+    WritingStats(writing::WritingStatsArgs),
+    /// Learn template boilerplate from the lines most entries share
+    Template(template::TemplateArgs),
+    /// Make a key for `--encrypt-content`, printed or saved in the OS keyring
+    ContentKey(content_key::ContentKeyArgs),
+    /// Run the MCP server over stdio, or as a long-lived HTTP+SSE server with `--transport sse`
+    Serve(mcp::ServeArgs),
+    /// Keep the embedding model loaded and answer searches over a Unix socket
+    Daemon,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let global = cli.global.resolve()?;
+
+    match cli.command {
+        Command::Index(args) => index::run(&global, args).await,
+        Command::Search(args) => search::run(&global, args).await,
+        Command::New(args) => new::run(&global, args).await,
+        Command::History(args) => history::run(&global, args),
+        Command::Tui(args) => tui::run(&global, args).await,
+        Command::Web(args) => web::serve(&global, args).await,
+        Command::Ask(args) => ask::run(&global, args).await,
+        Command::Frontmatter(args) => frontmatter::run(&global, args).await,
+        Command::Status(args) => status::run(&global, args).await,
+        Command::Verify(args) => verify::run(&global, args).await,
+        Command::Export(args) => transfer::export(&global, args).await,
+        Command::Import(args) => transfer::import(&global, args).await,
+        Command::Snapshots(args) => snapshots::list(&global, args).await,
+        Command::Rollback(args) => snapshots::rollback(&global, args).await,
+        Command::Vacuum(args) => snapshots::vacuum(&global, args).await,
+        Command::Tags(args) => tags::run(&global, args),
+        Command::Tasks(args) => tasks::run(&global, args),
+        Command::Habits(args) => habits::run(&global, args),
+        Command::WritingStats(args) => writing::run(&global, args),
+        Command::Template(args) => template::run(&global, args),
+        Command::ContentKey(args) => content_key::run(args),
+        Command::Serve(args) => mcp::serve(&global, args).await,
+        Command::Daemon => daemon::serve(&global).await,
+    }
+}
@@ -0,0 +1,747 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use md_rag_core::embeddings::{Backend, EmbeddingGenerator};
+use md_rag_core::rerank::Reranker;
+use md_rag_core::schema::db_path;
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::search::{
+    cursor_offset, index_exists, load_search_model, page_cursor, search_index_page, SearchPage, SearchQuery, SortOrder,
+    KEYWORD_FALLBACK_SCORE, MISSING_INDEX_HINT,
+};
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::frontmatter::{locate_entry, set_entry_fields, summary_table};
+use crate::{index, new, GlobalArgs};
+
+mod prompts;
+mod resources;
+mod sse;
+use prompts::Prompt;
+use resources::JournalResources;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const DEFAULT_NUM_RESULTS: usize = 10;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// How clients connect: stdio (the client spawns one server per session) or sse (one
+    /// long-lived HTTP server shared by every session, keeping the model loaded)
+    #[arg(long, default_value = "stdio", value_enum)]
+    transport: Transport,
+
+    /// Address the SSE server listens on; anything but loopback needs --token
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Bearer token SSE clients must send (`Authorization: Bearer TOKEN`)
+    #[arg(long, env = "MDRAG_MCP_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Port the SSE server listens on
+    #[arg(short, long, default_value_t = 8766)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Transport {
+    Stdio,
+    Sse,
+}
+
+/// Server state shared across requests, and across sessions over SSE
+struct Server {
+    lance_path: String,
+    table: String,
+    backend: Backend,
+    /// For prompts, new entries and index passes
+    global: GlobalArgs,
+    resources: JournalResources,
+    /// Loaded on first search and kept warm for the rest of the session
+    embedding_generator: Option<EmbeddingGenerator>,
+    /// Loaded on the first search that asks for reranking
+    reranker: Option<Reranker>,
+}
+
+/// Serve MCP requests over the chosen transport
+pub async fn serve(global: &GlobalArgs, args: ServeArgs) -> Result<()> {
+    if let Transport::Sse = args.transport {
+        sse::check_exposure(&args.host, args.token.as_deref())?;
+    }
+    let lance_path = db_path(&global.lance_dir);
+    eprintln!("🔌 RAG MCP server using index at: {} (table {})", lance_path.display(), global.table);
+
+    let mut server = Server {
+        lance_path: lance_path.to_string_lossy().to_string(),
+        table: global.table.clone(),
+        backend: global.backend,
+        global: global.clone(),
+        resources: JournalResources::new(
+            global.journal_dirs
+                .iter()
+                .map(|dir| Ok((dir.clone(), global.config.path_filter(dir, &[], &[])?)))
+                .collect::<Result<_>>()?,
+            TemplateFilter::with_config(&global.config.template),
+        ),
+        embedding_generator: None,
+        reranker: None,
+    };
+
+    match args.transport {
+        Transport::Stdio => serve_stdio(&mut server).await,
+        Transport::Sse => {
+            // Sessions come and go; the first one shouldn't wait for the model
+            if index_exists(&server.lance_path, &server.table).await? {
+                server.load_model().await?;
+            }
+            sse::serve(server, &args.host, args.port, args.token).await
+        }
+    }
+}
+
+/// One session on stdin/stdout, until the client disconnects
+async fn serve_stdio(server: &mut Server) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(mut out) = server.handle_message(&line).await {
+            out.push('\n');
+            stdout.write_all(out.as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Server {
+    /// Answer one JSON-RPC message as the transport received it; notifications get no response
+    async fn handle_message(&mut self, message: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(message) {
+            Ok(request) => self.handle_request(&request).await?,
+            Err(e) => error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e)),
+        };
+        // A `Value` always serializes
+        serde_json::to_string(&response).ok()
+    }
+
+    /// Load the index's model unless it already is, to keep for every later search
+    async fn load_model(&mut self) -> Result<()> {
+        if self.embedding_generator.is_none() {
+            self.embedding_generator = Some(load_search_model(&self.lance_path, &self.table, self.backend, None, false).await?);
+        }
+        Ok(())
+    }
+
+    async fn handle_request(&mut self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(json!({}));
+
+        // Requests without an id are notifications
+        let id = match id {
+            Some(id) => id,
+            None => return None,
+        };
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "serverInfo": {
+                    "name": "mdrag",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "capabilities": {
+                    "tools": { "listChanged": false },
+                    "resources": { "listChanged": false, "subscribe": false },
+                    "prompts": { "listChanged": false },
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": [rag_search_tool(), create_entry_tool(), append_to_entry_tool(), set_frontmatter_tool()] })),
+            "tools/call" => self.call_tool(&params).await,
+            "resources/list" => Ok(json!({ "resources": self.resources.list() })),
+            "resources/templates/list" => Ok(json!({ "resourceTemplates": self.resources.templates() })),
+            "resources/read" => {
+                let uri = params.get("uri").and_then(|u| u.as_str()).unwrap_or("");
+                self.resources.read(uri).map_err(|e| (INVALID_PARAMS, e.to_string()))
+            }
+            "prompts/list" => Ok(json!({ "prompts": prompts::list() })),
+            "prompts/get" => self.get_prompt(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn call_tool(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        match name {
+            "rag_search" => {
+                let request = parse_search_arguments(&arguments).map_err(|e| (INVALID_PARAMS, e))?;
+                let sort = parse_sort(&arguments).map_err(|e| (INVALID_PARAMS, e))?;
+
+                // Tool failures are reported in the result so the client can show them
+                Ok(match self.run_search(request, sort).await {
+                    Ok(text) => tool_result(text, false),
+                    Err(e) => tool_result(format!("Search failed: {:#}", e), true),
+                })
+            }
+            "create_entry" => {
+                let (date, fields, index) = parse_entry_arguments(&arguments).map_err(|e| (INVALID_PARAMS, e))?;
+                Ok(self.create_entry(date.as_deref(), &fields, index).await)
+            }
+            "append_to_entry" => {
+                let (date, heading, text, index) = parse_append_arguments(&arguments).map_err(|e| (INVALID_PARAMS, e))?;
+                Ok(self.append_to_entry(date.as_deref(), &heading, &text, index).await)
+            }
+            "set_frontmatter" => {
+                let (date, fields, index) = parse_entry_arguments(&arguments).map_err(|e| (INVALID_PARAMS, e))?;
+                if fields.is_empty() {
+                    return Err((INVALID_PARAMS, "'fields' must name at least one value".to_string()));
+                }
+                Ok(self.set_frontmatter(date.as_deref(), &fields, index).await)
+            }
+            _ => Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        }
+    }
+
+    /// Write a new entry from the template and optionally index it; failures are tool errors
+    async fn create_entry(&mut self, date: Option<&str>, fields: &[(String, String)], index: bool) -> Value {
+        let created = new::entry_date(date).and_then(|date| new::create_entry(&self.global, date, fields, None));
+        let path = match created {
+            Ok(path) => path,
+            Err(e) => return tool_result(format!("Could not create the entry: {:#}", e), true),
+        };
+        self.written(format!("Created {}", path.display()), &path, index).await
+    }
+
+    /// Add text under a heading of a day's entry, creating it from the template when missing
+    async fn append_to_entry(&mut self, date: Option<&str>, heading: &str, text: &str, index: bool) -> Value {
+        let appended = new::entry_date(date).and_then(|date| new::append_to_entry(&self.global, date, heading, text));
+        let (path, created) = match appended {
+            Ok(appended) => appended,
+            Err(e) => return tool_result(format!("Could not update the entry: {:#}", e), true),
+        };
+        let verb = if created { "Created" } else { "Updated" };
+        self.written(format!("{} {} under '{}'", verb, path.display(), heading.trim_start_matches('#').trim()), &path, index).await
+    }
+
+    /// Set frontmatter values in an existing entry
+    async fn set_frontmatter(&mut self, date: Option<&str>, fields: &[(String, String)], index: bool) -> Value {
+        let updated = new::entry_date(date)
+            .and_then(|date| locate_entry(&self.global, date))
+            .and_then(|path| set_entry_fields(&path, fields).map(|_| path));
+        let path = match updated {
+            Ok(path) => path,
+            Err(e) => return tool_result(format!("Could not update the frontmatter: {:#}", e), true),
+        };
+        let values: Vec<String> = fields.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+        self.written(format!("Set {} in {}", values.join(", "), path.display()), &path, index).await
+    }
+
+    /// The tool result after writing an entry, indexing it first if asked
+    async fn written(&mut self, mut text: String, path: &std::path::Path, index: bool) -> Value {
+        if let Some(uri) = self.resources.path_to_uri(path) {
+            text.push_str(&format!(" ({})", uri));
+        }
+        if !index {
+            return tool_result(text, false);
+        }
+        // Reuses the search model, which is the one the index was built with
+        match index::update(&self.global, &mut self.embedding_generator).await {
+            Ok(indexed) => tool_result(format!("{}\nIndexed {} new or modified entries", text, indexed), false),
+            Err(e) => tool_result(format!("{}\nIndexing failed: {:#}", text, e), true),
+        }
+    }
+
+    /// Fill in a prompt template with its searches and frontmatter over the requested period
+    async fn get_prompt(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
+        let prompt = Prompt::from_name(name).ok_or_else(|| (INVALID_PARAMS, format!("Unknown prompt: {}", name)))?;
+        // Prompt arguments are always strings
+        let argument = |key: &str| {
+            params.get("arguments")
+                .and_then(|a| a.get(key))
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.trim().is_empty())
+        };
+
+        let period = argument("period").unwrap_or(prompt.default_period());
+        let range = parse_date_range(period, Local::now().date_naive()).map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+        let fields = prompts::fields(argument("fields"));
+        let table = summary_table(&self.global, &fields, range.0, range.1)
+            .map_err(|e| (INTERNAL_ERROR, format!("Frontmatter query failed: {:#}", e)))?;
+
+        let mut searches = Vec::new();
+        for query in prompt.searches() {
+            let results = self.search_page(&prompts::period_query(query, range.0, range.1)).await;
+            searches.push((*query, results.map(|page| page.results).map_err(|e| format!("{:#}", e))));
+        }
+        Ok(prompts::render(prompt, range, &fields, table, searches))
+    }
+
+    async fn search_page(&mut self, request: &SearchQuery) -> Result<SearchPage> {
+        if !index_exists(&self.lance_path, &self.table).await? {
+            anyhow::bail!("no table '{}' found at {}. {}", self.table, self.lance_path, MISSING_INDEX_HINT);
+        }
+        if request.rerank && self.reranker.is_none() {
+            self.reranker = Some(Reranker::new()?);
+        }
+        self.load_model().await?;
+        let generator = self.embedding_generator.as_ref().unwrap();
+        search_index_page(&self.lance_path, &self.table, generator, self.reranker.as_ref(), self.resources.filter(), request).await
+    }
+
+    async fn run_search(&mut self, request: SearchQuery, sort: SortOrder) -> Result<String> {
+        let mut page = self.search_page(&request).await?;
+        sort.apply(&mut page.results);
+
+        // Same shape as `rag-search --format json`
+        let shown_to = request.offset + page.results.len();
+        let mut output = json!({ "results": page.results, "total": page.total, "offset": request.offset });
+        if shown_to < page.total {
+            output["next_cursor"] = json!(page_cursor(&request, shown_to));
+        }
+        Ok(serde_json::to_string_pretty(&output)?)
+    }
+}
+
+fn parse_search_arguments(arguments: &Value) -> Result<SearchQuery, String> {
+    let query = arguments.get("query")
+        .and_then(|q| q.as_str())
+        .filter(|q| !q.trim().is_empty())
+        .ok_or("'query' must be a non-empty string")?
+        .to_string();
+
+    let num_results = match arguments.get("num_results") {
+        None | Some(Value::Null) => DEFAULT_NUM_RESULTS,
+        Some(n) => n.as_u64()
+            .filter(|n| *n > 0)
+            .ok_or("'num_results' must be a positive integer")? as usize,
+    };
+
+    let parse_date = |key: &str| -> Result<Option<NaiveDate>, String> {
+        match arguments.get(key).and_then(|v| v.as_str()) {
+            Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("'{}' must be a date in YYYY-MM-DD format", key)),
+            None => Ok(None),
+        }
+    };
+
+    let expand = match arguments.get("expand") {
+        None | Some(Value::Null) => 0,
+        Some(n) => n.as_u64().ok_or("'expand' must be a non-negative integer")? as usize,
+    };
+
+    let offset = match arguments.get("offset") {
+        None | Some(Value::Null) => 0,
+        Some(n) => n.as_u64().ok_or("'offset' must be a non-negative integer")? as usize,
+    };
+
+    let cursor = match arguments.get("cursor") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(v.as_str().ok_or("'cursor' must be a string")?),
+    };
+
+    let snippets = match arguments.get("snippets") {
+        None | Some(Value::Null) => 0,
+        Some(n) => n.as_u64().ok_or("'snippets' must be a non-negative integer")? as usize,
+    };
+
+    let recency_halflife = match arguments.get("recency_halflife") {
+        None | Some(Value::Null) => None,
+        Some(n) => Some(n.as_f64()
+            .filter(|n| *n > 0.0)
+            .ok_or("'recency_halflife' must be a positive number of days")? as f32),
+    };
+
+    let min_score = match arguments.get("min_score") {
+        None | Some(Value::Null) => None,
+        Some(n) => Some(n.as_f64().ok_or("'min_score' must be a number")? as f32),
+    };
+
+    let keyword_fallback = match arguments.get("keyword_fallback") {
+        None | Some(Value::Null) => Some(KEYWORD_FALLBACK_SCORE),
+        Some(Value::Bool(false)) => None,
+        Some(n) => Some(n.as_f64()
+            .filter(|n| (0.0..=1.0).contains(n))
+            .ok_or("'keyword_fallback' must be a score from 0 to 1, or false")? as f32),
+    };
+
+    let filter = match arguments.get("where") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(v.as_str().ok_or("'where' must be a string")?.to_string()),
+    };
+
+    let tags = match arguments.get("tags") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items.iter()
+            .map(|tag| tag.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("'tags' must be an array of strings")?,
+        Some(_) => return Err("'tags' must be an array of strings".to_string()),
+    };
+
+    let alternate_queries = match arguments.get("alternate_queries") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items.iter()
+            .map(|query| query.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("'alternate_queries' must be an array of strings")?,
+        Some(_) => return Err("'alternate_queries' must be an array of strings".to_string()),
+    };
+
+    let parse_flag = |key: &str| -> Result<bool, String> {
+        match arguments.get(key) {
+            None | Some(Value::Null) => Ok(false),
+            Some(v) => v.as_bool().ok_or_else(|| format!("'{}' must be a boolean", key)),
+        }
+    };
+
+    let mut request = SearchQuery {
+        query,
+        after: parse_date("after")?,
+        before: parse_date("before")?,
+        limit: num_results,
+        offset,
+        hybrid: parse_flag("hybrid")?,
+        rerank: parse_flag("rerank")?,
+        group_by_doc: parse_flag("group_by_doc")?,
+        expand,
+        full_doc: parse_flag("full_doc")?,
+        snippets,
+        recency_halflife,
+        min_score,
+        filter,
+        tags,
+        keyword_fallback,
+        alternate_queries,
+        // Always the metric the index was built for, with default ANN tuning
+        ..Default::default()
+    };
+    if let Some(cursor) = cursor {
+        request.offset = cursor_offset(&request, cursor).map_err(|e| e.to_string())?;
+    }
+    Ok(request)
+}
+
+/// `create_entry` arguments: the date expression, frontmatter fields as YAML values, and whether to index.
+/// Keys pass `new::check_key`, and every value is written as JSON on one line, which YAML reads
+/// the same: strings double-quoted with their newlines escaped, so no value can add keys
+fn parse_entry_arguments(arguments: &Value) -> Result<(Option<String>, Vec<(String, String)>, bool), String> {
+    let date = match arguments.get("date") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(v.as_str().ok_or("'date' must be a string")?.to_string()),
+    };
+    let fields = match arguments.get("fields") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .map(|(key, value)| {
+                new::check_key(key)?;
+                Ok((key.clone(), value.to_string()))
+            })
+            .collect::<Result<_, String>>()?,
+        Some(_) => return Err("'fields' must be an object of frontmatter values".to_string()),
+    };
+    let index = match arguments.get("index") {
+        None | Some(Value::Null) => false,
+        Some(v) => v.as_bool().ok_or("'index' must be a boolean")?,
+    };
+    Ok((date, fields, index))
+}
+
+/// `append_to_entry` arguments: the date expression, heading, text and whether to index
+fn parse_append_arguments(arguments: &Value) -> Result<(Option<String>, String, String, bool), String> {
+    if arguments.get("fields").is_some() {
+        return Err("'fields' is only for create_entry".to_string());
+    }
+    let (date, _, index) = parse_entry_arguments(arguments)?;
+    let required = |key: &str| {
+        arguments.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .map(String::from)
+            .ok_or_else(|| format!("'{}' must be a non-empty string", key))
+    };
+    Ok((date, required("heading")?, required("text")?, index))
+}
+
+fn parse_sort(arguments: &Value) -> Result<SortOrder, String> {
+    match arguments.get("sort") {
+        None | Some(Value::Null) => Ok(SortOrder::Score),
+        Some(v) => v.as_str().ok_or("'sort' must be \"score\" or \"date\"")?.parse(),
+    }
+}
+
+fn rag_search_tool() -> Value {
+    json!({
+        "name": "rag_search",
+        "description": "Semantic search over the indexed journal entries. Returns {results, total, offset, next_cursor} as JSON: matching chunks with path, date, score and snippet, and a cursor for the next page when there are more.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Natural language search query",
+                },
+                "alternate_queries": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Other phrasings of the query; each is searched and the rankings are fused, catching entries one wording misses",
+                },
+                "num_results": {
+                    "type": "integer",
+                    "description": format!("Number of results to return (default: {})", DEFAULT_NUM_RESULTS),
+                    "minimum": 1,
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Skip this many results (default: 0)",
+                    "minimum": 0,
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "'next_cursor' from a previous call with the same arguments, to fetch the next page",
+                },
+                "after": {
+                    "type": "string",
+                    "description": "Only return entries on or after this date (YYYY-MM-DD)",
+                },
+                "before": {
+                    "type": "string",
+                    "description": "Only return entries on or before this date (YYYY-MM-DD)",
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only return entries carrying all of these tags (frontmatter tags or #hashtags)",
+                },
+                "where": {
+                    "type": "string",
+                    "description": "SQL condition on indexed frontmatter fields, e.g. \"mood >= 7 AND project = 'mdrag'\"",
+                },
+                "hybrid": {
+                    "type": "boolean",
+                    "description": "Also match exact terms with full-text search (names, acronyms, error codes)",
+                },
+                "rerank": {
+                    "type": "boolean",
+                    "description": "Rerank candidates with a cross-encoder for more relevant results (slower)",
+                },
+                "group_by_doc": {
+                    "type": "boolean",
+                    "description": "Return one result per journal entry (its best chunk) with the number of matching chunks",
+                },
+                "expand": {
+                    "type": "integer",
+                    "description": "Include this many neighboring chunks on each side of every hit as 'context'",
+                    "minimum": 0,
+                },
+                "full_doc": {
+                    "type": "boolean",
+                    "description": "Include the whole cleaned journal entry of every hit as 'context'",
+                },
+                "snippets": {
+                    "type": "integer",
+                    "description": "Also return up to this many non-overlapping passages of every hit as 'snippets', with character offsets",
+                    "minimum": 0,
+                },
+                "sort": {
+                    "type": "string",
+                    "enum": ["score", "date"],
+                    "description": "Return the results best first (default) or oldest first, e.g. to follow a project over time",
+                },
+                "min_score": {
+                    "type": "number",
+                    "description": "Drop results scoring below this (0-1); an empty list means nothing relevant was found",
+                },
+                "recency_halflife": {
+                    "type": "number",
+                    "description": "Favor recent entries: an entry this many days old keeps 75% of its score",
+                    "exclusiveMinimum": 0,
+                },
+                "keyword_fallback": {
+                    "type": ["number", "boolean"],
+                    "description": format!(
+                        "When no result scores at least this (0-1, default: {}), search the text for the query's words instead and mark those results 'keyword_match'; false never does",
+                        KEYWORD_FALLBACK_SCORE,
+                    ),
+                },
+            },
+            "required": ["query"],
+        },
+    })
+}
+
+fn create_entry_tool() -> Value {
+    json!({
+        "name": "create_entry",
+        "description": "Start a journal entry from the template at journal/YYYY/MM/DD.md, with the date and the given frontmatter filled in. Fails if the entry already exists.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "date": {
+                    "type": "string",
+                    "description": "Day of the entry: YYYY-MM-DD or an expression such as 'yesterday' (default: today)",
+                },
+                "fields": {
+                    "type": "object",
+                    "description": "Frontmatter values to fill in, e.g. {\"mood\": 7, \"tags\": [\"work\"]}",
+                },
+                "index": {
+                    "type": "boolean",
+                    "description": "Index the new entry right away so searches find it",
+                },
+            },
+        },
+    })
+}
+
+fn append_to_entry_tool() -> Value {
+    json!({
+        "name": "append_to_entry",
+        "description": "Add text (e.g. a bullet such as '- Finished the report') under a heading of a day's journal entry, after what the section already holds. The entry is created from the template if it doesn't exist, and a missing heading is added as a new section at the end.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "heading": {
+                    "type": "string",
+                    "description": "Section title, e.g. 'Key Accomplishments' or '### Highlights of the Day' (case-insensitive)",
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Markdown to insert; an empty '-' bullet from the template is replaced by it",
+                },
+                "date": {
+                    "type": "string",
+                    "description": "Day of the entry: YYYY-MM-DD or an expression such as 'yesterday' (default: today)",
+                },
+                "index": {
+                    "type": "boolean",
+                    "description": "Index the entry right away so searches find the new text",
+                },
+            },
+            "required": ["heading", "text"],
+        },
+    })
+}
+
+fn set_frontmatter_tool() -> Value {
+    json!({
+        "name": "set_frontmatter",
+        "description": "Set frontmatter values (e.g. mood, sleep_hours, tags) in a day's existing journal entry, keeping its other keys, their order and comments.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "fields": {
+                    "type": "object",
+                    "description": "Values to set, e.g. {\"mood\": 7, \"weight_kg\": 82.4, \"tags\": [\"work\"]}",
+                },
+                "date": {
+                    "type": "string",
+                    "description": "Day of the entry: YYYY-MM-DD or an expression such as 'yesterday' (default: today)",
+                },
+                "index": {
+                    "type": "boolean",
+                    "description": "Index the entry right away so `where` filters see the new values",
+                },
+            },
+            "required": ["fields"],
+        },
+    })
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_arguments() {
+        let request = parse_search_arguments(&json!({
+            "query": "rust",
+            "num_results": 3,
+            "after": "2025-01-01",
+        })).unwrap();
+        assert_eq!(request.query, "rust");
+        assert_eq!(request.limit, 3);
+        assert!(!request.hybrid);
+        assert_eq!(request.after, NaiveDate::from_ymd_opt(2025, 1, 1));
+        assert!(request.before.is_none());
+
+        assert!(parse_search_arguments(&json!({ "query": "" })).is_err());
+        assert!(parse_search_arguments(&json!({ "query": "x", "after": "July" })).is_err());
+        assert!(parse_search_arguments(&json!({ "query": "x", "hybrid": true })).unwrap().hybrid);
+        assert!(parse_search_arguments(&json!({ "query": "x", "rerank": "yes" })).is_err());
+        assert_eq!(parse_search_arguments(&json!({ "query": "x", "tags": ["work"] })).unwrap().tags, vec!["work"]);
+        assert!(parse_search_arguments(&json!({ "query": "x", "tags": "work" })).is_err());
+        assert_eq!(request.keyword_fallback, Some(KEYWORD_FALLBACK_SCORE));
+        assert_eq!(parse_search_arguments(&json!({ "query": "x", "keyword_fallback": 0.3 })).unwrap().keyword_fallback, Some(0.3));
+        assert_eq!(parse_search_arguments(&json!({ "query": "x", "keyword_fallback": false })).unwrap().keyword_fallback, None);
+        assert!(parse_search_arguments(&json!({ "query": "x", "keyword_fallback": 2 })).is_err());
+        assert_eq!(
+            parse_search_arguments(&json!({ "query": "x", "alternate_queries": ["y"] })).unwrap().alternate_queries,
+            vec!["y"],
+        );
+        assert_eq!(parse_sort(&json!({ "query": "x", "sort": "date" })), Ok(SortOrder::Date));
+        assert!(parse_sort(&json!({ "query": "x", "sort": "mood" })).is_err());
+
+        let first = parse_search_arguments(&json!({ "query": "x", "num_results": 5 })).unwrap();
+        let cursor = page_cursor(&first, 5);
+        let next = parse_search_arguments(&json!({ "query": "x", "num_results": 5, "cursor": cursor })).unwrap();
+        assert_eq!(next.offset, 5);
+        assert!(parse_search_arguments(&json!({ "query": "y", "num_results": 5, "cursor": cursor })).is_err());
+    }
+
+    #[test]
+    fn test_parse_entry_arguments_quotes_values() {
+        let (date, fields, index) = parse_entry_arguments(&json!({
+            "date": "yesterday",
+            "fields": { "mood": 7, "note": "fine\nrating: 10", "tags": ["work"] },
+        })).unwrap();
+        assert_eq!(date.as_deref(), Some("yesterday"));
+        assert!(!index);
+        let content = md_rag_core::frontmatter::set_fields("", &fields);
+        let frontmatter = md_rag_core::frontmatter::extract_frontmatter(&content).unwrap();
+        assert_eq!(frontmatter.len(), 3);
+        assert_eq!(frontmatter["mood"], serde_yaml::Value::from(7));
+        assert_eq!(frontmatter["note"], serde_yaml::Value::from("fine\nrating: 10"));
+
+        assert!(parse_entry_arguments(&json!({ "fields": { "a: b": 1 } })).is_err());
+        assert!(parse_entry_arguments(&json!({ "fields": { "mood\nrating": 1 } })).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+
+use md_rag_core::search::{SearchQuery, SearchResult};
+
+/// Frontmatter fields shown when the client doesn't pick any
+const DEFAULT_FIELDS: &str = "mood,anxiety";
+
+/// Hits embedded per predefined search
+const RESULTS_PER_SEARCH: usize = 5;
+
+/// Journaling templates offered to MCP clients. Each runs its searches and a frontmatter
+/// query over a period and hands the results to the model with instructions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt {
+    WeeklyReview,
+    MoodRetrospective,
+}
+
+const ALL: [Prompt; 2] = [Prompt::WeeklyReview, Prompt::MoodRetrospective];
+
+impl Prompt {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL.into_iter().find(|prompt| prompt.name() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Prompt::WeeklyReview => "weekly_review",
+            Prompt::MoodRetrospective => "mood_retrospective",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Prompt::WeeklyReview => "Review a week of journaling: wins, struggles, mood and intentions for the next one",
+            Prompt::MoodRetrospective => "Look back at how mood moved over a period and what went with the good and bad stretches",
+        }
+    }
+
+    /// Date expression used when the client leaves `period` empty
+    pub fn default_period(self) -> &'static str {
+        match self {
+            Prompt::WeeklyReview => "last 7 days",
+            Prompt::MoodRetrospective => "last 30 days",
+        }
+    }
+
+    /// Queries whose hits in the period are embedded in the prompt
+    pub fn searches(self) -> &'static [&'static str] {
+        match self {
+            Prompt::WeeklyReview => &[
+                "accomplishments and things that went well",
+                "problems, setbacks and frustrations",
+                "plans and intentions for the coming days",
+            ],
+            Prompt::MoodRetrospective => &[
+                "feeling happy, calm or energized",
+                "feeling low, anxious or stressed",
+                "sleep, exercise and health",
+            ],
+        }
+    }
+
+    fn instructions(self) -> &'static str {
+        match self {
+            Prompt::WeeklyReview => "Write my weekly review from the daily check-ins and journal excerpts below: what went well, what was hard, how my mood and energy moved, and three concrete intentions for next week. Cite entries by date.",
+            Prompt::MoodRetrospective => "Look back at my mood using the daily ratings and journal excerpts below. Describe the trend, the best and worst stretches and what seems to go with them (sleep, people, work, exercise), and point out anything I might want to change. Cite entries by date.",
+        }
+    }
+
+    /// The `prompts/list` entry
+    fn definition(self) -> Value {
+        json!({
+            "name": self.name(),
+            "description": self.description(),
+            "arguments": [
+                {
+                    "name": "period",
+                    "description": format!("Date expression such as 'last 7 days', 'this week', 'last month' or '2025-07' (default: {})", self.default_period()),
+                    "required": false,
+                },
+                {
+                    "name": "fields",
+                    "description": format!("Comma-separated frontmatter fields to tabulate (default: {})", DEFAULT_FIELDS),
+                    "required": false,
+                },
+            ],
+        })
+    }
+}
+
+pub fn list() -> Vec<Value> {
+    ALL.into_iter().map(Prompt::definition).collect()
+}
+
+/// The `fields` argument as a list, or the default fields
+pub fn fields(argument: Option<&str>) -> Vec<String> {
+    let picked: Vec<String> = argument
+        .unwrap_or(DEFAULT_FIELDS)
+        .split(',')
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if picked.is_empty() { fields(None) } else { picked }
+}
+
+/// One predefined search, limited to the prompt's period
+pub fn period_query(query: &str, start: NaiveDate, end: NaiveDate) -> SearchQuery {
+    SearchQuery {
+        query: query.to_string(),
+        after: Some(start),
+        before: Some(end),
+        limit: RESULTS_PER_SEARCH,
+        group_by_doc: true,
+        ..Default::default()
+    }
+}
+
+/// The `prompts/get` result: one user message with the instructions and everything gathered.
+/// A failed search is noted in its place, so the rest of the prompt is still usable
+pub fn render(
+    prompt: Prompt,
+    (start, end): (NaiveDate, NaiveDate),
+    fields: &[String],
+    table: Option<String>,
+    searches: Vec<(&str, Result<Vec<SearchResult>, String>)>,
+) -> Value {
+    let mut text = format!("{}\n\nPeriod: {} to {}\n\n## Daily check-ins ({})\n\n", prompt.instructions(), start, end, fields.join(", "));
+    text.push_str(table.as_deref().unwrap_or("No entries with frontmatter in this period."));
+
+    for (query, results) in searches {
+        text.push_str(&format!("\n\n## Journal excerpts: {}\n", query));
+        match results {
+            Ok(results) if results.is_empty() => text.push_str("\nNothing found."),
+            Ok(results) => {
+                for result in results {
+                    let section = result.section.map(|s| format!(" ({})", s)).unwrap_or_default();
+                    let snippet = result.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+                    text.push_str(&format!("\n- {} {}{}: {}", result.date, result.path.display(), section, snippet));
+                }
+            }
+            Err(e) => text.push_str(&format!("\nSearch failed: {}", e)),
+        }
+    }
+
+    json!({
+        "description": format!("{} ({} to {})", prompt.description(), start, end),
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text },
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt() {
+        assert_eq!(Prompt::from_name("weekly_review"), Some(Prompt::WeeklyReview));
+        assert!(Prompt::from_name("daily_gratitude").is_none());
+        assert_eq!(fields(Some(" mood, ,sleep_hours")), vec!["mood", "sleep_hours"]);
+        assert_eq!(fields(Some(",")), vec!["mood", "anxiety"]);
+
+        let day = |d| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+        let rendered = render(
+            Prompt::WeeklyReview,
+            (day(14), day(20)),
+            &fields(None),
+            None,
+            vec![("wins", Ok(Vec::new())), ("setbacks", Err("no index".to_string()))],
+        );
+        let text = rendered["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.contains("Period: 2025-07-14 to 2025-07-20"));
+        assert!(text.contains("## Daily check-ins (mood, anxiety)\n\nNo entries with frontmatter"));
+        assert!(text.contains("## Journal excerpts: wins\n\nNothing found."));
+        assert!(text.contains("Search failed: no index"));
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use md_rag_core::frontmatter::split_frontmatter;
+use md_rag_core::path_filter::PathFilter;
+use md_rag_core::template_filter::TemplateFilter;
+
+const URI_SCHEME: &str = "journal://";
+
+/// Serves journal entries as MCP resources addressed by `journal://YYYY/MM/DD`
+pub struct JournalResources {
+    /// Each journal directory with the filter deciding which of its entries are served;
+    /// entries excluded from indexing (e.g. by `.ragignore`) aren't served either
+    roots: Vec<(PathBuf, PathFilter)>,
+    filter: TemplateFilter,
+}
+
+impl JournalResources {
+    pub fn new(roots: Vec<(PathBuf, PathFilter)>, filter: TemplateFilter) -> Self {
+        Self { roots, filter }
+    }
+
+    /// The configured template filter entries are cleaned with
+    pub fn filter(&self) -> &TemplateFilter {
+        &self.filter
+    }
+
+    /// List every markdown entry in the journal directories, newest first
+    pub fn list(&self) -> Vec<Value> {
+        let mut uris: Vec<String> = self.roots
+            .iter()
+            .flat_map(|(_, paths)| paths.markdown_files())
+            .filter_map(|path| self.path_to_uri(&path))
+            .collect();
+
+        uris.sort();
+        uris.dedup();
+        uris.reverse();
+
+        uris.into_iter()
+            .map(|uri| {
+                let name = uri.trim_start_matches(URI_SCHEME).to_string();
+                json!({
+                    "uri": uri,
+                    "name": name,
+                    "mimeType": "text/markdown",
+                })
+            })
+            .collect()
+    }
+
+    /// URI templates advertised to clients
+    pub fn templates(&self) -> Vec<Value> {
+        vec![json!({
+            "uriTemplate": "journal://{year}/{month}/{day}",
+            "name": "Journal entry",
+            "description": "Daily journal entry with template boilerplate removed",
+            "mimeType": "text/markdown",
+        })]
+    }
+
+    /// Read an entry and return its cleaned content
+    pub fn read(&self, uri: &str) -> Result<Value> {
+        let path = self.uri_to_path(uri)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+
+        // Drop the frontmatter, then the template noise
+        let (_, body) = split_frontmatter(&content);
+        let cleaned = self.filter.clean_content(body);
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "text/markdown",
+                "text": cleaned,
+            }]
+        }))
+    }
+
+    /// The `journal://` URI of an entry, if it is inside a journal directory
+    pub fn path_to_uri(&self, path: &Path) -> Option<String> {
+        let relative = self.roots
+            .iter()
+            .find_map(|(dir, _)| path.strip_prefix(dir).ok())?
+            .with_extension("");
+        let parts: Vec<_> = relative.components()
+            .map(|c| c.as_os_str().to_str())
+            .collect::<Option<_>>()?;
+        Some(format!("{}{}", URI_SCHEME, parts.join("/")))
+    }
+
+    fn uri_to_path(&self, uri: &str) -> Result<PathBuf> {
+        let Some(relative) = uri.strip_prefix(URI_SCHEME) else {
+            bail!("Unsupported resource URI: {}", uri);
+        };
+
+        let relative = Path::new(relative);
+        // Never let a URI escape the journal directory
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            bail!("Invalid journal URI: {}", uri);
+        }
+
+        // The first directory that has the entry and doesn't exclude it
+        let path = self.roots
+            .iter()
+            .map(|(dir, paths)| (dir.join(relative).with_extension("md"), paths))
+            .find(|(path, paths)| path.is_file() && paths.is_included(path))
+            .map(|(path, _)| path);
+        match path {
+            Some(path) => Ok(path),
+            None => bail!("No journal entry at {}", uri),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (journal, notes) = (root.join("journal"), root.join("notes"));
+        fs::create_dir_all(journal.join("2025/07")).unwrap();
+        fs::create_dir_all(&notes).unwrap();
+        fs::write(journal.join("2025/07/21.md"), "# Monday\n").unwrap();
+        fs::write(notes.join("ideas.md"), "# Ideas\n").unwrap();
+
+        let roots = [&journal, &notes]
+            .into_iter()
+            .map(|dir| (dir.clone(), PathFilter::new(dir, &[], &[]).unwrap()))
+            .collect();
+        let resources = JournalResources::new(roots, TemplateFilter::new());
+        let uri = resources.path_to_uri(&journal.join("2025/07/21.md")).unwrap();
+        assert_eq!(uri, "journal://2025/07/21");
+        assert_eq!(resources.uri_to_path(&uri).unwrap(), journal.join("2025/07/21.md"));
+        assert_eq!(resources.uri_to_path("journal://ideas").unwrap(), notes.join("ideas.md"));
+        assert!(resources.uri_to_path("journal://2025/07/22").is_err());
+        assert!(resources.uri_to_path("journal://../secrets").is_err());
+        assert!(resources.uri_to_path("file:///etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! The HTTP+SSE transport: a client opens an event stream on `GET /sse`, is told where to post
+//! its messages by an `endpoint` event, and gets every response back as a `message` event.
+
+use anyhow::{bail, Result};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+use md_rag_core::crypto::random_token;
+
+use super::Server;
+use crate::web::check_host;
+
+/// Where each open session's responses go
+type Sessions = Arc<std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<String>>>>;
+
+#[derive(Clone)]
+struct SseState {
+    /// One server for every session, so the model stays loaded between conversations
+    server: Arc<Mutex<Server>>,
+    sessions: Sessions,
+}
+
+/// Forgets a session when its event stream is dropped, i.e. when the client disconnects
+struct SessionGuard {
+    id: String,
+    sessions: Sessions,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.id);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MessageParams {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+/// Refuse to listen beyond this machine without a token: the tools write to the journal
+pub fn check_exposure(host: &str, token: Option<&str>) -> Result<()> {
+    if token.is_some_and(|token| token.trim().is_empty()) {
+        bail!("--token is empty");
+    }
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host.trim_matches(['[', ']']).parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if !loopback && token.is_none() {
+        bail!("Listening on {} lets other machines read and write the journal; set --token (or MDRAG_MCP_TOKEN) too", host);
+    }
+    Ok(())
+}
+
+/// Serve MCP over HTTP+SSE until Ctrl+C
+pub async fn serve(server: Server, host: &str, port: u16, token: Option<String>) -> Result<()> {
+    let state = SseState {
+        server: Arc::new(Mutex::new(server)),
+        sessions: Arc::default(),
+    };
+    let app = Router::new()
+        .route("/sse", get(open_session))
+        .route("/message", post(message))
+        .with_state(state);
+    let app = check_host(app, host, port);
+    let app = match token {
+        Some(token) => require_token(app, token),
+        None => app,
+    };
+
+    let listener = tokio::net::TcpListener::bind((host, port)).await?;
+    eprintln!("🔌 MCP over SSE at http://{}/sse (Ctrl+C to stop)", listener.local_addr()?);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// Refuse requests without `Authorization: Bearer <token>`
+fn require_token(app: Router, token: String) -> Router {
+    let expected = Arc::new(format!("Bearer {}", token));
+    app.layer(middleware::from_fn(move |request: Request, next: Next| {
+        let expected = expected.clone();
+        async move {
+            let given = request.headers().get(header::AUTHORIZATION).map(|value| value.as_bytes());
+            if given.is_some_and(|given| same_bytes(given, expected.as_bytes())) {
+                next.run(request).await
+            } else {
+                unauthorized()
+            }
+        }
+    }))
+}
+
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")]).into_response()
+}
+
+/// Compare without stopping at the first difference, so timing doesn't reveal the token
+fn same_bytes(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Start a session: the `endpoint` event, then the responses to whatever is posted there
+async fn open_session(State(state): State<SseState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let id = random_token();
+    let (sender, receiver) = mpsc::unbounded_channel();
+    state.sessions.lock().unwrap().insert(id.clone(), sender);
+
+    let endpoint = Event::default().event("endpoint").data(format!("/message?sessionId={}", id));
+    let guard = SessionGuard { id, sessions: state.sessions.clone() };
+    let responses = stream::unfold((receiver, guard), |(mut receiver, guard)| async move {
+        let message = receiver.recv().await?;
+        Some((Event::default().event("message").data(message), (receiver, guard)))
+    });
+    Sse::new(stream::once(async { endpoint }).chain(responses).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+/// Handle a posted JSON-RPC message; its response goes out on the session's event stream
+async fn message(State(state): State<SseState>, Query(params): Query<MessageParams>, body: String) -> StatusCode {
+    let Some(sender) = state.sessions.lock().unwrap().get(&params.session_id).cloned() else {
+        return StatusCode::NOT_FOUND;
+    };
+    let response = state.server.lock().await.handle_message(&body).await;
+    // The client disconnected while the message was handled
+    if let Some(response) = response
+        && sender.send(response).is_err()
+    {
+        return StatusCode::GONE;
+    }
+    StatusCode::ACCEPTED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_exposure() {
+        assert!(check_exposure("127.0.0.1", None).is_ok());
+        assert!(check_exposure("localhost", None).is_ok());
+        assert!(check_exposure("[::1]", None).is_ok());
+        assert!(check_exposure("0.0.0.0", None).is_err());
+        assert!(check_exposure("192.168.1.20", None).is_err());
+        assert!(check_exposure("0.0.0.0", Some("s3cret")).is_ok());
+        assert!(check_exposure("0.0.0.0", Some(" ")).is_err());
+
+        assert!(same_bytes(b"Bearer s3cret", b"Bearer s3cret"));
+        assert!(!same_bytes(b"Bearer s3cre", b"Bearer s3cret"));
+        assert!(!same_bytes(b"Bearer s3creT", b"Bearer s3cret"));
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::frontmatter::extract_frontmatter;
+use md_rag_core::new_entry::{append_to_section, entry_path, instantiate, BUILTIN_TEMPLATE};
+
+use crate::index;
+use crate::GlobalArgs;
+
+/// Template looked up next to the journal directory when none is configured
+const DEFAULT_TEMPLATE: &str = "template/daily.md";
+
+#[derive(clap::Args, Debug)]
+pub struct NewArgs {
+    /// Day of the entry: YYYY-MM-DD or an expression such as yesterday [default: today]
+    date: Option<String>,
+
+    /// Frontmatter value to fill in, e.g. --set mood=7; repeatable, and wins over `[new] fields`
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_field)]
+    fields: Vec<(String, String)>,
+
+    /// Template file [default: `[new] template` in .mdrag.toml, else template/daily.md beside
+    /// the journal directory, else a bare frontmatter block]
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Index the new entry right away (an incremental pass)
+    #[arg(long)]
+    index: bool,
+}
+
+/// Create the entry and print its path, so `$EDITOR "$(mdrag new)"` opens it
+pub async fn run(global: &GlobalArgs, args: NewArgs) -> Result<()> {
+    let date = entry_date(args.date.as_deref())?;
+    let path = create_entry(global, date, &args.fields, args.template.as_deref())?;
+    eprintln!("📝 Created {}", path.display());
+    println!("{}", path.display());
+
+    if args.index {
+        let indexed = index::update(global, &mut None).await?;
+        eprintln!("✅ Indexed {} new or modified entries", indexed);
+    }
+    Ok(())
+}
+
+/// The single day a date argument names; today without one
+pub fn entry_date(text: Option<&str>) -> Result<NaiveDate> {
+    let today = Local::now().date_naive();
+    let Some(text) = text.filter(|text| !text.trim().is_empty()) else {
+        return Ok(today);
+    };
+    let (start, end) = parse_date_range(text, today)?;
+    if start != end {
+        bail!("'{}' covers {} to {}; an entry needs a single day", text, start, end);
+    }
+    Ok(start)
+}
+
+/// Write the entry for `date` into the first journal directory, filled in from the template
+/// with `[new] fields` and then `fields`. An existing entry is never overwritten, and nothing is
+/// written if the frontmatter wouldn't parse
+pub fn create_entry(global: &GlobalArgs, date: NaiveDate, fields: &[(String, String)], template: Option<&Path>) -> Result<PathBuf> {
+    let template = load_template(global, template)?;
+    let fields = [global.config.new.field_values().as_slice(), fields].concat();
+    let content = instantiate(&template, date, &fields);
+
+    let path = entry_path(&global.journal_dirs[0], date);
+    extract_frontmatter(&content).with_context(|| format!("Not creating {}: its frontmatter would be invalid", path.display()))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => bail!("{} already exists", path.display()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to create {}", path.display())),
+    };
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Add `text` under `heading` in the entry for `date`, creating the entry from the template first
+/// if there is none. Returns the path and whether the entry was created
+pub fn append_to_entry(global: &GlobalArgs, date: NaiveDate, heading: &str, text: &str) -> Result<(PathBuf, bool)> {
+    if heading.trim_start_matches('#').trim().is_empty() || text.trim().is_empty() {
+        bail!("Both a heading and some text are needed");
+    }
+    let (path, created) = match find_entry(global, date) {
+        Some(path) => (path, false),
+        None => (create_entry(global, date, &[], None)?, true),
+    };
+
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    replace_file(&path, &append_to_section(&content, heading, text))?;
+    Ok((path, created))
+}
+
+/// The existing `YYYY/MM/DD.md` entry for `date` in any journal directory
+pub fn find_entry(global: &GlobalArgs, date: NaiveDate) -> Option<PathBuf> {
+    global.journal_dirs.iter().map(|dir| entry_path(dir, date)).find(|path| path.is_file())
+}
+
+/// Write `content` beside `path` and rename it over the file, so an editor or the indexer
+/// never sees it half written
+pub fn replace_file(path: &Path, content: &str) -> Result<()> {
+    let partial = path.with_extension("md.partial");
+    fs::write(&partial, content).with_context(|| format!("Failed to write {}", partial.display()))?;
+    fs::rename(&partial, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+fn load_template(global: &GlobalArgs, template: Option<&Path>) -> Result<String> {
+    let configured = template.map(Path::to_path_buf).or_else(|| global.config.new.template.clone());
+    // The repo layout keeps the templates next to the journal
+    let path = configured.or_else(|| {
+        let beside = global.journal_dirs[0].parent()?.join(DEFAULT_TEMPLATE);
+        beside.is_file().then_some(beside)
+    });
+    match path {
+        Some(path) => fs::read_to_string(&path).with_context(|| format!("Failed to read template {}", path.display())),
+        None => Ok(BUILTIN_TEMPLATE.to_string()),
+    }
+}
+
+/// `KEY=VALUE` from `--set` or `frontmatter set`; the value is YAML on one line
+pub fn parse_field(text: &str) -> Result<(String, String), String> {
+    let (key, value) = text.split_once('=').ok_or("expected KEY=VALUE")?;
+    let key = key.trim();
+    check_key(key)?;
+    let value = value.trim();
+    if value.contains(['\n', '\r']) {
+        return Err(format!("the value of '{}' must be on one line", key));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// A key `set_fields` can write as a `key: value` line of its own
+pub fn check_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.starts_with(['#', '-']) || key.contains(|c: char| c == ':' || c.is_whitespace()) {
+        return Err(format!("'{}' is not a frontmatter key", key));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_field_and_date() {
+        assert_eq!(parse_field("mood = 7"), Ok(("mood".to_string(), "7".to_string())));
+        assert_eq!(parse_field("tags=[work, rust]"), Ok(("tags".to_string(), "[work, rust]".to_string())));
+        assert!(parse_field("mood").is_err());
+        assert!(parse_field("my mood=7").is_err());
+        assert!(parse_field("# mood=7").is_err());
+        assert!(parse_field("mood=7\nrating: 10").is_err());
+
+        assert_eq!(entry_date(Some("2025-07-21")).unwrap(), NaiveDate::from_ymd_opt(2025, 7, 21).unwrap());
+        assert_eq!(entry_date(None).unwrap(), Local::now().date_naive());
+        assert!(entry_date(Some("last week")).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// Progress bars for the phases of an index pass, drawn on stdout (or stderr) when it is a terminal.
+///
+/// Piped or logged output gets a plain line per completed batch instead, so CI logs
+/// don't fill up with redraws.
+pub struct Progress {
+    bars: MultiProgress,
+    terminal: bool,
+    stderr: bool,
+}
+
+impl Progress {
+    /// Report on stderr instead of stdout when stdout carries machine-readable output
+    pub fn new(stderr: bool) -> Self {
+        let terminal = if stderr { std::io::stderr().is_terminal() } else { std::io::stdout().is_terminal() };
+        let target = match (terminal, stderr) {
+            (false, _) => ProgressDrawTarget::hidden(),
+            (true, false) => ProgressDrawTarget::stdout(),
+            (true, true) => ProgressDrawTarget::stderr(),
+        };
+        Self { bars: MultiProgress::with_draw_target(target), terminal, stderr }
+    }
+
+    /// Start a phase counting `unit`s; without a `total` it shows a spinner with the count so far
+    pub fn phase(&self, name: &'static str, unit: &'static str, total: Option<usize>) -> Phase {
+        let bar = match total {
+            Some(total) => ProgressBar::new(total as u64).with_style(
+                ProgressStyle::with_template("  {prefix:<9} [{bar:30}] {pos}/{len} {msg} ({per_sec}, ETA {eta})")
+                    .unwrap()
+                    .progress_chars("=> "),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("  {prefix:<9} {spinner} {pos} {msg} ({per_sec})").unwrap(),
+            ),
+        };
+        let bar = self.bars.add(bar.with_prefix(name).with_message(unit));
+        if total.is_none() && self.terminal {
+            bar.enable_steady_tick(Duration::from_millis(100));
+        }
+        Phase { bar, name, unit, terminal: self.terminal, stderr: self.stderr }
+    }
+}
+
+/// One phase of indexing, such as scanning files or embedding chunks
+pub struct Phase {
+    bar: ProgressBar,
+    name: &'static str,
+    unit: &'static str,
+    terminal: bool,
+    stderr: bool,
+}
+
+impl Phase {
+    /// Count finished items without logging, for per-file steps
+    pub fn inc(&self, n: usize) {
+        self.bar.inc(n as u64);
+    }
+
+    /// Count a finished batch, with `detail` such as the chunk throughput; logged when there is no bar
+    pub fn batch(&self, n: usize, detail: String) {
+        self.bar.inc(n as u64);
+        self.bar.set_message(format!("{} {}", self.unit, detail));
+        if !self.terminal {
+            self.log(format!("  {}: {} {}", self.name, self.done(), detail));
+        }
+    }
+
+    /// End the phase, returning the seconds since it started
+    pub fn finish(&self) -> f64 {
+        self.bar.finish();
+        let elapsed = self.bar.elapsed().as_secs_f64();
+        if !self.terminal {
+            self.log(format!("  {}: {} in {:.1}s", self.name, self.done(), elapsed));
+        }
+        elapsed
+    }
+
+    fn log(&self, line: String) {
+        if self.stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    fn done(&self) -> String {
+        match self.bar.length() {
+            Some(total) => format!("{}/{} {}", self.bar.position(), total, self.unit),
+            None => format!("{} {}", self.bar.position(), self.unit),
+        }
+    }
+}
+
+/// Items per second over `elapsed` seconds, for throughput messages
+pub fn rate(items: usize, elapsed: f64) -> f64 {
+    if elapsed > 0.0 { items as f64 / elapsed } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_counts_batches() {
+        let progress = Progress { bars: MultiProgress::with_draw_target(ProgressDrawTarget::hidden()), terminal: true, stderr: false };
+        let phase = progress.phase("Embedding", "documents", Some(10));
+        phase.batch(4, String::new());
+        phase.inc(2);
+        assert_eq!(phase.done(), "6/10 documents");
+        assert_eq!(rate(10, 0.0), 0.0);
+    }
+}
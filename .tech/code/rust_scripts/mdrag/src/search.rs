@@ -0,0 +1,1150 @@
+use anyhow::{Context, Result};
+use clap::{Args, FromArgMatches};
+use chrono::{Datelike, Local};
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::embeddings::{Backend, EmbeddingGenerator, DEFAULT_MODEL};
+use md_rag_core::history::{self, HistoryEntry};
+use md_rag_core::metric::Metric;
+use md_rag_core::prompt::{llm_token_counter, pack_sources, render_sources, Source};
+use md_rag_core::rerank::Reranker;
+use md_rag_core::saved::SavedSearches;
+use md_rag_core::schema::{db_path, schema_model};
+use md_rag_core::search::{
+    count_filtered, count_needs_search, count_query, cursor_offset, index_exists, list_collections, load_search_model,
+    merge_collections, page_cursor, search_index, search_index_page, search_table_page, verify_index_model, SearchPage,
+    SearchQuery, SearchResult, SortOrder, KEYWORD_FALLBACK_SCORE, MISSING_INDEX_HINT,
+};
+use md_rag_core::snippet::HIGHLIGHT;
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::daemon;
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct SearchArgs {
+    /// Search query
+    #[arg(required_unless_present_any = ["queries_file", "like", "queries", "saved", "count"])]
+    query: Option<String>,
+
+    /// Another phrasing to search for; repeat for more. Each is searched on its own and the
+    /// rankings are fused with reciprocal rank fusion, so an entry any of them finds can rank
+    #[arg(short = 'q', long = "query", value_name = "QUERY", conflicts_with_all = ["like", "queries_file"])]
+    queries: Vec<String>,
+
+    /// Save this search (query and options as typed) under NAME in the user config dir, then run it
+    #[arg(long, value_name = "NAME")]
+    save: Option<String>,
+
+    /// Run a search saved with --save; options given here are added to the saved ones, or
+    /// override them
+    #[arg(long, value_name = "NAME")]
+    saved: Option<String>,
+
+    /// Leave this search out of the history `mdrag history` shows (`history = false` under
+    /// `[search]` in `.mdrag.toml` turns it off for good)
+    #[arg(long)]
+    no_history: bool,
+
+    /// Find the entries most similar to this indexed one (path as results show it) instead of
+    /// searching for a query; implies --group-by-doc
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["query", "queries_file", "hybrid", "rerank", "all_collections"])]
+    like: Option<String>,
+
+    /// Run every line of FILE as a query (`-` reads stdin), loading the model once, and print
+    /// a JSON object mapping each query to its results
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["query", "all_collections"])]
+    queries_file: Option<PathBuf>,
+
+    /// Only entries on or after this date: YYYY-MM-DD or an expression like "2 weeks ago" or "last month"
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only entries on or before this date (same forms as --after; a period counts up to its last day)
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Only entries within this period, e.g. "last month", "this week", "july 2025" or "2024"
+    #[arg(long, conflicts_with_all = ["after", "before"])]
+    during: Option<String>,
+
+    /// Only entries from today's month and day, in any year
+    #[arg(long)]
+    on_this_day: bool,
+
+    /// Filter on frontmatter columns from `mdrag index --metadata-fields`, e.g. "mood >= 7"
+    #[arg(long = "where", value_name = "CONDITION")]
+    filter: Option<String>,
+
+    /// Only entries with this tag (frontmatter `tags:` or #hashtag); repeat to require several
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Only entries whose path starts with this, e.g. journal/2025 (paths as results show them)
+    #[arg(long, value_name = "PREFIX")]
+    path_prefix: Option<String>,
+
+    /// Only entries whose path matches this glob, e.g. "**/topics/*.md"
+    #[arg(long, value_name = "GLOB")]
+    path_glob: Option<String>,
+
+    /// Only chunks containing this text (ignoring case); repeat to require several
+    #[arg(long, value_name = "TEXT")]
+    must_contain: Vec<String>,
+
+    /// Only chunks matching this regular expression, e.g. "E0\d{3}" (use (?i) to ignore case)
+    #[arg(long, value_name = "REGEX")]
+    must_match_regex: Option<String>,
+
+    /// Number of results to return [default: 10]
+    #[arg(short, long)]
+    num_results: Option<usize>,
+
+    /// Show the results by score, or oldest first by date (still the most relevant ones) [default: score]
+    #[arg(long, value_name = "score|date")]
+    sort: Option<SortOrder>,
+
+    /// Skip this many results, to show the pages after the first [default: 0]
+    #[arg(long)]
+    offset: Option<usize>,
+
+    /// Continue from the `next_cursor` of a previous JSON page (same query and options)
+    #[arg(long, conflicts_with_all = ["offset", "queries_file"])]
+    cursor: Option<String>,
+
+    /// Return only file paths
+    #[arg(long)]
+    files_only: bool,
+
+    /// Print the results as one context block for an LLM: repeated text dropped, best first
+    /// (or by --sort), each under a `[n] path (date · section)` header and cut to --max-tokens
+    #[arg(long, conflicts_with_all = ["files_only", "queries_file"])]
+    pack_context: bool,
+
+    /// Token budget for --pack-context, counted with the cl100k_base tokenizer [default: 4000]
+    #[arg(long, requires = "pack_context")]
+    max_tokens: Option<usize>,
+
+    /// Print only how many chunks (entries with --group-by-doc) match, exiting 1 if none do.
+    /// Without a query this counts what passes the filters; with one, --min-score and the
+    /// content filters apply too
+    #[arg(long, conflicts_with_all = ["files_only", "pack_context", "queries_file", "offset", "cursor"])]
+    count: bool,
+
+    /// Show debug information (scores, metadata)
+    #[arg(long)]
+    debug: bool,
+
+    /// Plain text even on a terminal (so does a non-empty NO_COLOR)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Print straight to the terminal instead of through $PAGER when the results don't fit
+    #[arg(long)]
+    no_pager: bool,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+
+    /// Embedding model (fastembed model code, or the API model name with --backend openai);
+    /// defaults to the model the index was built with
+    #[arg(short, long)]
+    model: Option<String>,
+
+    /// Search even if the model doesn't match the one the index was built with
+    #[arg(long)]
+    force: bool,
+
+    /// Combine vector search with BM25 full-text search (better for exact terms and names)
+    #[arg(long)]
+    hybrid: bool,
+
+    /// Rerank the top candidates with a cross-encoder (slower, more relevant)
+    #[arg(long)]
+    rerank: bool,
+
+    /// Return one result per entry with its best-matching chunk
+    #[arg(long)]
+    group_by_doc: bool,
+
+    /// Include N neighboring chunks on each side of every hit [default: 0]
+    #[arg(long)]
+    expand: Option<usize>,
+
+    /// Include the whole cleaned entry for every hit
+    #[arg(long)]
+    full_doc: bool,
+
+    /// Show up to N non-overlapping passages of each hit instead of one snippet
+    #[arg(long, value_name = "N")]
+    snippets: Option<usize>,
+
+    /// Favor recent entries; an entry this many days old keeps 75% of its score
+    #[arg(long, value_name = "DAYS")]
+    recency_halflife: Option<f32>,
+
+    /// Drop results scoring below this (0-1). Exits with status 2 if nothing is left
+    #[arg(long)]
+    min_score: Option<f32>,
+
+    /// When no result scores at least this, fall back to a case-insensitive scan of chunk text
+    /// for the query's words (results are labelled as keyword matches). A score under the cosine
+    /// metric; l2 indexes compare with the equivalent l2 score
+    #[arg(long, value_name = "SCORE", default_value_t = KEYWORD_FALLBACK_SCORE)]
+    keyword_fallback: f32,
+
+    /// Never fall back to the keyword scan
+    #[arg(long)]
+    no_keyword_fallback: bool,
+
+    /// Distance metric (cosine, l2, dot); defaults to the one the index was built for
+    #[arg(long)]
+    metric: Option<Metric>,
+
+    /// ANN index partitions to probe (with `mdrag index --build-ann`); higher is slower and more accurate
+    #[arg(long)]
+    nprobes: Option<usize>,
+
+    /// Re-rank N times the requested ANN candidates with exact distances
+    #[arg(long)]
+    refine_factor: Option<u32>,
+
+    /// Apply date filters before the vector search (exact); `--prefilter false` filters its
+    /// results instead, which is faster with an ANN index but can return fewer results
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    prefilter: bool,
+
+    /// Search every collection in the database (see --collection) and merge the rankings
+    #[arg(long)]
+    all_collections: bool,
+
+    /// Treat a missing index as no results instead of an error (for tests)
+    #[arg(long, hide = true)]
+    allow_missing_index: bool,
+}
+
+/// Exit status when the search ran but found nothing, so scripts can tell it apart from errors
+const EXIT_NO_RESULTS: i32 = 2;
+
+/// Exit status when the search itself failed
+const EXIT_ERROR: i32 = 1;
+
+/// JSON output shape; always an object so an empty result is `{"results": [], "total": 0, ...}`
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    results: &'a [SearchResult],
+    /// Results across all pages
+    total: usize,
+    offset: usize,
+    /// Pass to `--cursor` for the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+/// Batch output: an object from each query to its results, in the order of the file
+struct BatchOutput<'a>(&'a [(String, Vec<SearchResult>)]);
+
+impl Serialize for BatchOutput<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(query, results)| (query, results)))
+    }
+}
+
+/// JSON output when the search failed: `{"error": "...", "hint": "..."}`
+#[derive(Serialize)]
+struct JsonError<'a> {
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// One JSON result per line, for streaming into jq, fzf or another agent
+    Ndjson,
+    /// A list of links with blockquoted snippets, to paste into a note or an LLM prompt
+    Markdown,
+    /// `path:line:snippet` per snippet like `grep -n`, for editors, fzf and quickfix lists
+    Grep,
+}
+
+/// Results returned when neither `-n` nor `.mdrag.toml` sets a number
+const DEFAULT_NUM_RESULTS: usize = 10;
+
+/// Results fetched for `--pack-context` without `-n`, enough to fill the default budget
+const PACK_CONTEXT_RESULTS: usize = 30;
+
+/// `--pack-context` budget when `--max-tokens` isn't given
+const DEFAULT_PACK_TOKENS: usize = 4000;
+
+/// `--pack-context --format json` output
+#[derive(Serialize)]
+struct PackedOutput<'a> {
+    context: String,
+    /// Tokens of `context`
+    tokens: usize,
+    sources: &'a [Source],
+}
+
+pub async fn run(global: &GlobalArgs, args: SearchArgs) -> Result<()> {
+    let args = resolve_saved(args)?;
+    // Flags win over `[search]` in `.mdrag.toml`; switches can only be turned on
+    let defaults = &global.config.search;
+    let recency_halflife = args.recency_halflife.or(defaults.recency_halflife);
+    
+    // Parse date filters; a period like "last month" starts --after at its first day and ends --before at its last
+    let today = Local::now().date_naive();
+    let mut after_date = args.after
+        .as_deref()
+        .map(|s| parse_date_range(s, today).map(|(start, _)| start))
+        .transpose()?;
+    
+    let mut before_date = args.before
+        .as_deref()
+        .map(|s| parse_date_range(s, today).map(|(_, end)| end))
+        .transpose()?;
+    
+    if let Some(during) = &args.during {
+        let (start, end) = parse_date_range(during, today)?;
+        after_date = Some(start);
+        before_date = Some(end);
+    }
+    
+    if let Some(halflife) = recency_halflife && halflife <= 0.0 {
+        anyhow::bail!("--recency-halflife must be a positive number of days");
+    }
+    
+    if args.debug {
+        if let Some(query) = &args.query {
+            eprintln!("🔍 Query: '{}'", query);
+        }
+        for query in &args.queries {
+            eprintln!("🔍 Query: '{}'", query);
+        }
+        if let Some(path) = &args.like {
+            eprintln!("🔍 Like: {}", path);
+        }
+        if let Some(after) = after_date {
+            eprintln!("📅 After: {}", after);
+        }
+        if let Some(before) = before_date {
+            eprintln!("📅 Before: {}", before);
+        }
+        if args.on_this_day {
+            eprintln!("📅 On this day: {}", today.format("%m-%d"));
+        }
+        if !args.tags.is_empty() {
+            eprintln!("🏷️  Tags: {}", args.tags.join(", "));
+        }
+        if let Some(filter) = &args.filter {
+            eprintln!("🔎 Where: {}", filter);
+        }
+    }
+    
+    // Connect to LanceDB
+    // The positional query, or else the first -q, leads; it is the one reranking and the keyword fallback use
+    let mut phrasings = args.query.iter().chain(&args.queries).cloned();
+    let mut request = SearchQuery {
+        query: phrasings.next().unwrap_or_default(),
+        after: after_date,
+        before: before_date,
+        limit: args.num_results
+            .or(args.pack_context.then_some(PACK_CONTEXT_RESULTS))
+            .or(defaults.num_results)
+            .unwrap_or(DEFAULT_NUM_RESULTS),
+        offset: args.offset.unwrap_or(0),
+        // Both need query text, so `--like` leaves the configured defaults off
+        hybrid: args.hybrid || (defaults.hybrid && args.like.is_none()),
+        rerank: args.rerank || (defaults.rerank && args.like.is_none()),
+        // Similar entries, not the chunks of one similar entry
+        group_by_doc: args.group_by_doc || defaults.group_by_doc || args.like.is_some(),
+        expand: args.expand.or(defaults.expand).unwrap_or(0),
+        full_doc: args.full_doc || defaults.full_doc,
+        chunk_text: args.pack_context,
+        snippets: args.snippets.unwrap_or(0),
+        recency_halflife,
+        min_score: args.min_score.or(defaults.min_score),
+        metric: args.metric,
+        nprobes: args.nprobes,
+        refine_factor: args.refine_factor,
+        prefilter: args.prefilter,
+        filter: args.filter.clone(),
+        tags: args.tags.clone(),
+        path_prefix: args.path_prefix.clone(),
+        path_glob: args.path_glob.clone(),
+        must_contain: args.must_contain.clone(),
+        must_match: args.must_match_regex.clone(),
+        keyword_fallback: (!args.no_keyword_fallback).then_some(args.keyword_fallback),
+        on_this_day: args.on_this_day.then(|| (today.month(), today.day())),
+        like: args.like.clone(),
+        alternate_queries: phrasings.collect(),
+    };
+    if let Some(cursor) = &args.cursor {
+        request.offset = cursor_offset(&request, cursor).unwrap_or_else(|e| fail(&args.format, format!("{:#}", e), None));
+    }
+    if let Some(file) = &args.queries_file {
+        return run_batch(global, &args, file, request).await;
+    }
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    let collections = if args.all_collections {
+        list_collections(&lance_path).await?
+    } else if index_exists(&lance_path, &global.table).await? {
+        vec![global.table.clone()]
+    } else {
+        Vec::new()
+    };
+    if collections.is_empty() && !args.allow_missing_index {
+        let error = if args.all_collections {
+            format!("No collections found at {}", lance_path)
+        } else {
+            format!("No table '{}' found at {}", global.table, lance_path)
+        };
+        fail(&args.format, error, Some(MISSING_INDEX_HINT));
+    }
+    if args.count {
+        return print_count(global, &args, &collections, &request).await;
+    }
+    let page = if collections.is_empty() {
+        SearchPage::default()
+    } else {
+        let searched = if args.all_collections {
+            search_collections(global, &collections, args.model.as_deref(), args.force, &request).await
+        } else {
+            run_search(global, &global.table, args.model.as_deref(), args.force, &request).await
+        };
+        match searched {
+            Ok(page) => page,
+            Err(e) => fail(&args.format, format!("{:#}", e), None),
+        }
+    };
+    if !collections.is_empty() && !args.no_history && defaults.history != Some(false) {
+        record_history(global, &args, &request, page.results.len());
+    }
+    let mut results = page.results;
+    let sort = args.sort.or(defaults.sort).unwrap_or_default();
+    if args.pack_context {
+        return print_packed(&args, &results, sort);
+    }
+    sort.apply(&mut results);
+    let shown_to = request.offset + results.len();
+    let next_cursor = (shown_to < page.total).then(|| page_cursor(&request, shown_to));
+    
+    // Output results
+    match args.format {
+        OutputFormat::Text => {
+            // Collected first, so a long list can go through the pager
+            let mut out = String::new();
+            if args.files_only {
+                for result in &results {
+                    writeln!(out, "{}", result.path.display())?;
+                }
+                page_output(&out, !args.no_pager)?;
+            } else {
+                let color = use_color(&args);
+                for (i, result) in results.iter().enumerate() {
+                    let collection = result.collection.as_deref().map(|c| format!("{} | ", c)).unwrap_or_default();
+                    let kind = if result.keyword_match { "Keyword match" } else { "Score" };
+                    writeln!(out, "\n{} {} | {}{} | {}: {}", 
+                        request.offset + i + 1,
+                        paint(&result.date.to_string(), DIM, color),
+                        collection,
+                        location(result),
+                        kind,
+                        paint(&format!("{:.3}", result.score), score_color(result), color),
+                    )?;
+                    if let Some(section) = &result.section {
+                        writeln!(out, "  {}", paint(&format!("§ {}", section), DIM, color))?;
+                    }
+                    if let Some(chunks) = result.matching_chunks {
+                        writeln!(out, "  {} matching chunk(s), aggregate score {:.3}", chunks, result.aggregate_score.unwrap_or(result.score))?;
+                    }
+                    match (&result.context, &result.snippets) {
+                        (Some(context), _) => writeln!(out, "{}", indent(context))?,
+                        (None, Some(snippets)) => {
+                            for snippet in snippets {
+                                writeln!(out, "  • {}", highlight(&snippet.text, color))?;
+                            }
+                        }
+                        (None, None) => writeln!(out, "  {}", highlight(&result.snippet, color))?,
+                    }
+                    
+                    if args.debug && let Some(meta) = &result.metadata {
+                        writeln!(out, "  Debug: {}", serde_json::to_string_pretty(meta)?)?;
+                    }
+                }
+                page_output(&out, !args.no_pager)?;
+                if next_cursor.is_some() {
+                    eprintln!(
+                        "\n📄 Showing {}-{} of {}; add --offset {} for more",
+                        request.offset + 1,
+                        shown_to,
+                        page.total,
+                        shown_to,
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::to_string_pretty(&JsonOutput {
+                results: &results,
+                total: page.total,
+                offset: request.offset,
+                next_cursor,
+            })?;
+            println!("{}", output);
+        }
+        OutputFormat::Ndjson => {
+            let mut stdout = std::io::stdout().lock();
+            for result in &results {
+                serde_json::to_writer(&mut stdout, result)?;
+                writeln!(stdout)?;
+            }
+        }
+        OutputFormat::Markdown => {
+            for result in &results {
+                println!("{}", markdown_result(result));
+            }
+        }
+        OutputFormat::Grep => {
+            let color = use_color(&args);
+            for result in &results {
+                for line in grep_lines(result, color) {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+    
+    if results.is_empty() {
+        if matches!(args.format, OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Grep) {
+            eprintln!("No results");
+        }
+        std::process::exit(EXIT_NO_RESULTS);
+    }
+    
+    Ok(())
+}
+
+/// Print how many results the search has across `collections`, exiting 1 if there are none
+async fn print_count(global: &GlobalArgs, args: &SearchArgs, collections: &[String], request: &SearchQuery) -> Result<()> {
+    let mut request = request.clone();
+    if request.query.is_empty() && request.like.is_none() {
+        if request.min_score.is_some() {
+            fail(&args.format, "--min-score needs a query to score against".to_string(), None);
+        }
+        // Nothing to rank or look up by keyword; the filters alone decide
+        request.hybrid = false;
+        request.keyword_fallback = None;
+    }
+
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    let mut count = 0;
+    for table in collections {
+        match count_collection(global, args, &lance_path, table, &request).await {
+            Ok(matched) => count += matched,
+            Err(e) => fail(&args.format, format!("{:#}", e), None),
+        }
+    }
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::json!({ "count": count })),
+        _ => println!("{}", count),
+    }
+    if count == 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Results of `request` in one collection, searching only when the filters alone can't tell
+async fn count_collection(
+    global: &GlobalArgs,
+    args: &SearchArgs,
+    lance_path: &str,
+    table: &str,
+    request: &SearchQuery,
+) -> Result<usize> {
+    let chunks = count_filtered(lance_path, table, request, false).await?;
+    if chunks == 0 {
+        return Ok(0);
+    }
+    if !count_needs_search(request) {
+        return if request.group_by_doc { count_filtered(lance_path, table, request, true).await } else { Ok(chunks) };
+    }
+    let page = run_search(global, table, args.model.as_deref(), args.force, &count_query(request, chunks)).await?;
+    Ok(page.total)
+}
+
+/// Log the search for `mdrag history`. A history that can't be written only warrants a warning
+fn record_history(global: &GlobalArgs, args: &SearchArgs, request: &SearchQuery, results: usize) {
+    let Some(path) = history::default_path() else {
+        return;
+    };
+    let query = match &args.like {
+        Some(path) => format!("--like {}", path),
+        None => std::iter::once(&request.query).chain(&request.alternate_queries).cloned().collect::<Vec<_>>().join(" | "),
+    };
+    let collection = if args.all_collections { "*".to_string() } else { global.table.clone() };
+    let entry = HistoryEntry { at: chrono::Utc::now(), query, collection, results };
+    if let Err(e) = history::append(&path, &entry) {
+        eprintln!("⚠️  Search history not updated: {:#}", e);
+    }
+}
+
+/// Options that pick the index rather than describe the search, and the saving options themselves;
+/// none of them is stored with a saved search
+const UNSAVED_FLAGS: &[&str] = &[
+    "-j", "--journal-dir", "-l", "--lance-dir", "--table", "--collection", "--backend", "--config", "--save", "--saved",
+];
+
+/// `args` with the saved search it names filled in, storing the search first if `--save` asks to
+fn resolve_saved(args: SearchArgs) -> Result<SearchArgs> {
+    if args.save.is_none() && args.saved.is_none() {
+        return Ok(args);
+    }
+    let path = SavedSearches::default_path()
+        .context("No home or config directory to keep saved searches in; set MDRAG_CONFIG_DIR")?;
+    let mut saved = SavedSearches::load(&path)?;
+    let typed = typed_search_args(&std::env::args().collect::<Vec<_>>());
+
+    let save = args.save.clone();
+    let (args, tokens) = match &args.saved {
+        Some(name) => {
+            let Some(search) = saved.get(name) else {
+                let names = saved.names();
+                let known = if names.is_empty() { "none yet".to_string() } else { names.join(", ") };
+                anyhow::bail!("No saved search '{}' (saved: {})", name, known);
+            };
+            // Typed options come last, so they win over saved ones
+            let tokens = [search.args.clone(), typed].concat();
+            let args = parse_search_args(&tokens).with_context(|| format!("Running saved search '{}'", name))?;
+            (args, tokens)
+        }
+        None => (args, typed),
+    };
+
+    if let Some(name) = save {
+        saved.insert(&name, tokens);
+        saved.write(&path)?;
+        eprintln!("💾 Saved search '{}' to {}", name, path.display());
+    }
+    Ok(args)
+}
+
+/// The arguments after `search` in `argv`, leaving out [`UNSAVED_FLAGS`] and their values
+fn typed_search_args(argv: &[String]) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut in_command = false;
+    let mut argv = argv.iter().skip(1);
+    while let Some(token) = argv.next() {
+        if UNSAVED_FLAGS.contains(&token.as_str()) {
+            argv.next();
+            continue;
+        }
+        // `--table=notes` or `-jjournal`
+        let attached = UNSAVED_FLAGS.iter().any(|flag| {
+            token.starts_with(&format!("{}=", flag)) || (flag.len() == 2 && token.len() > 2 && token.starts_with(flag))
+        });
+        if attached {
+            continue;
+        }
+        if in_command {
+            tokens.push(token.clone());
+        } else {
+            // The first word that isn't an option is the subcommand
+            in_command = !token.starts_with('-');
+        }
+    }
+    tokens
+}
+
+/// Parse `search` arguments, letting a later option override an earlier one
+fn parse_search_args(tokens: &[String]) -> Result<SearchArgs> {
+    let command = SearchArgs::augment_args(clap::Command::new("search")).args_override_self(true);
+    let matches = command.try_get_matches_from(std::iter::once("search").chain(tokens.iter().map(String::as_str)))?;
+    Ok(SearchArgs::from_arg_matches(&matches)?)
+}
+
+/// Print the results as one context block within the token budget
+fn print_packed(args: &SearchArgs, results: &[SearchResult], sort: SortOrder) -> Result<()> {
+    // Best results get the budget first; the order they are shown in comes after
+    let count_tokens = llm_token_counter();
+    let mut sources = pack_sources(results, args.max_tokens.unwrap_or(DEFAULT_PACK_TOKENS), &*count_tokens);
+    if sort == SortOrder::Date {
+        sources.sort_by_key(|source| source.date);
+        for (i, source) in sources.iter_mut().enumerate() {
+            source.number = i + 1;
+        }
+    }
+    let context = render_sources(&sources);
+    let tokens = count_tokens(&context);
+
+    if let OutputFormat::Json = args.format {
+        println!("{}", serde_json::to_string_pretty(&PackedOutput { context, tokens, sources: &sources })?);
+    } else {
+        println!("{}", context);
+        let trimmed = sources.iter().filter(|source| source.trimmed).count();
+        eprintln!(
+            "📦 Packed {} of {} results into {} tokens ({} trimmed)",
+            sources.len(),
+            results.len(),
+            tokens,
+            trimmed,
+        );
+    }
+
+    if sources.is_empty() {
+        if !matches!(args.format, OutputFormat::Json) {
+            eprintln!("No results");
+        }
+        std::process::exit(EXIT_NO_RESULTS);
+    }
+    Ok(())
+}
+
+/// Run each query of `file` with the same options and one loaded model
+async fn run_batch(global: &GlobalArgs, args: &SearchArgs, file: &Path, request: SearchQuery) -> Result<()> {
+    let sort = args.sort.or(global.config.search.sort).unwrap_or_default();
+    let text = if file == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?
+    };
+    let queries = batch_queries(&text);
+    if queries.is_empty() {
+        anyhow::bail!("No queries in {}", file.display());
+    }
+    
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    if !index_exists(&lance_path, &global.table).await? {
+        fail(&args.format, format!("No table '{}' found at {}", global.table, lance_path), Some(MISSING_INDEX_HINT));
+    }
+    let embedding_generator = load_search_model(&lance_path, &global.table, global.backend, args.model.as_deref(), args.force).await?;
+    let reranker = if request.rerank { Some(Reranker::new()?) } else { None };
+    let template = TemplateFilter::with_config(&global.config.template);
+    
+    let mut answered = Vec::new();
+    for query in queries {
+        let request = SearchQuery { query: query.clone(), ..request.clone() };
+        let mut results = search_index(&lance_path, &global.table, &embedding_generator, reranker.as_ref(), &template, &request)
+            .await
+            .with_context(|| format!("Searching for '{}'", query))?;
+        sort.apply(&mut results);
+        if let OutputFormat::Ndjson = args.format {
+            // Streamed, so a long batch shows progress
+            println!("{}", serde_json::to_string(&serde_json::json!({ "query": query, "results": results }))?);
+        } else {
+            answered.push((query, results));
+        }
+    }
+    if !matches!(args.format, OutputFormat::Ndjson) {
+        println!("{}", serde_json::to_string_pretty(&BatchOutput(&answered))?);
+    }
+    
+    Ok(())
+}
+
+/// Non-empty lines of a queries file, without duplicates; `#` starts a comment line
+fn batch_queries(text: &str) -> Vec<String> {
+    let mut queries: Vec<String> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() && !line.starts_with('#') && !queries.iter().any(|q| q == line) {
+            queries.push(line.to_string());
+        }
+    }
+    queries
+}
+
+/// Searches one collection many times: through the daemon when it runs, else with a model
+/// loaded once and kept (`mdrag tui`, `mdrag web`)
+pub struct Searcher {
+    lance_dir: PathBuf,
+    lance_path: String,
+    table: String,
+    backend: Backend,
+    template: TemplateFilter,
+    model: Option<EmbeddingGenerator>,
+}
+
+impl Searcher {
+    /// Loads the model right away unless a daemon socket exists, so its messages come before
+    /// an interface takes over the terminal
+    pub async fn new(global: &GlobalArgs) -> Result<Self> {
+        let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+        if !index_exists(&lance_path, &global.table).await? {
+            anyhow::bail!("No table '{}' found at {}. {}", global.table, lance_path, MISSING_INDEX_HINT);
+        }
+        let mut searcher = Self {
+            lance_dir: global.lance_dir.clone(),
+            lance_path,
+            table: global.table.clone(),
+            backend: global.backend,
+            template: TemplateFilter::with_config(&global.config.template),
+            model: None,
+        };
+        if !daemon::socket_path(&global.lance_dir, &global.table).exists() {
+            eprintln!("🧠 Loading embedding model...");
+            searcher.model = Some(searcher.load_model().await?);
+        }
+        Ok(searcher)
+    }
+
+    /// Reranking isn't offered, so a session never waits for the cross-encoder to load
+    pub async fn search(&mut self, request: &SearchQuery) -> Result<SearchPage> {
+        if self.model.is_none()
+            && let Some(page) = daemon::query(&self.lance_dir, &self.table, request).await?
+        {
+            return Ok(page);
+        }
+        let model = match self.model.take() {
+            Some(model) => model,
+            None => self.load_model().await?,
+        };
+        let request = SearchQuery { rerank: false, ..request.clone() };
+        let page = search_index_page(&self.lance_path, &self.table, &model, None, &self.template, &request).await;
+        self.model = Some(model);
+        page
+    }
+
+    async fn load_model(&self) -> Result<EmbeddingGenerator> {
+        load_search_model(&self.lance_path, &self.table, self.backend, None, false).await
+    }
+}
+
+/// Search one collection, through `mdrag daemon` when one is running
+pub async fn run_search(
+    global: &GlobalArgs,
+    table: &str,
+    model: Option<&str>,
+    force: bool,
+    request: &SearchQuery,
+) -> Result<SearchPage> {
+    // A running `mdrag daemon` already has the index's model loaded
+    if model.is_none() && let Some(page) = daemon::query(&global.lance_dir, table, request).await? {
+        return Ok(page);
+    }
+    
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    let embedding_generator = load_search_model(&lance_path, table, global.backend, model, force).await?;
+    let reranker = if request.rerank { Some(Reranker::new()?) } else { None };
+    let template = TemplateFilter::with_config(&global.config.template);
+    search_index_page(&lance_path, table, &embedding_generator, reranker.as_ref(), &template, request).await
+}
+
+/// Search each collection with its own model and merge the rankings into one. Each model is
+/// loaded once, however many collections were built with it
+async fn search_collections(
+    global: &GlobalArgs,
+    collections: &[String],
+    model: Option<&str>,
+    force: bool,
+    request: &SearchQuery,
+) -> Result<SearchPage> {
+    // The page can come from any mix of collections, so each contributes everything up to its end
+    let per_collection = SearchQuery { offset: 0, limit: request.offset + request.limit, ..request.clone() };
+    let lance_path = db_path(&global.lance_dir).to_string_lossy().to_string();
+    let db = lancedb::connect(&lance_path).execute().await?;
+    let reranker = if request.rerank { Some(Reranker::new()?) } else { None };
+    let template = TemplateFilter::with_config(&global.config.template);
+    let mut generators: HashMap<String, EmbeddingGenerator> = HashMap::new();
+    let mut ranked = Vec::new();
+    for collection in collections {
+        let page: Result<SearchPage> = async {
+            // A running `mdrag daemon` already has the collection's model loaded
+            if model.is_none() && let Some(page) = daemon::query(&global.lance_dir, collection, &per_collection).await? {
+                return Ok(page);
+            }
+            let table = db.open_table(collection).execute().await?;
+            let schema = table.schema().await?;
+            let model_id = model.unwrap_or(schema_model(&schema).unwrap_or(DEFAULT_MODEL));
+            let generator = match generators.entry(model_id.to_string()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(EmbeddingGenerator::load(global.backend, model_id)?),
+            };
+            if !force {
+                verify_index_model(&schema, generator)?;
+            }
+            search_table_page(&table, generator, reranker.as_ref(), &template, &per_collection).await
+        }
+        .await;
+        let page = page.with_context(|| format!("Searching collection '{}'", collection))?;
+        ranked.push((collection.clone(), page));
+    }
+    Ok(merge_collections(ranked, request.offset, request.limit))
+}
+
+/// `path:start-end`, so editors and terminals can jump to the match; just the path for old indexes
+fn location(result: &SearchResult) -> String {
+    match (result.start_line, result.end_line) {
+        (Some(start), Some(end)) if end > start => format!("{}:{}-{}", result.path.display(), start, end),
+        (Some(start), _) => format!("{}:{}", result.path.display(), start),
+        _ => result.path.display().to_string(),
+    }
+}
+
+/// Color on a terminal, unless `--no-color` or `NO_COLOR` (https://no-color.org) turns it off
+fn use_color(args: &SearchArgs) -> bool {
+    !args.no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+/// ANSI style for dates and sections
+const DIM: &str = "2";
+
+/// `text` in the ANSI style `code` when `color` is on
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() }
+}
+
+/// Green for strong matches, yellow for fair ones, red for weak ones; keyword matches have no
+/// similarity to grade, so they are cyan
+fn score_color(result: &SearchResult) -> &'static str {
+    match result.score {
+        _ if result.keyword_match => "36",
+        score if score >= 0.7 => "32",
+        score if score >= 0.5 => "33",
+        _ => "31",
+    }
+}
+
+/// Print `text`, through `$PAGER` (`less` if unset, none if empty) when stdout is a terminal the
+/// text doesn't fit on. `less` gets `LESS=FRX` unless the user set `LESS`, so it keeps the colors
+/// and quits by itself on a short page
+fn page_output(text: &str, allow_pager: bool) -> Result<()> {
+    let rows = terminal_size::terminal_size().map(|(_, terminal_size::Height(rows))| rows as usize);
+    let too_long = rows.is_some_and(|rows| text.lines().count() >= rows);
+    if allow_pager && too_long && std::io::stdout().is_terminal() {
+        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+        let mut words = pager.split_whitespace();
+        if let Some(program) = words.next() {
+            let mut command = std::process::Command::new(program);
+            command.args(words).stdin(std::process::Stdio::piped());
+            if std::env::var_os("LESS").is_none() {
+                command.env("LESS", "FRX");
+            }
+            // A pager that won't start just means printing directly
+            if let Ok(mut child) = command.spawn() {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // Quitting the pager early closes the pipe, which is fine
+                    let _ = stdin.write_all(text.as_bytes());
+                }
+                child.wait()?;
+                return Ok(());
+            }
+        }
+    }
+    print!("{}", text);
+    Ok(())
+}
+
+/// Show the snippet's `**term**` marks as bold yellow on a terminal; piped output keeps the markdown
+fn highlight(snippet: &str, color: bool) -> String {
+    if !color {
+        return snippet.to_string();
+    }
+    let mut colored = String::with_capacity(snippet.len());
+    for (i, part) in snippet.split(HIGHLIGHT).enumerate() {
+        if i > 0 {
+            colored.push_str(if i % 2 == 1 { "\x1b[1;33m" } else { "\x1b[0m" });
+        }
+        colored.push_str(part);
+    }
+    // A mark left open by the entry's own `**` mustn't color the rest of the terminal
+    if snippet.matches(HIGHLIGHT).count() % 2 == 1 {
+        colored.push_str("\x1b[0m");
+    }
+    colored
+}
+
+/// `- [path:lines](path#Lstart) — date · score · section` with the snippets blockquoted below
+fn markdown_result(result: &SearchResult) -> String {
+    let path = result.path.display().to_string();
+    let mut target = path.replace(' ', "%20");
+    if let Some(start) = result.start_line {
+        target.push_str(&format!("#L{}", start));
+    }
+    let kind = if result.keyword_match { "keyword match" } else { "score" };
+    let mut line = format!("- [{}]({}) — {} · {} {:.3}", location(result), target, result.date, kind, result.score);
+    if let Some(collection) = &result.collection {
+        line.push_str(&format!(" · {}", collection));
+    }
+    if let Some(section) = &result.section {
+        line.push_str(&format!(" · {}", section));
+    }
+
+    let passages: Vec<&str> = match (&result.context, &result.snippets) {
+        (Some(context), _) => vec![context.as_str()],
+        (None, Some(snippets)) => snippets.iter().map(|snippet| snippet.text.as_str()).collect(),
+        (None, None) => vec![result.snippet.as_str()],
+    };
+    for passage in passages {
+        // Indented under the list item; an empty `>` line keeps paragraphs in one quote
+        let quoted: Vec<String> = passage.lines().map(|l| format!("  > {}", l).trim_end().to_string()).collect();
+        line.push_str("\n\n");
+        line.push_str(&quoted.join("\n"));
+    }
+    line
+}
+
+/// `path:line:snippet`, one per snippet, each flattened to a single line. The line is the one in
+/// the chunk's range of the file holding the snippet's first matched term, else the chunk's first
+fn grep_lines(result: &SearchResult, color: bool) -> Vec<String> {
+    let passages: Vec<&str> = match &result.snippets {
+        Some(snippets) => snippets.iter().map(|snippet| snippet.text.as_str()).collect(),
+        None => vec![result.snippet.as_str()],
+    };
+    let file = result.start_line.and_then(|_| fs::read_to_string(&result.path).ok());
+    passages
+        .into_iter()
+        .map(|passage| {
+            let line = file
+                .as_deref()
+                .and_then(|text| term_line(text, passage, result))
+                .or(result.start_line)
+                .unwrap_or(1);
+            let text = passage.split_whitespace().collect::<Vec<_>>().join(" ");
+            // Piped output is for tools, which don't want the markdown marks
+            let text = if color { highlight(&text, true) } else { text.replace(HIGHLIGHT, "") };
+            format!("{}:{}:{}", result.path.display(), line, text)
+        })
+        .collect()
+}
+
+/// Line of `file`, within the result's chunk, on which the passage's first `**term**` occurs
+fn term_line(file: &str, passage: &str, result: &SearchResult) -> Option<usize> {
+    let term = passage.split(HIGHLIGHT).nth(1)?.to_lowercase();
+    let start = result.start_line?;
+    let end = result.end_line.unwrap_or(start).max(start);
+    file.lines()
+        .enumerate()
+        .skip(start.saturating_sub(1))
+        .take(end + 1 - start)
+        .find(|(_, line)| line.to_lowercase().contains(&term))
+        .map(|(i, _)| i + 1)
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("  {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Report a failed search on stdout (JSON) or stderr (text) and exit
+fn fail(format: &OutputFormat, error: String, hint: Option<&str>) -> ! {
+    match format {
+        OutputFormat::Json => {
+            // Scripts and MCP wrappers parse stdout, so the error goes there as well
+            let output = JsonError { error, hint };
+            println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+        }
+        OutputFormat::Ndjson => {
+            // A line of its own, so a consumer reading results line by line sees it too
+            let output = JsonError { error, hint };
+            println!("{}", serde_json::to_string(&output).unwrap_or_default());
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Grep => {
+            eprintln!("❌ {}", error);
+            if let Some(hint) = hint {
+                eprintln!("💡 {}", hint);
+            }
+        }
+    }
+    std::process::exit(EXIT_ERROR);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_json_error_shape() {
+        let error = JsonError { error: "No index found".to_string(), hint: Some(MISSING_INDEX_HINT) };
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["error"], "No index found");
+        assert_eq!(value["hint"], MISSING_INDEX_HINT);
+
+        let error = JsonError { error: "boom".to_string(), hint: None };
+        assert_eq!(serde_json::to_string(&error).unwrap(), r#"{"error":"boom"}"#);
+    }
+
+    #[test]
+    fn test_markdown_result_links_to_the_line() {
+        let result: SearchResult = serde_json::from_value(serde_json::json!({
+            "path": "journal/2025/07/21.md",
+            "date": "2025-07-21",
+            "score": 0.8123,
+            "snippet": "Met **Ana**.\n\nThen lunch.",
+            "section": "Evening",
+            "start_line": 12,
+            "end_line": 14,
+        })).unwrap();
+        assert_eq!(
+            markdown_result(&result),
+            "- [journal/2025/07/21.md:12-14](journal/2025/07/21.md#L12) — 2025-07-21 · score 0.812 · Evening\n\n  > Met **Ana**.\n  >\n  > Then lunch.",
+        );
+    }
+
+    #[test]
+    fn test_grep_line_points_at_the_matched_term() {
+        let result: SearchResult = serde_json::from_value(serde_json::json!({
+            "path": "journal/2025/07/21.md",
+            "date": "2025-07-21",
+            "score": 0.8,
+            "snippet": "Met **Ana**.\n\nThen lunch.",
+            "start_line": 3,
+            "end_line": 6,
+        })).unwrap();
+        let file = "---\ndate: 2025-07-21\n---\n# Monday\nLunch. Met ana.\nThen lunch.\nAna again";
+        assert_eq!(term_line(file, &result.snippet, &result), Some(5));
+        assert_eq!(term_line(file, "no marks", &result), None);
+
+        // The file isn't there, so the chunk's first line stands in
+        assert_eq!(grep_lines(&result, false), vec!["journal/2025/07/21.md:3:Met Ana. Then lunch."]);
+    }
+
+    #[test]
+    fn test_batch_queries_skip_blanks_comments_and_repeats() {
+        let queries = batch_queries("sleep\n\n# warm-up\n  burnout  \nsleep\n");
+        assert_eq!(queries, vec!["sleep", "burnout"]);
+        let output = serde_json::to_string(&BatchOutput(&[("b".to_string(), vec![]), ("a".to_string(), vec![])])).unwrap();
+        assert_eq!(output, r#"{"b":[],"a":[]}"#);
+    }
+
+    #[test]
+    fn test_saved_search_args() {
+        let argv: Vec<String> = ["mdrag", "--table", "notes", "search", "wins", "--during", "last week", "-jjournal", "--save", "weekly"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let typed = typed_search_args(&argv);
+        assert_eq!(typed, vec!["wins", "--during", "last week"]);
+
+        // A later -n overrides the saved one
+        let tokens: Vec<String> = [typed, vec!["-n".to_string(), "3".to_string(), "-n".to_string(), "5".to_string()]].concat();
+        let args = parse_search_args(&tokens).unwrap();
+        assert_eq!(args.query.as_deref(), Some("wins"));
+        assert_eq!(args.num_results, Some(5));
+    }
+
+    #[test]
+    fn test_highlight_colors_marked_terms() {
+        assert_eq!(highlight("met **Ana**", false), "met **Ana**");
+        assert_eq!(highlight("met **Ana**", true), "met \x1b[1;33mAna\x1b[0m");
+        assert_eq!(paint("2025-07-21", DIM, true), "\x1b[2m2025-07-21\x1b[0m");
+        assert_eq!(paint("2025-07-21", DIM, false), "2025-07-21");
+    }
+}
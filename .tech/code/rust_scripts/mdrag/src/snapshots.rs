@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use lancedb::table::OptimizeAction;
+use lancedb::Table;
+use serde::Serialize;
+
+use md_rag_core::lock::IndexLock;
+use md_rag_core::schema::db_path;
+
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct SnapshotsArgs {
+    #[command(subcommand)]
+    command: SnapshotsCommand,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum SnapshotsCommand {
+    /// List the versions of the collection that can be rolled back to
+    List(ListArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ListArgs {
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RollbackArgs {
+    /// Version to restore, from `mdrag snapshots list`
+    #[arg(long)]
+    version: u64,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VacuumArgs {
+    /// Versions to keep, counting back from the current one
+    #[arg(long, default_value_t = 10)]
+    keep_last: usize,
+}
+
+/// One version of a collection. Every write makes one, so an index pass leaves several
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    version: u64,
+    timestamp: DateTime<Utc>,
+    current: bool,
+}
+
+pub async fn list(global: &GlobalArgs, args: SnapshotsArgs) -> Result<()> {
+    let SnapshotsCommand::List(args) = args.command;
+    let table = open_collection(global).await?;
+    let snapshots = snapshots(&table).await?;
+
+    match args.format {
+        OutputFormat::Text => {
+            for snapshot in &snapshots {
+                let marker = if snapshot.current { "  ← current" } else { "" };
+                println!(
+                    "{:>6}  {}{}",
+                    snapshot.version,
+                    snapshot.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+                    marker,
+                );
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "snapshots": snapshots }))?);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn rollback(global: &GlobalArgs, args: RollbackArgs) -> Result<()> {
+    let _lock = lock(global)?;
+    let table = open_collection(global).await?;
+    if !snapshots(&table).await?.iter().any(|s| s.version == args.version) {
+        anyhow::bail!("No version {} of '{}'. See `mdrag snapshots list`.", args.version, global.table);
+    }
+
+    let now = restore_version(&table, args.version).await?;
+    println!("⏪ Restored '{}' to version {} (now version {})", global.table, args.version, now);
+
+    Ok(())
+}
+
+/// Write `version`'s state, schema included, as a new version, so the rollback can itself be
+/// undone; a version from before a rebuild brings back its model, metric and fields. Returns the
+/// new version
+async fn restore_version(table: &Table, version: u64) -> Result<u64> {
+    table.checkout(version).await?;
+    table.restore().await?;
+    Ok(table.version().await?)
+}
+
+pub async fn vacuum(global: &GlobalArgs, args: VacuumArgs) -> Result<()> {
+    if args.keep_last == 0 {
+        anyhow::bail!("--keep-last must be at least 1, the current version");
+    }
+    let _lock = lock(global)?;
+    let table = open_collection(global).await?;
+    let snapshots = snapshots(&table).await?;
+
+    let Some(oldest_kept) = oldest_kept(&snapshots, args.keep_last) else {
+        println!("✨ Nothing to remove: '{}' has {} versions", global.table, snapshots.len());
+        return Ok(());
+    };
+    // Pruning goes by age; the extra second keeps the oldest wanted version however long this took
+    let older_than = Utc::now() - oldest_kept + chrono::Duration::seconds(1);
+    let stats = table
+        .optimize(OptimizeAction::Prune {
+            older_than: Some(older_than),
+            delete_unverified: None,
+            error_if_tagged_old_versions: None,
+        })
+        .await?;
+
+    let (versions, bytes) = stats
+        .prune
+        .map_or((0, 0), |removed| (removed.old_versions, removed.bytes_removed));
+    println!("🧹 Removed {} old versions of '{}', freeing {} bytes", versions, global.table, bytes);
+
+    Ok(())
+}
+
+/// Timestamp of the oldest version to keep, or `None` if there are no more than `keep_last`
+fn oldest_kept(snapshots: &[Snapshot], keep_last: usize) -> Option<DateTime<Utc>> {
+    if snapshots.len() <= keep_last {
+        return None;
+    }
+    let mut timestamps: Vec<DateTime<Utc>> = snapshots.iter().map(|s| s.timestamp).collect();
+    timestamps.sort();
+    Some(timestamps[timestamps.len() - keep_last])
+}
+
+async fn open_collection(global: &GlobalArgs) -> Result<Table> {
+    let lance_path = db_path(&global.lance_dir);
+    if !lance_path.exists() {
+        anyhow::bail!("No index at {}. Run `mdrag index` first.", lance_path.display());
+    }
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    db.open_table(&global.table)
+        .execute()
+        .await
+        .with_context(|| format!("No collection '{}'. Run `mdrag index` to create it", global.table))
+}
+
+/// Versions of the table, oldest first
+async fn snapshots(table: &Table) -> Result<Vec<Snapshot>> {
+    let current = table.version().await?;
+    let mut snapshots: Vec<Snapshot> = table
+        .list_versions()
+        .await?
+        .into_iter()
+        .map(|v| Snapshot { version: v.version, timestamp: v.timestamp, current: v.version == current })
+        .collect();
+    snapshots.sort_by_key(|s| s.version);
+    Ok(snapshots)
+}
+
+/// Rollbacks and vacuums rewrite the table, so they wait their turn like index passes
+fn lock(global: &GlobalArgs) -> Result<IndexLock> {
+    IndexLock::try_acquire(&global.lance_dir)?.map_err(|holder| {
+        anyhow::anyhow!("Another mdrag run ({}) is writing to this index; try again when it is done", holder)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use lancedb::query::ExecutableQuery;
+    use md_rag_core::metric::Metric;
+    use md_rag_core::schema::{documents_schema, rebuild_table_name, schema_dimension, schema_model};
+
+    use crate::index::swap_in_rebuild;
+
+    #[test]
+    fn test_oldest_kept() {
+        let at = |minute| Utc.with_ymd_and_hms(2025, 7, 21, 12, minute, 0).unwrap();
+        let snapshots: Vec<Snapshot> = (1..=5)
+            .map(|v| Snapshot { version: v, timestamp: at(v as u32), current: v == 5 })
+            .collect();
+        assert_eq!(oldest_kept(&snapshots, 2), Some(at(4)));
+        assert_eq!(oldest_kept(&snapshots, 5), None);
+    }
+    #[tokio::test]
+    async fn test_rollback_across_a_model_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = lancedb::connect(tmp.path().to_str().unwrap()).execute().await.unwrap();
+        let schema = |model, dim| documents_schema(model, dim, true, Metric::Cosine, &[], false, false);
+        db.create_empty_table("documents", schema("model-a", 2)).execute().await.unwrap();
+        db.create_empty_table(rebuild_table_name("documents"), schema("model-b", 3)).execute().await.unwrap();
+
+        let table = swap_in_rebuild(&db, "documents", None, true).await.unwrap();
+        assert_eq!(schema_model(&table.schema().await.unwrap()), Some("model-b"));
+        let versions = snapshots(&table).await.unwrap();
+        assert!(versions.len() >= 2);
+
+        restore_version(&table, versions[0].version).await.unwrap();
+        let restored = table.schema().await.unwrap();
+        assert_eq!((schema_model(&restored), schema_dimension(&restored)), (Some("model-a"), Some(2)));
+        assert_eq!(db.table_names().execute().await.unwrap(), vec!["documents"]);
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use arrow::array::{Array, Int32Array};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use serde::Serialize;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use md_rag_core::embeddings::DEFAULT_MODEL;
+use md_rag_core::metric::Metric;
+use md_rag_core::schema::{
+    days_to_date, db_path, schema_dimension, schema_encrypted, schema_metadata_fields, schema_metric, schema_model,
+    table_dir,
+};
+
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct StatusArgs {
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// What is in a collection and how it was built
+#[derive(Debug, Serialize)]
+struct IndexStatus {
+    collection: String,
+    documents: usize,
+    chunks: usize,
+    /// Earliest and latest entry dates
+    first_date: Option<NaiveDate>,
+    last_date: Option<NaiveDate>,
+    model: String,
+    dimension: Option<usize>,
+    metric: String,
+    metadata_fields: Vec<String>,
+    /// Whether chunk text is stored encrypted
+    encrypted: bool,
+    /// Bytes the collection takes on disk, including old versions not yet cleaned up
+    size_bytes: u64,
+    /// When the collection's files were last written
+    last_indexed: Option<DateTime<Utc>>,
+    /// Kind of vector index on the embeddings, if `mdrag index --build-ann` built one
+    ann_index: Option<String>,
+}
+
+pub async fn run(global: &GlobalArgs, args: StatusArgs) -> Result<()> {
+    let lance_path = db_path(&global.lance_dir);
+    if !lance_path.exists() {
+        anyhow::bail!("No index at {}. Run `mdrag index` first.", lance_path.display());
+    }
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    let table = db
+        .open_table(&global.table)
+        .execute()
+        .await
+        .with_context(|| format!("No collection '{}'. Run `mdrag index` to create it", global.table))?;
+
+    let schema = table.schema().await?;
+    let dates = entry_dates(&table).await?;
+    let ann_index = table
+        .list_indices()
+        .await?
+        .into_iter()
+        .find(|index| index.columns.iter().any(|column| column == "embedding"))
+        .map(|index| index.index_type.to_string());
+    let (size_bytes, last_indexed) = disk_usage(&table_dir(&lance_path, &global.table));
+
+    let status = IndexStatus {
+        collection: global.table.clone(),
+        documents: dates.len(),
+        chunks: table.count_rows(None).await?,
+        first_date: dates.iter().min().copied(),
+        last_date: dates.iter().max().copied(),
+        // Tables from before models and metrics were configurable used the defaults of the time
+        model: schema_model(&schema).unwrap_or(DEFAULT_MODEL).to_string(),
+        dimension: schema_dimension(&schema),
+        metric: schema_metric(&schema).unwrap_or(Metric::L2).to_string(),
+        metadata_fields: schema_metadata_fields(&schema).into_iter().map(|field| field.name).collect(),
+        encrypted: schema_encrypted(&schema),
+        size_bytes,
+        last_indexed,
+        ann_index,
+    };
+
+    match args.format {
+        OutputFormat::Text => print_status(&status),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&status)?),
+    }
+
+    Ok(())
+}
+
+fn print_status(status: &IndexStatus) {
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+    println!("🗂️  Collection:   {}", status.collection);
+    println!("📄 Documents:    {}", status.documents);
+    println!("🧩 Chunks:       {}", status.chunks);
+    println!(
+        "📅 Dates:        {} to {}",
+        or_none(status.first_date.map(|d| d.to_string())),
+        or_none(status.last_date.map(|d| d.to_string())),
+    );
+    println!(
+        "🤖 Model:        {} ({} dimensions, {})",
+        status.model,
+        or_none(status.dimension.map(|d| d.to_string())),
+        status.metric,
+    );
+    if !status.metadata_fields.is_empty() {
+        println!("🏷️  Fields:       {}", status.metadata_fields.join(", "));
+    }
+    if status.encrypted {
+        println!("🔐 Content:      encrypted (AES-256-GCM)");
+    }
+    println!("💾 Size on disk: {}", human_size(status.size_bytes));
+    println!(
+        "🕒 Last indexed: {}",
+        or_none(status.last_indexed.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())),
+    );
+    println!("🧭 ANN index:    {}", status.ann_index.as_deref().unwrap_or("none (exhaustive search)"));
+}
+
+/// Date of every indexed entry, one per entry
+async fn entry_dates(table: &lancedb::Table) -> Result<Vec<NaiveDate>> {
+    // Every chunk of an entry carries the same date, so the first chunk is enough
+    let batches: Vec<RecordBatch> = table
+        .query()
+        .select(Select::columns(&["date"]))
+        .only_if("chunk_index = 0")
+        .execute()
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut dates = Vec::new();
+    for batch in batches {
+        let column = batch.column_by_name("date")
+            .and_then(|c| c.as_any().downcast_ref::<Int32Array>())
+            .ok_or_else(|| anyhow::anyhow!("Missing date column"))?;
+        dates.extend((0..column.len()).map(|i| days_to_date(column.value(i))));
+    }
+    Ok(dates)
+}
+
+/// Total size of the files under `dir` and the time the newest one was written
+fn disk_usage(dir: &Path) -> (u64, Option<DateTime<Utc>>) {
+    let mut size = 0;
+    let mut newest = None;
+    for metadata in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()).filter_map(|e| e.metadata().ok()) {
+        if metadata.is_file() {
+            size += metadata.len();
+            newest = newest.max(metadata.modified().ok().map(DateTime::<Utc>::from));
+        }
+    }
+    (size, newest)
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
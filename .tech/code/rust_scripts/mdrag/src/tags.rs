@@ -0,0 +1,106 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct TagsArgs {
+    #[command(subcommand)]
+    command: TagsCommand,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum TagsCommand {
+    /// List every tag in the journal with the number of entries carrying it
+    List(ListArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ListArgs {
+    /// Only count entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct TagCount {
+    tag: String,
+    /// Entries carrying the tag
+    count: usize,
+}
+
+pub fn run(global: &GlobalArgs, args: TagsArgs) -> Result<()> {
+    match args.command {
+        TagsCommand::List(args) => list(global, args),
+    }
+}
+
+fn list(global: &GlobalArgs, args: ListArgs) -> Result<()> {
+    // Read straight from the journal, so this works without an index
+    let dates = global.config.entry_dates()?;
+    let mut documents = Vec::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        documents.extend(scan_journal_directory(dir, &paths, args.since.as_deref(), &dates, default_jobs(), false, &|_| {})?);
+    }
+    let counts = count_tags(documents.iter().map(|doc| doc.tags.as_slice()));
+
+    match args.format {
+        OutputFormat::Text => {
+            for TagCount { tag, count } in &counts {
+                println!("{:>5}  {}", count, tag);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "tags": counts }))?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Entries per tag, most used first and alphabetical among equals
+fn count_tags<'a>(entries: impl Iterator<Item = &'a [String]>) -> Vec<TagCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tags in entries {
+        for tag in tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount { tag: tag.to_string(), count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_tags() {
+        let entries = [
+            vec!["rust".to_string(), "work".to_string()],
+            vec!["health".to_string(), "work".to_string()],
+        ];
+        let counts = count_tags(entries.iter().map(Vec::as_slice));
+        let summary: Vec<(&str, usize)> = counts.iter().map(|c| (c.tag.as_str(), c.count)).collect();
+        assert_eq!(summary, vec![("work", 2), ("health", 1), ("rust", 1)]);
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+use md_rag_core::schema::days_to_date;
+use md_rag_core::tasks::{extract_tasks, Task};
+
+use crate::frontmatter::GroupBy;
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct TasksArgs {
+    /// Only entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// List completed tasks too
+    #[arg(long)]
+    all: bool,
+
+    /// Instead of the list, the share of tasks completed per week, month or year
+    #[arg(long, value_enum, value_name = "PERIOD", conflicts_with = "carry_forward")]
+    completion: Option<GroupBy>,
+
+    /// Instead of the list, the tasks left open in the last N days before today, as `- [ ]` lines
+    /// to paste into today's entry. Each is listed once, and not at all if it was done later
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "since")]
+    carry_forward: Option<u32>,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// The tasks of one entry
+#[derive(Debug, Serialize)]
+struct EntryTasks {
+    date: NaiveDate,
+    path: String,
+    tasks: Vec<Task>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Completion {
+    period: String,
+    start: NaiveDate,
+    done: usize,
+    open: usize,
+    /// Done over all tasks
+    rate: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct CarriedTask {
+    text: String,
+    /// Entry it was first left open in
+    since: NaiveDate,
+    path: String,
+}
+
+pub fn run(global: &GlobalArgs, args: TasksArgs) -> Result<()> {
+    let today = Local::now().date_naive();
+    let since = match args.carry_forward {
+        Some(days) => Some((today - Duration::days(days as i64)).to_string()),
+        None => args.since.clone(),
+    };
+
+    // Read straight from the journal, so this works without an index
+    let dates = global.config.entry_dates()?;
+    let mut entries = Vec::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        for doc in scan_journal_directory(dir, &paths, since.as_deref(), &dates, default_jobs(), false, &|_| {})? {
+            let mut tasks = extract_tasks(&doc.content);
+            for task in &mut tasks {
+                task.line += doc.first_line - 1;
+            }
+            entries.push(EntryTasks { date: days_to_date(doc.date), path: doc.path, tasks });
+        }
+    }
+    entries.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.path.cmp(&b.path)));
+
+    if let Some(period) = args.completion {
+        return print_completion(&completion(&entries, period), &args.format);
+    }
+    if args.carry_forward.is_some() {
+        entries.retain(|entry| entry.date < today);
+        return print_carried(&carry_forward(&entries), &args.format);
+    }
+
+    for entry in &mut entries {
+        entry.tasks.retain(|task| args.all || !task.done);
+    }
+    entries.retain(|entry| !entry.tasks.is_empty());
+    match args.format {
+        OutputFormat::Text => {
+            for entry in &entries {
+                println!("{}  {}", entry.date, entry.path);
+                let mut section = None;
+                for task in &entry.tasks {
+                    if section != Some(&task.section) && !task.section.is_empty() {
+                        println!("  {}", task.section);
+                    }
+                    section = Some(&task.section);
+                    println!("    - [{}] {} (line {})", if task.done { "x" } else { " " }, task.text, task.line);
+                }
+            }
+            let open: usize = entries.iter().map(|entry| entry.tasks.iter().filter(|task| !task.done).count()).sum();
+            eprintln!("📋 {} open tasks in {} entries", open, entries.len());
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "entries": entries }))?);
+        }
+    }
+    Ok(())
+}
+
+/// Done and open tasks per period, oldest first; periods without tasks are left out
+fn completion(entries: &[EntryTasks], period: GroupBy) -> Vec<Completion> {
+    let mut buckets: BTreeMap<NaiveDate, (String, usize, usize)> = BTreeMap::new();
+    for entry in entries {
+        let (label, start) = period.bucket(entry.date);
+        let bucket = buckets.entry(start).or_insert_with(|| (label, 0, 0));
+        for task in &entry.tasks {
+            if task.done {
+                bucket.1 += 1;
+            } else {
+                bucket.2 += 1;
+            }
+        }
+    }
+    buckets
+        .into_iter()
+        .filter(|(_, (_, done, open))| done + open > 0)
+        .map(|(start, (period, done, open))| Completion { period, start, done, open, rate: done as f64 / (done + open) as f64 })
+        .collect()
+}
+
+/// Tasks still open at the end of `entries` (in date order), first-seen order. Tasks match by
+/// text, ignoring case, so one copied from day to day is listed once and one ticked off on a
+/// later day is dropped
+fn carry_forward(entries: &[EntryTasks]) -> Vec<CarriedTask> {
+    let mut carried: Vec<Option<CarriedTask>> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        for task in entry.tasks.iter().filter(|task| !task.text.is_empty()) {
+            let key = task.text.to_lowercase();
+            match positions.get(&key) {
+                Some(&i) if task.done => carried[i] = None,
+                Some(&i) if carried[i].is_none() => {
+                    carried[i] = Some(CarriedTask { text: task.text.clone(), since: entry.date, path: entry.path.clone() });
+                }
+                Some(_) => {}
+                None if task.done => {}
+                None => {
+                    positions.insert(key, carried.len());
+                    carried.push(Some(CarriedTask { text: task.text.clone(), since: entry.date, path: entry.path.clone() }));
+                }
+            }
+        }
+    }
+    carried.into_iter().flatten().collect()
+}
+
+fn print_completion(completion: &[Completion], format: &OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            println!("{:<10}  {:>5}  {:>5}  {:>5}", "period", "done", "total", "rate");
+            for row in completion {
+                println!("{:<10}  {:>5}  {:>5}  {:>4.0}%", row.period, row.done, row.done + row.open, row.rate * 100.0);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "completion": completion }))?);
+        }
+    }
+    Ok(())
+}
+
+fn print_carried(carried: &[CarriedTask], format: &OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for task in carried {
+                println!("- [ ] {}", task.text);
+            }
+            eprintln!("📋 {} unfinished tasks to carry forward", carried.len());
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "carry_forward": carried }))?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_and_carry_forward() {
+        let entry = |date: &str, body: &str| EntryTasks { date: date.parse().unwrap(), path: format!("{}.md", date), tasks: extract_tasks(body) };
+        let entries = [
+            entry("2025-07-20", "- [ ] Call Ana\n- [x] Run\n- [ ] Fix the bike\n"),
+            entry("2025-07-21", "- [ ] call ana\n- [x] Fix the bike\n- [ ] Book flights\n"),
+            entry("2025-08-01", "- [x] Book flights\n- [x] Read\n"),
+        ];
+
+        let months = completion(&entries, GroupBy::Month);
+        let rows: Vec<_> = months.iter().map(|c| (c.period.as_str(), c.done, c.open)).collect();
+        assert_eq!(rows, vec![("2025-07", 2, 4), ("2025-08", 2, 0)]);
+        assert_eq!(months[1].rate, 1.0);
+
+        let carried: Vec<_> = carry_forward(&entries[..2]).into_iter().map(|t| (t.text, t.since.to_string())).collect();
+        assert_eq!(carried, vec![("Call Ana".to_string(), "2025-07-20".to_string()), ("Book flights".to_string(), "2025-07-21".to_string())]);
+        assert_eq!(carry_forward(&entries).len(), 1);
+    }
+}
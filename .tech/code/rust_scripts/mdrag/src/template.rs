@@ -0,0 +1,67 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+use md_rag_core::config::TemplatePatterns;
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+use md_rag_core::template_filter::common_lines;
+
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct TemplateArgs {
+    #[command(subcommand)]
+    command: TemplateCommand,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum TemplateCommand {
+    /// Print the lines most entries share verbatim as a named template for `[template] file`
+    Learn(LearnArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct LearnArgs {
+    /// Share of entries, above 0 and up to 1, a line has to appear in
+    #[arg(long, default_value_t = 0.5)]
+    min_share: f64,
+
+    /// Only entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Name of the template in the output
+    #[arg(long, default_value = "learned")]
+    name: String,
+}
+
+pub fn run(global: &GlobalArgs, args: TemplateArgs) -> Result<()> {
+    let TemplateCommand::Learn(args) = args.command;
+    if !(args.min_share > 0.0 && args.min_share <= 1.0) {
+        bail!("--min-share must be above 0 and at most 1, not {}", args.min_share);
+    }
+
+    // Learn from the raw entries, so the result doesn't depend on what's stripped already
+    let dates = global.config.entry_dates()?;
+    let mut bodies = Vec::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        for doc in scan_journal_directory(dir, &paths, args.since.as_deref(), &dates, default_jobs(), false, &|_| {})? {
+            bodies.push(doc.content);
+        }
+    }
+
+    let lines = common_lines(bodies.iter().map(|body| &**body), args.min_share);
+    eprintln!(
+        "🧩 {} lines appear in at least {:.0}% of {} entries",
+        lines.len(),
+        args.min_share * 100.0,
+        bodies.len(),
+    );
+    for (line, count) in &lines {
+        eprintln!("   {:>5}  {}", count, line);
+    }
+
+    let patterns = TemplatePatterns { boilerplate_lines: lines.into_iter().map(|(line, _)| line).collect(), ..Default::default() };
+    print!("{}", serde_yaml::to_string(&BTreeMap::from([(args.name, patterns)]))?);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use arrow::record_batch::RecordBatch;
+use futures::TryStreamExt;
+use lancedb::database::CreateTableMode;
+use lancedb::query::ExecutableQuery;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use md_rag_core::schema::{db_path, schema_model};
+
+use crate::GlobalArgs;
+use crate::index::{build_scalar_indexes, is_current_format, lock_index, recover_interrupted_swap};
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// File format
+    #[arg(long, default_value = "parquet", value_enum)]
+    format: ExportFormat,
+
+    /// File to write
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum ExportFormat {
+    /// One Parquet file holding every chunk with its embedding, plus the index's model and metric
+    Parquet,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Parquet file written by `mdrag export`
+    input: PathBuf,
+
+    /// Replace the collection if it already exists
+    #[arg(long)]
+    replace: bool,
+}
+
+/// Write every chunk of the collection, embeddings included, to a file
+pub async fn export(global: &GlobalArgs, args: ExportArgs) -> Result<()> {
+    let ExportFormat::Parquet = args.format;
+    let lance_path = db_path(&global.lance_dir);
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    let table = db
+        .open_table(&global.table)
+        .execute()
+        .await
+        .with_context(|| format!("No collection '{}' to export", global.table))?;
+
+    // The table schema carries the model, dimension and metric; the writer stores it in the file
+    let schema = table.schema().await?;
+    let file = File::create(&args.output)
+        .with_context(|| format!("Failed to create {}", args.output.display()))?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+
+    let mut rows = 0;
+    let mut batches = table.query().execute().await?;
+    while let Some(batch) = batches.try_next().await? {
+        rows += batch.num_rows();
+        writer.write(&RecordBatch::try_new(schema.clone(), batch.columns().to_vec())?)?;
+    }
+    writer.close()?;
+
+    println!("📦 Exported {} chunks of '{}' to {}", rows, global.table, args.output.display());
+    Ok(())
+}
+
+/// Load an exported collection as is, without embedding anything
+pub async fn import(global: &GlobalArgs, args: ImportArgs) -> Result<()> {
+    let file = File::open(&args.input)
+        .with_context(|| format!("Failed to open {}", args.input.display()))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .with_context(|| format!("{} is not a Parquet file", args.input.display()))?;
+    let schema = reader.schema().clone();
+    if schema.field_with_name("embedding").is_err() || !is_current_format(&schema) {
+        anyhow::bail!("{} is not an export from this version of mdrag", args.input.display());
+    }
+    let reader = reader.build()?;
+
+    let lance_path = db_path(&global.lance_dir);
+    fs::create_dir_all(&global.lance_dir)?;
+    let _lock = lock_index(&global.lance_dir, false, false).await?;
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    recover_interrupted_swap(&db, &lance_path, &global.table, false).await?;
+
+    // An existing collection keeps serving searches until the import is committed as its next
+    // version, which `mdrag rollback` can undo
+    let exists = db.table_names().execute().await?.contains(&global.table);
+    if exists && !args.replace {
+        anyhow::bail!("Collection '{}' already exists. Pass --replace to overwrite it.", global.table);
+    }
+    let mode = if exists { CreateTableMode::Overwrite } else { CreateTableMode::Create };
+
+    let table = db.create_table(&global.table, reader).mode(mode).execute().await?;
+    build_scalar_indexes(&table, false).await?;
+    let rows = table.count_rows(None).await?;
+
+    println!(
+        "📥 Imported {} chunks embedded with {} into '{}'",
+        rows,
+        schema_model(&schema).unwrap_or("an unknown model"),
+        global.table,
+    );
+    Ok(())
+}
@@ -0,0 +1,423 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::frontmatter::split_frontmatter;
+use md_rag_core::search::{SearchQuery, SearchResult};
+use md_rag_core::snippet::{matched_words, HIGHLIGHT};
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::search::Searcher;
+use crate::GlobalArgs;
+
+#[derive(clap::Args, Debug)]
+pub struct TuiArgs {
+    /// Query to start with; without one the query box is open for typing
+    query: Option<String>,
+
+    /// Results to list [default: 50]
+    #[arg(short, long)]
+    num_results: Option<usize>,
+
+    /// Only entries with this tag; repeat to require several (`t` edits them)
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Only entries within this period, e.g. "last month" (`d` edits it)
+    #[arg(long)]
+    during: Option<String>,
+
+    /// Start with hybrid search on (`h` toggles it)
+    #[arg(long)]
+    hybrid: bool,
+
+    /// Start with one result per entry (`g` toggles it)
+    #[arg(long)]
+    group_by_doc: bool,
+}
+
+/// Results listed without `-n`, more than `mdrag search` shows since the list scrolls
+const DEFAULT_NUM_RESULTS: usize = 50;
+
+/// Lines of the entry kept above its first matched term when the preview scrolls to it
+const PREVIEW_LEAD: usize = 3;
+
+const HELP: &str = "/ query · t tags · d dates · h hybrid · g by entry · ↑↓ select · PgUp/PgDn preview · ⏎ open · q quit";
+
+/// What typed keys go to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Browse,
+    Edit(Field),
+}
+
+/// A search setting edited in the input line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Query,
+    /// Comma-separated
+    Tags,
+    During,
+}
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::Query => "Query",
+            Field::Tags => "Tags (comma-separated)",
+            Field::During => "Dates (e.g. last month, 2024; empty for all)",
+        }
+    }
+}
+
+struct App {
+    query: String,
+    tags: Vec<String>,
+    during: Option<String>,
+    hybrid: bool,
+    group_by_doc: bool,
+    limit: usize,
+    results: Vec<SearchResult>,
+    total: usize,
+    list: ListState,
+    /// The cleaned entry of the selected result, split into lines
+    preview: Vec<String>,
+    scroll: usize,
+    mode: Mode,
+    input: String,
+    status: String,
+    filter: TemplateFilter,
+}
+
+/// Browse search results in the terminal: a query line, the result list and a preview of the selected entry
+pub async fn run(global: &GlobalArgs, args: TuiArgs) -> Result<()> {
+    let mut searcher = Searcher::new(global).await?;
+
+    let defaults = &global.config.search;
+    let mut app = App {
+        query: args.query.clone().unwrap_or_default(),
+        tags: args.tags,
+        during: args.during,
+        hybrid: args.hybrid || defaults.hybrid,
+        group_by_doc: args.group_by_doc || defaults.group_by_doc,
+        limit: args.num_results.unwrap_or(DEFAULT_NUM_RESULTS),
+        results: Vec::new(),
+        total: 0,
+        list: ListState::default(),
+        preview: Vec::new(),
+        scroll: 0,
+        mode: if args.query.is_some() { Mode::Browse } else { Mode::Edit(Field::Query) },
+        input: String::new(),
+        status: String::new(),
+        filter: TemplateFilter::with_config(&global.config.template),
+    };
+
+    let mut terminal = ratatui::init();
+    let outcome = app.main_loop(&mut terminal, &mut searcher).await;
+    ratatui::restore();
+    outcome
+}
+
+impl App {
+    async fn main_loop(&mut self, terminal: &mut DefaultTerminal, searcher: &mut Searcher) -> Result<()> {
+        if self.mode == Mode::Browse {
+            self.search(terminal, searcher).await?;
+        }
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.mode {
+                Mode::Edit(field) => {
+                    if self.edit(field, key) {
+                        self.search(terminal, searcher).await?;
+                    }
+                }
+                Mode::Browse => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    KeyCode::Char('/') | KeyCode::Char('i') => self.start_edit(Field::Query),
+                    KeyCode::Char('t') => self.start_edit(Field::Tags),
+                    KeyCode::Char('d') => self.start_edit(Field::During),
+                    KeyCode::Char('h') => {
+                        self.hybrid = !self.hybrid;
+                        self.search(terminal, searcher).await?;
+                    }
+                    KeyCode::Char('g') => {
+                        self.group_by_doc = !self.group_by_doc;
+                        self.search(terminal, searcher).await?;
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => self.select(self.list.selected().map_or(0, |i| i + 1)),
+                    KeyCode::Up | KeyCode::Char('k') => self.select(self.list.selected().map_or(0, |i| i.saturating_sub(1))),
+                    KeyCode::PageDown | KeyCode::Char(' ') => self.scroll = self.scroll.saturating_add(10),
+                    KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                    KeyCode::Enter | KeyCode::Char('o') => self.open(terminal)?,
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn start_edit(&mut self, field: Field) {
+        self.input = match field {
+            Field::Query => self.query.clone(),
+            Field::Tags => self.tags.join(", "),
+            Field::During => self.during.clone().unwrap_or_default(),
+        };
+        self.mode = Mode::Edit(field);
+    }
+
+    /// Apply a key to the input line; true once an edit is confirmed and should be searched
+    fn edit(&mut self, field: Field, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Enter => {
+                let input = self.input.trim().to_string();
+                match field {
+                    Field::Query => self.query = input,
+                    Field::Tags => {
+                        self.tags = input.split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect()
+                    }
+                    Field::During => self.during = (!input.is_empty()).then_some(input),
+                }
+                self.mode = Mode::Browse;
+                true
+            }
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                false
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                false
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.clear();
+                false
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Run the search for the current query and filters. Bad input shows in the status line
+    async fn search(&mut self, terminal: &mut DefaultTerminal, searcher: &mut Searcher) -> Result<()> {
+        if self.query.trim().is_empty() {
+            self.status = "Type a query with /".to_string();
+            return Ok(());
+        }
+        let (after, before) = match self.during.as_deref().map(|during| parse_date_range(during, Local::now().date_naive())) {
+            Some(Ok((start, end))) => (Some(start), Some(end)),
+            Some(Err(e)) => {
+                self.status = format!("Dates: {:#}", e);
+                return Ok(());
+            }
+            None => (None, None),
+        };
+
+        self.status = "Searching...".to_string();
+        terminal.draw(|frame| self.render(frame))?;
+        let request = SearchQuery {
+            query: self.query.clone(),
+            after,
+            before,
+            limit: self.limit,
+            hybrid: self.hybrid,
+            group_by_doc: self.group_by_doc,
+            tags: self.tags.clone(),
+            ..Default::default()
+        };
+        match searcher.search(&request).await {
+            Ok(page) => {
+                self.status = format!("{} of {} results", page.results.len(), page.total);
+                self.results = page.results;
+                self.total = page.total;
+            }
+            Err(e) => {
+                self.status = format!("Search failed: {:#}", e);
+                self.results.clear();
+                self.total = 0;
+            }
+        }
+        self.list = ListState::default();
+        self.select(0);
+        Ok(())
+    }
+
+    /// Select a result, clamped to the list, and preview its entry scrolled to the match
+    fn select(&mut self, index: usize) {
+        let Some(last) = self.results.len().checked_sub(1) else {
+            self.preview.clear();
+            return;
+        };
+        let index = index.min(last);
+        if self.list.selected() == Some(index) {
+            return;
+        }
+        self.list.select(Some(index));
+        let result = &self.results[index];
+        self.preview = match fs::read_to_string(&result.path) {
+            Ok(content) => self.filter.clean_content(split_frontmatter(&content).1).lines().map(String::from).collect(),
+            Err(e) => vec![format!("Cannot read {}: {}", result.path.display(), e)],
+        };
+        self.scroll = preview_line(&self.preview, &result.snippet).map_or(0, |line| line.saturating_sub(PREVIEW_LEAD));
+    }
+
+    /// Open the selected result in `$VISUAL` or `$EDITOR` (default `vi`) at its first line
+    fn open(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(result) = self.list.selected().and_then(|i| self.results.get(i)) else {
+            return Ok(());
+        };
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let mut words = editor.split_whitespace();
+        let program = words.next().context("$EDITOR is empty")?;
+        let mut command = std::process::Command::new(program);
+        command.args(words).arg(format!("+{}", result.start_line.unwrap_or(1))).arg(&result.path);
+
+        ratatui::restore();
+        let status = command.status();
+        *terminal = ratatui::init();
+        terminal.clear()?;
+        match status {
+            Ok(status) if !status.success() => self.status = format!("{} exited with {}", program, status),
+            Ok(_) => {}
+            Err(e) => self.status = format!("Cannot start {}: {}", program, e),
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [top, main, bottom] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+
+        self.render_input(frame, top);
+
+        let items: Vec<ListItem> = self
+            .results
+            .iter()
+            .map(|result| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(result.date.to_string(), Style::new().dim()),
+                    Span::raw(" "),
+                    Span::styled(format!("{:.2}", result.score), Style::new().fg(score_color(result))),
+                    Span::raw(" "),
+                    Span::raw(result.path.display().to_string()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Results ({})", self.total)))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let title = self
+            .list
+            .selected()
+            .and_then(|i| self.results.get(i))
+            .map(|result| match &result.section {
+                Some(section) => format!("{} · {}", result.path.display(), section),
+                None => result.path.display().to_string(),
+            })
+            .unwrap_or_default();
+        // The paragraph is given only what is on screen onwards, so scrolling counts source lines
+        let lines: Vec<Line> = self.preview.iter().skip(self.scroll).map(|line| marked_line(line, &self.query)).collect();
+        let preview = Paragraph::new(lines).block(Block::bordered().title(title)).wrap(Wrap { trim: false });
+        frame.render_widget(preview, preview_area);
+
+        let status = if self.status.is_empty() { HELP.to_string() } else { format!("{} · {}", self.status, HELP) };
+        frame.render_widget(Paragraph::new(status).dim(), bottom);
+    }
+
+    fn render_input(&self, frame: &mut Frame, area: Rect) {
+        let (label, text) = match self.mode {
+            Mode::Edit(field) => (field.label().to_string(), self.input.clone()),
+            Mode::Browse => {
+                let mut filters = Vec::new();
+                if !self.tags.is_empty() {
+                    filters.push(format!("tags: {}", self.tags.join(", ")));
+                }
+                if let Some(during) = &self.during {
+                    filters.push(format!("dates: {}", during));
+                }
+                if self.hybrid {
+                    filters.push("hybrid".to_string());
+                }
+                if self.group_by_doc {
+                    filters.push("by entry".to_string());
+                }
+                let label = if filters.is_empty() { "Query".to_string() } else { format!("Query · {}", filters.join(" · ")) };
+                (label, self.query.clone())
+            }
+        };
+        let editing = matches!(self.mode, Mode::Edit(_));
+        let block = if editing { Block::bordered().title(label).yellow() } else { Block::bordered().title(label) };
+        frame.render_widget(Paragraph::new(text.as_str()).block(block), area);
+        if editing {
+            let x = area.x + 1 + (text.chars().count() as u16).min(area.width.saturating_sub(3));
+            frame.set_cursor_position((x, area.y + 1));
+        }
+    }
+}
+
+/// Same grading as the colored `mdrag search` output
+fn score_color(result: &SearchResult) -> Color {
+    match result.score {
+        _ if result.keyword_match => Color::Cyan,
+        score if score >= 0.7 => Color::Green,
+        score if score >= 0.5 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+/// `line` with the words matching the query in bold yellow
+fn marked_line<'a>(line: &'a str, query: &str) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut copied = 0;
+    for word in matched_words(line, query) {
+        spans.push(Span::raw(&line[copied..word.start]));
+        spans.push(Span::styled(&line[word.clone()], Style::new().yellow().bold()));
+        copied = word.end;
+    }
+    spans.push(Span::raw(&line[copied..]));
+    Line::from(spans)
+}
+
+/// Index of the first preview line holding the snippet's first `**`-marked term
+fn preview_line(lines: &[String], snippet: &str) -> Option<usize> {
+    let term = snippet.split(HIGHLIGHT).nth(1)?.to_lowercase();
+    lines.iter().position(|line| line.to_lowercase().contains(&term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_scrolls_to_the_snippet_term() {
+        let lines: Vec<String> = ["# Monday", "Lunch.", "Met Ana about the release."].iter().map(|s| s.to_string()).collect();
+        assert_eq!(preview_line(&lines, "...Met **Ana** about the **release**."), Some(2));
+        assert_eq!(preview_line(&lines, "Nothing marked"), None);
+
+        let line = marked_line("Met Ana about the release.", "ana release");
+        let styled: Vec<&str> = line.spans.iter().filter(|s| s.style != Style::new()).map(|s| s.content.as_ref()).collect();
+        assert_eq!(styled, vec!["Ana", "release"]);
+    }
+}
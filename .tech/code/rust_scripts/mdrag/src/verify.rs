@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use arrow::datatypes::{DataType, Schema};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use md_rag_core::embeddings::{model_dimension, DEFAULT_MODEL};
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+use md_rag_core::schema::{db_path, schema_model, schema_skips_code_blocks, DIMENSION_METADATA_KEY};
+
+use crate::GlobalArgs;
+use crate::index::{self, entry_hash, indexed_hashes, orphaned_paths, IndexArgs};
+
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    /// Repair drift with an incremental index pass: re-embed stale and new entries, drop missing ones
+    #[arg(long)]
+    fix: bool,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Where the index and the journal disagree
+#[derive(Debug, Default, Serialize)]
+struct Drift {
+    /// Indexed entries whose file was deleted, renamed or is now excluded
+    missing: Vec<String>,
+    /// Entries whose file changed since they were indexed
+    stale: Vec<String>,
+    /// Journal files that aren't in the index yet
+    unindexed: Vec<String>,
+    /// Why the stored vectors don't fit the model searches embed queries with
+    dimension_mismatch: Option<String>,
+}
+
+impl Drift {
+    fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.stale.is_empty() && self.unindexed.is_empty() && self.dimension_mismatch.is_none()
+    }
+}
+
+pub async fn run(global: &GlobalArgs, args: VerifyArgs) -> Result<()> {
+    let lance_path = db_path(&global.lance_dir);
+    if !lance_path.exists() {
+        anyhow::bail!("No index at {}. Run `mdrag index` first.", lance_path.display());
+    }
+    let db = lancedb::connect(lance_path.to_str().unwrap()).execute().await?;
+    let table = db
+        .open_table(&global.table)
+        .execute()
+        .await
+        .with_context(|| format!("No collection '{}'. Run `mdrag index` to create it", global.table))?;
+
+    // Hash the journal the way `mdrag index` does, with the same filters, template config and
+    // code block setting
+    let schema = table.schema().await?;
+    let filter = index::hashing_filter(global, schema_skips_code_blocks(&schema));
+    let dates = global.config.entry_dates()?;
+    let mut current = HashMap::new();
+    let mut filters = Vec::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        for doc in scan_journal_directory(dir, &paths, None, &dates, default_jobs(), false, &|_| {})? {
+            current.insert(doc.path.clone(), entry_hash(&filter, &doc));
+        }
+        filters.push(paths);
+    }
+
+    let indexed = indexed_hashes(&table).await?;
+    let mut drift = compare(&indexed, &current, |path| !filters.iter().any(|f| f.is_included(path)));
+
+    // The configured model is the one `mdrag index` would embed with; else the one the index names
+    let model = global.config.model.as_deref().unwrap_or(schema_model(&schema).unwrap_or(DEFAULT_MODEL));
+    let dimension = model_dimension(global.backend, model)?;
+    drift.dimension_mismatch = dimension_mismatch(&schema, model, dimension);
+
+    match args.format {
+        OutputFormat::Text => print_drift(&drift),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&drift)?),
+    }
+
+    if drift.is_clean() {
+        return Ok(());
+    }
+    if !args.fix {
+        anyhow::bail!("Index has drifted from the journal. Run `mdrag verify --fix` to repair it.");
+    }
+    if let Some(problem) = &drift.dimension_mismatch {
+        anyhow::bail!("{}. Run `mdrag index --rebuild` to re-embed every entry.", problem);
+    }
+
+    eprintln!("\n🔧 Repairing with an incremental index pass...");
+    match args.format {
+        // Status to stderr, so stdout holds only the drift report
+        OutputFormat::Json => index::update(global, &mut None).await.map(|_| ()),
+        OutputFormat::Text => index::run(global, IndexArgs::default()).await,
+    }
+}
+
+/// Drift between what the index holds (`indexed`) and the journal (`current`), both path to entry hash
+fn compare(
+    indexed: &HashMap<String, String>,
+    current: &HashMap<String, String>,
+    is_excluded: impl Fn(&std::path::Path) -> bool,
+) -> Drift {
+    let scanned: HashSet<&str> = current.keys().map(String::as_str).collect();
+    let mut drift = Drift {
+        missing: orphaned_paths(indexed, &scanned, is_excluded),
+        ..Default::default()
+    };
+
+    for (path, hash) in current {
+        match indexed.get(path) {
+            Some(indexed_hash) if indexed_hash != hash => drift.stale.push(path.clone()),
+            Some(_) => {}
+            None => drift.unindexed.push(path.clone()),
+        }
+    }
+    drift.stale.sort();
+    drift.unindexed.sort();
+    drift
+}
+
+/// Compare the width of the embedding column with the dimension `model` produces, and with the
+/// dimension the index records
+fn dimension_mismatch(schema: &Schema, model: &str, dimension: usize) -> Option<String> {
+    let stored = match schema.field_with_name("embedding").ok()?.data_type() {
+        DataType::FixedSizeList(_, size) => *size as usize,
+        other => return Some(format!("Embedding column has unexpected type {}", other)),
+    };
+    if stored != dimension {
+        return Some(format!("Embeddings have {} dimensions but {} produces {}", stored, model, dimension));
+    }
+    let recorded = schema.metadata().get(DIMENSION_METADATA_KEY)?;
+    (recorded.parse() != Ok(stored)).then(|| {
+        format!("Embeddings have {} dimensions but the index records {}", stored, recorded)
+    })
+}
+
+fn print_drift(drift: &Drift) {
+    let sections = [
+        ("🗑️  Missing from disk or excluded", &drift.missing),
+        ("✏️  Modified since indexed", &drift.stale),
+        ("🆕 Not indexed yet", &drift.unindexed),
+    ];
+    for (title, paths) in sections {
+        if paths.is_empty() {
+            continue;
+        }
+        println!("{} ({}):", title, paths.len());
+        for path in paths {
+            println!("  {}", path);
+        }
+    }
+    if let Some(problem) = &drift.dimension_mismatch {
+        println!("📐 {}", problem);
+    }
+    if drift.is_clean() {
+        println!("✅ Index matches the journal");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use md_rag_core::metric::Metric;
+    use md_rag_core::schema::documents_schema;
+
+    #[test]
+    fn test_compare_finds_drift() {
+        let map = |entries: &[(&str, &str)]| -> HashMap<String, String> {
+            entries.iter().map(|(p, h)| (p.to_string(), h.to_string())).collect()
+        };
+        let indexed = map(&[("journal/same.md", "a"), ("journal/edited.md", "b"), ("journal/gone.md", "c")]);
+        let current = map(&[("journal/same.md", "a"), ("journal/edited.md", "B"), ("journal/new.md", "d")]);
+        let drift = compare(&indexed, &current, |_| false);
+        assert_eq!(drift.missing, vec!["journal/gone.md"]);
+        assert_eq!(drift.stale, vec!["journal/edited.md"]);
+        assert_eq!(drift.unindexed, vec!["journal/new.md"]);
+        assert!(!drift.is_clean());
+
+        let schema = documents_schema("m", 4, true, Metric::Cosine, &[], false, false);
+        assert_eq!(dimension_mismatch(&schema, "m", 4), None);
+        assert_eq!(
+            dimension_mismatch(&schema, "BAAI/bge-base-en-v1.5", 768).as_deref(),
+            Some("Embeddings have 4 dimensions but BAAI/bge-base-en-v1.5 produces 768"),
+        );
+    }
+}
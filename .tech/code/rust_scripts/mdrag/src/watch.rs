@@ -0,0 +1,94 @@
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long the journal must be quiet before a batch of changes is reported.
+/// Editors often write a file several times per save.
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Watches the journal directories for changes to markdown files
+pub struct JournalWatcher {
+    // Dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+}
+
+impl JournalWatcher {
+    pub fn new(journal_dirs: &[PathBuf]) -> Result<Self> {
+        let (tx, events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // The receiver only goes away when we are shutting down
+            let _ = tx.send(event);
+        })?;
+        for dir in journal_dirs {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self { _watcher: watcher, events })
+    }
+
+    /// Wait for the next burst of markdown changes and return the paths involved.
+    ///
+    /// Returns `None` once the watcher has stopped.
+    pub async fn next_change(&mut self) -> Option<Vec<PathBuf>> {
+        let mut changed = BTreeSet::new();
+
+        // Block until something relevant happens
+        while changed.is_empty() {
+            let event = self.events.recv().await?;
+            collect_markdown_changes(event, &mut changed);
+        }
+
+        // Then keep absorbing events until the directory has been quiet for a while
+        while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {
+            collect_markdown_changes(event, &mut changed);
+        }
+
+        Some(changed.into_iter().collect())
+    }
+}
+
+fn collect_markdown_changes(event: notify::Result<Event>, changed: &mut BTreeSet<PathBuf>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            eprintln!("  ⚠️  Watch error: {}", e);
+            return;
+        }
+    };
+
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+        return;
+    }
+
+    changed.extend(
+        event.paths
+            .into_iter()
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("md")),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind};
+
+    #[test]
+    fn test_only_markdown_changes_are_collected() {
+        let mut changed = BTreeSet::new();
+
+        let create = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("journal/2025/07/21.md"))
+            .add_path(PathBuf::from("journal/2025/07/.21.md.swp"));
+        collect_markdown_changes(Ok(create), &mut changed);
+
+        let access = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("journal/2025/07/22.md"));
+        collect_markdown_changes(Ok(access), &mut changed);
+
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec![PathBuf::from("journal/2025/07/21.md")]);
+    }
+}
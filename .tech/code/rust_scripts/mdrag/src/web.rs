@@ -0,0 +1,232 @@
+use anyhow::Result;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Local;
+use pulldown_cmark::{html, Event, Options, Parser};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use md_rag_core::date_expr::parse_date_range;
+use md_rag_core::frontmatter::split_frontmatter;
+use md_rag_core::search::SearchQuery;
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::search::Searcher;
+use crate::GlobalArgs;
+
+/// The whole interface: one page of HTML, CSS and script compiled into the binary
+const INDEX_HTML: &str = include_str!("web/index.html");
+
+#[derive(clap::Args, Debug)]
+pub struct WebArgs {
+    /// Address to listen on; the journal is private, so only this machine by default
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8765)]
+    port: u16,
+}
+
+/// Results per page when the request doesn't say
+const DEFAULT_LIMIT: usize = 20;
+
+/// Largest page a request can ask for
+const MAX_LIMIT: usize = 100;
+
+#[derive(Clone)]
+struct AppState {
+    searcher: Arc<Mutex<Searcher>>,
+    filter: Arc<TemplateFilter>,
+    /// Canonical journal directories; entries are only served from inside them
+    journal_dirs: Arc<Vec<PathBuf>>,
+}
+
+/// `GET /api/search` parameters
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    /// `YYYY-MM-DD` or a date expression, as `mdrag search --after` takes
+    #[serde(default)]
+    after: Option<String>,
+    #[serde(default)]
+    before: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    hybrid: bool,
+    #[serde(default)]
+    group_by_doc: bool,
+}
+
+/// `GET /api/entry` parameters
+#[derive(Debug, Deserialize)]
+struct EntryParams {
+    /// As search results show it
+    path: String,
+}
+
+#[derive(Serialize)]
+struct EntryOutput {
+    path: String,
+    /// The cleaned entry rendered as HTML
+    html: String,
+}
+
+/// `{"error": "..."}` with a status code
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Serve the search page and its JSON API over HTTP until Ctrl+C
+pub async fn serve(global: &GlobalArgs, args: WebArgs) -> Result<()> {
+    let searcher = Searcher::new(global).await?;
+    let state = AppState {
+        searcher: Arc::new(Mutex::new(searcher)),
+        filter: Arc::new(TemplateFilter::with_config(&global.config.template)),
+        journal_dirs: Arc::new(global.journal_dirs.iter().filter_map(|dir| dir.canonicalize().ok()).collect()),
+    };
+
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/api/search", get(search))
+        .route("/api/entry", get(entry))
+        .with_state(state);
+    let app = check_host(app, &args.host, args.port);
+
+    let listener = tokio::net::TcpListener::bind((args.host.as_str(), args.port)).await?;
+    eprintln!("🌐 Serving '{}' on http://{} (Ctrl+C to stop)", global.table, listener.local_addr()?);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// Refuse requests whose `Host` isn't this server, so a web page can't read the journal by
+/// rebinding its own domain to 127.0.0.1. Any name goes when listening on all interfaces
+pub fn check_host(app: Router, host: &str, port: u16) -> Router {
+    let Some(allowed) = allowed_hosts(host, port) else {
+        return app;
+    };
+    app.layer(middleware::from_fn(move |request: Request, next: Next| {
+        let allowed = allowed.clone();
+        async move {
+            let host = request.headers().get(header::HOST).and_then(|host| host.to_str().ok());
+            if host.is_some_and(|host| allowed.iter().any(|a| a == host)) {
+                next.run(request).await
+            } else {
+                ApiError(StatusCode::FORBIDDEN, "Unexpected Host header".to_string()).into_response()
+            }
+        }
+    }))
+}
+
+/// `Host` headers a browser sends for this server; None when listening on all interfaces
+fn allowed_hosts(host: &str, port: u16) -> Option<Arc<Vec<String>>> {
+    if matches!(host, "0.0.0.0" | "::" | "[::]") {
+        return None;
+    }
+    let names = [host, "localhost", "127.0.0.1", "[::1]"];
+    Some(Arc::new(names.iter().map(|name| format!("{}:{}", name, port)).collect()))
+}
+
+async fn index() -> impl IntoResponse {
+    ([(header::CACHE_CONTROL, "no-cache")], Html(INDEX_HTML))
+}
+
+async fn search(State(state): State<AppState>, Query(params): Query<SearchParams>) -> Result<Response, ApiError> {
+    if params.q.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Missing query".to_string()));
+    }
+    let today = Local::now().date_naive();
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| parse_date_range(s, today))
+            .transpose()
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:#}", e)))
+    };
+    let after = parse(&params.after)?.map(|(start, _)| start);
+    let before = parse(&params.before)?.map(|(_, end)| end);
+
+    let request = SearchQuery {
+        query: params.q,
+        after,
+        before,
+        limit: params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+        offset: params.offset,
+        hybrid: params.hybrid,
+        group_by_doc: params.group_by_doc,
+        ..Default::default()
+    };
+    // One search at a time, like the daemon; they take milliseconds once the model is loaded
+    let page = state
+        .searcher
+        .lock()
+        .await
+        .search(&request)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    Ok(Json(page).into_response())
+}
+
+async fn entry(State(state): State<AppState>, Query(params): Query<EntryParams>) -> Result<Json<EntryOutput>, ApiError> {
+    let not_found = || ApiError(StatusCode::NOT_FOUND, format!("No journal entry at {}", params.path));
+    let path = Path::new(&params.path).canonicalize().map_err(|_| not_found())?;
+    // Only markdown inside the journal, whatever `..` or links the path holds
+    let inside = state.journal_dirs.iter().any(|dir| path.starts_with(dir));
+    if !inside || path.extension().is_none_or(|ext| ext != "md") {
+        return Err(not_found());
+    }
+    let content = tokio::fs::read_to_string(&path).await.map_err(|_| not_found())?;
+    let (_, body) = split_frontmatter(&content);
+    Ok(Json(EntryOutput { path: params.path.clone(), html: render_markdown(&state.filter.clean_content(body)) }))
+}
+
+/// Markdown to HTML, with any raw HTML in the entry shown as text rather than run
+fn render_markdown(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(text) | Event::InlineHtml(text) => Event::Text(text),
+        event => event,
+    });
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_escapes_raw_html() {
+        let rendered = render_markdown("## Evening\n- **Ran** 5k <script>alert(1)</script>");
+        assert!(rendered.starts_with("<h2>Evening</h2>"));
+        assert!(rendered.contains("<strong>Ran</strong> 5k &lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!rendered.contains("<script>"));
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let allowed = allowed_hosts("127.0.0.1", 8765).unwrap();
+        assert!(allowed.contains(&"localhost:8765".to_string()));
+        assert!(!allowed.contains(&"evil.example:8765".to_string()));
+        assert!(allowed_hosts("0.0.0.0", 8765).is_none());
+    }
+}
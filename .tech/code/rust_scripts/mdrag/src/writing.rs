@@ -0,0 +1,229 @@
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use md_rag_core::scan::{default_jobs, scan_journal_directory};
+use md_rag_core::schema::days_to_date;
+use md_rag_core::template_filter::TemplateFilter;
+
+use crate::chart;
+use crate::habits::streaks;
+use crate::GlobalArgs;
+
+/// Words counted for text before an entry's first section heading
+const NO_SECTION: &str = "(no section)";
+
+#[derive(clap::Args, Debug)]
+pub struct WritingStatsArgs {
+    /// Only entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Words a day needs to count towards a writing streak
+    #[arg(long, default_value_t = 1)]
+    min_words: usize,
+
+    /// Sections to list, most written first
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_enum)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct WritingStats {
+    total_words: usize,
+    /// Days with an entry
+    days: usize,
+    /// Days with at least `--min-words`
+    writing_days: usize,
+    avg_words_per_day: f64,
+    current_streak: usize,
+    longest_streak: usize,
+    by_weekday: Vec<WeekdayWords>,
+    sections: Vec<SectionWords>,
+    daily: Vec<DayWords>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct DayWords {
+    date: NaiveDate,
+    words: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct WeekdayWords {
+    weekday: String,
+    days: usize,
+    avg_words: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct SectionWords {
+    section: String,
+    words: usize,
+    /// Entries with text in the section
+    entries: usize,
+}
+
+pub fn run(global: &GlobalArgs, args: WritingStatsArgs) -> Result<()> {
+    // Read straight from the journal, so this works without an index
+    let dates = global.config.entry_dates()?;
+    let filter = TemplateFilter::with_config(&global.config.template);
+    let mut daily: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut sections: HashMap<String, (usize, usize)> = HashMap::new();
+    for dir in &global.journal_dirs {
+        let paths = global.config.path_filter(dir, &[], &[])?;
+        for doc in scan_journal_directory(dir, &paths, args.since.as_deref(), &dates, default_jobs(), false, &|_| {})? {
+            let cleaned = filter.clean_content(&doc.content);
+            let mut words = 0;
+            for (section, count) in section_words(&cleaned) {
+                let totals = sections.entry(section).or_default();
+                totals.0 += count;
+                totals.1 += 1;
+                words += count;
+            }
+            *daily.entry(days_to_date(doc.date)).or_default() += words;
+        }
+    }
+
+    let stats = writing_stats(&daily, sections, args.min_words, args.top, Local::now().date_naive());
+    match args.format {
+        OutputFormat::Text => print_report(&stats),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+    }
+    Ok(())
+}
+
+/// Words per section of a cleaned entry, by the text of the nearest heading below the entry's
+/// `#` title, in order of appearance; headings themselves aren't counted and empty sections are left out
+fn section_words(text: &str) -> Vec<(String, usize)> {
+    let mut sections: Vec<(String, usize)> = Vec::new();
+    let mut section = NO_SECTION;
+    for line in text.lines() {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if level > 0 && line[level..].starts_with(' ') {
+            if level > 1 {
+                section = line[level..].trim();
+            }
+            continue;
+        }
+        let words = line.split_whitespace().count();
+        if words == 0 {
+            continue;
+        }
+        match sections.iter_mut().find(|(name, _)| name == section) {
+            Some((_, count)) => *count += words,
+            None => sections.push((section.to_string(), words)),
+        }
+    }
+    sections
+}
+
+fn writing_stats(
+    daily: &BTreeMap<NaiveDate, usize>,
+    sections: HashMap<String, (usize, usize)>,
+    min_words: usize,
+    top: usize,
+    today: NaiveDate,
+) -> WritingStats {
+    let total_words: usize = daily.values().sum();
+    let writing_days: Vec<NaiveDate> = daily.iter().filter(|(_, words)| **words >= min_words).map(|(date, _)| *date).collect();
+    let (current_streak, longest_streak) = streaks(&writing_days, today);
+
+    let mut weekdays = [(0usize, 0usize); 7];
+    for (date, words) in daily {
+        let slot = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+        slot.0 += 1;
+        slot.1 += words;
+    }
+    let by_weekday = weekdays
+        .iter()
+        .enumerate()
+        .map(|(i, (days, words))| WeekdayWords {
+            weekday: Weekday::try_from(i as u8).unwrap().to_string(),
+            days: *days,
+            avg_words: *words as f64 / (*days).max(1) as f64,
+        })
+        .collect();
+
+    let mut sections: Vec<SectionWords> =
+        sections.into_iter().map(|(section, (words, entries))| SectionWords { section, words, entries }).collect();
+    sections.sort_by(|a, b| b.words.cmp(&a.words).then_with(|| a.section.cmp(&b.section)));
+    sections.truncate(top);
+
+    WritingStats {
+        total_words,
+        days: daily.len(),
+        writing_days: writing_days.len(),
+        avg_words_per_day: total_words as f64 / daily.len().max(1) as f64,
+        current_streak,
+        longest_streak,
+        by_weekday,
+        sections,
+        daily: daily.iter().map(|(date, words)| DayWords { date: *date, words: *words }).collect(),
+    }
+}
+
+fn print_report(stats: &WritingStats) {
+    let (Some(first), Some(last)) = (stats.daily.first(), stats.daily.last()) else {
+        eprintln!("No entries found");
+        return;
+    };
+    println!("✍️  {} words in {} entries, {} to {}", stats.total_words, stats.days, first.date, last.date);
+    println!("   {:.0} words per entry day; wrote on {} days", stats.avg_words_per_day, stats.writing_days);
+    println!("   Current streak {} days, longest {} days", stats.current_streak, stats.longest_streak);
+
+    let points: Vec<(NaiveDate, f64)> = stats.daily.iter().map(|day| (day.date, day.words as f64)).collect();
+    let line = chart::sparkline(&chart::resample(&chart::daily_series(&points), chart::chart_width(3)));
+    println!("\n   {}", line);
+
+    println!("\nAverage words by weekday:");
+    for weekday in &stats.by_weekday {
+        println!("   {}  {:>6.0}  ({} days)", weekday.weekday, weekday.avg_words, weekday.days);
+    }
+
+    if !stats.sections.is_empty() {
+        println!("\nMost written sections:");
+        for section in &stats.sections {
+            println!("   {:>7} words  {:>4} entries  {}", section.words, section.entries, section.section);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writing_stats() {
+        let text = "# Monday\nSlept badly.\n## Work\nShipped the parser today.\n\n### Notes\n#tag only\n## Work\nMore work.\n";
+        assert_eq!(
+            section_words(text),
+            vec![("(no section)".to_string(), 2), ("Work".to_string(), 6), ("Notes".to_string(), 2)],
+        );
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+        // 2025-07-07 and 2025-07-14 are Mondays
+        let daily = BTreeMap::from([(day(7), 300), (day(8), 0), (day(9), 120), (day(10), 80), (day(14), 100)]);
+        let sections = HashMap::from([("Work".to_string(), (400, 3)), ("Gratitude".to_string(), (150, 4)), ("Notes".to_string(), (50, 1))]);
+        let stats = writing_stats(&daily, sections, 100, 2, day(15));
+
+        assert_eq!((stats.total_words, stats.days, stats.writing_days), (600, 5, 3));
+        assert_eq!((stats.current_streak, stats.longest_streak), (1, 1));
+        assert_eq!(stats.avg_words_per_day, 120.0);
+        assert_eq!(stats.by_weekday[0], WeekdayWords { weekday: "Mon".to_string(), days: 2, avg_words: 200.0 });
+        assert_eq!(stats.by_weekday[6].days, 0);
+        let sections: Vec<_> = stats.sections.iter().map(|s| s.section.as_str()).collect();
+        assert_eq!(sections, vec!["Work", "Gratitude"]);
+    }
+}
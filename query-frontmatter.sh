@@ -5,4 +5,4 @@
 SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" &> /dev/null && pwd )"
 
 # Run from project root so journal directory is found
-cd "$SCRIPT_DIR" && .tech/code/rust_scripts/frontmatter_query/target/release/frontmatter-query "$@"
\ No newline at end of file
+cd "$SCRIPT_DIR" && .tech/code/rust_scripts/target/release/mdrag frontmatter "$@"
@@ -2,4 +2,4 @@
 # Search the journal with the Rust RAG system
 
 # Run from project root to ensure relative paths work correctly
-.tech/code/rust_scripts/rag_search/target/release/rag-search "$@"
\ No newline at end of file
+.tech/code/rust_scripts/target/release/mdrag search "$@"
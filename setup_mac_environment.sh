@@ -58,35 +58,23 @@ fi
 # --- 3. Build Rust Tools ---
 print_message "Building Rust CLI tools..."
 
-# Build frontmatter query tool
-FRONTMATTER_DIR=".tech/code/rust_scripts/frontmatter_query"
-if [ -d "$FRONTMATTER_DIR" ]; then
-  echo "Building frontmatter query tool..."
-  (cd "$FRONTMATTER_DIR" && cargo build --release)
-  if [ $? -eq 0 ]; then
-    echo "✅ Frontmatter query tool built successfully"
-  else
-    echo "❌ Failed to build frontmatter query tool"
-  fi
-else
-  echo "WARNING: $FRONTMATTER_DIR not found"
-fi
-
-# Build RAG search tools
-RAG_DIR=".tech/code/rust_scripts/rag_search"
-if [ -d "$RAG_DIR" ]; then
-  echo "Building RAG search tools..."
+# Build all tools from the shared workspace
+RUST_DIR=".tech/code/rust_scripts"
+if [ -d "$RUST_DIR" ]; then
+  echo "Building journal RAG tools..."
   echo "Note: First build will download embedding models (~400MB)"
-  (cd "$RAG_DIR" && cargo build --release)
+  (cd "$RUST_DIR" && cargo build --release)
   if [ $? -eq 0 ]; then
-    echo "✅ RAG search tools built successfully"
-    echo "   - rag-index: For indexing journal entries"
-    echo "   - rag-search: For semantic search"
+    echo "✅ Rust tools built successfully"
+    echo "   - mdrag index: For indexing journal entries"
+    echo "   - mdrag search: For semantic search"
+    echo "   - mdrag frontmatter: For metadata analysis"
+    echo "   - mdrag serve: MCP server for AI assistants"
   else
-    echo "❌ Failed to build RAG search tools"
+    echo "❌ Failed to build Rust tools"
   fi
 else
-  echo "WARNING: $RAG_DIR not found"
+  echo "WARNING: $RUST_DIR not found"
 fi
 
 # --- 4. Create convenience scripts ---